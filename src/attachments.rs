@@ -1,9 +1,13 @@
+use crate::Error;
 use poise::serenity_prelude::Attachment;
 use std::path::Path;
 
 pub const MAX_ATTACHMENT_BYTES: u64 = 25 * 1024 * 1024;
 pub const MAX_TRANSFORMED_BYTES: usize = MAX_ATTACHMENT_BYTES as usize;
 pub const MAX_IMAGE_PIXELS: u64 = 16_000_000;
+pub const MAX_SHEET_ROWS: usize = 100_000;
+pub const MAX_SHEET_COLUMNS: usize = 1_000;
+pub const MAX_SHEET_CELLS: u64 = 2_000_000;
 const DEFAULT_FILENAME: &str = "file";
 const MAX_FILENAME_LEN: usize = 120;
 
@@ -19,6 +23,25 @@ pub fn validate_attachment_size(file: &Attachment) -> Result<(), String> {
     Ok(())
 }
 
+pub fn validate_combined_attachment_size(files: &[Attachment]) -> Result<(), String> {
+    let total: u64 = files.iter().map(|file| u64::from(file.size)).sum();
+    if total > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "Uploads larger than {} MiB in total are rejected to avoid exhausting bot resources.",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn download_attachment(file: &Attachment) -> Result<Vec<u8>, Error> {
+    validate_attachment_size(file).map_err(Error::from)?;
+    file.download()
+        .await
+        .map_err(|e| Error::from(format!("Failed to download `{}`: {}", file.filename, e)))
+}
+
 pub fn validate_output_size(size: usize, label: &str) -> Result<(), String> {
     if size > MAX_TRANSFORMED_BYTES {
         return Err(format!(
@@ -70,10 +93,28 @@ pub fn validate_image_dimensions(width: u32, height: u32) -> Result<(), String>
     Ok(())
 }
 
+pub fn validate_sheet_dimensions(rows: usize, columns: usize) -> Result<(), String> {
+    if rows > MAX_SHEET_ROWS || columns > MAX_SHEET_COLUMNS {
+        return Err(format!(
+            "Sheets above {} rows or {} columns are rejected to avoid excessive memory use.",
+            MAX_SHEET_ROWS, MAX_SHEET_COLUMNS
+        ));
+    }
+    if rows as u64 * columns as u64 > MAX_SHEET_CELLS {
+        return Err(format!(
+            "Sheets above {} cells are rejected to avoid excessive memory use.",
+            MAX_SHEET_CELLS
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
         MAX_ATTACHMENT_BYTES, sanitize_filename, validate_image_dimensions, validate_output_size,
+        validate_sheet_dimensions,
     };
 
     #[test]
@@ -94,4 +135,11 @@ mod tests {
         assert!(validate_image_dimensions(2000, 2000).is_ok());
         assert!(validate_image_dimensions(5000, 5000).is_err());
     }
+
+    #[test]
+    fn sheet_dimension_validation_rejects_large_sheets() {
+        assert!(validate_sheet_dimensions(50_000, 40).is_ok());
+        assert!(validate_sheet_dimensions(200_000, 1).is_err());
+        assert!(validate_sheet_dimensions(10_000, 500).is_err());
+    }
 }
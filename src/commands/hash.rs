@@ -1,4 +1,8 @@
 use crate::attachments::validate_attachment_size;
+use crate::conversion::image::decode_image;
+use crate::imaging::similarity::{
+    PerceptualHash, SIMILAR_DISTANCE, hash_distance, perceptual_hash,
+};
 use crate::utils::format_file_size;
 use crate::{Context, Error};
 use poise::serenity_prelude as serenity;
@@ -17,12 +21,44 @@ pub enum HashAlgorithm {
     Md5,
     #[name = "BLAKE3"]
     Blake3,
+    #[name = "aHash (perceptual, images only)"]
+    AHash,
+    #[name = "dHash (perceptual, images only)"]
+    DHash,
+    #[name = "pHash (perceptual, images only)"]
+    PHash,
+}
+
+impl HashAlgorithm {
+    /// The perceptual hash behind this algorithm, if it is one
+    const fn perceptual(&self) -> Option<PerceptualHash> {
+        match self {
+            Self::AHash => Some(PerceptualHash::Average),
+            Self::DHash => Some(PerceptualHash::Difference),
+            Self::PHash => Some(PerceptualHash::Perceptual),
+            Self::Sha256 | Self::Sha1 | Self::Md5 | Self::Blake3 => None,
+        }
+    }
+}
+
+/// Decode the data as an image and compute a hex-encoded perceptual hash.
+fn compute_perceptual_hash(
+    data: &[u8],
+    kind: PerceptualHash,
+) -> Result<(String, &'static str), Error> {
+    let img = decode_image(data)?;
+    Ok((
+        format!("{:016x}", perceptual_hash(&img, kind)),
+        kind.label(),
+    ))
 }
 
 /// Compute a hash of the given data using the specified algorithm.
 /// Returns the hex-encoded hash string and the algorithm display name.
-fn compute_hash(data: &[u8], algorithm: &HashAlgorithm) -> (String, &'static str) {
-    match algorithm {
+///
+/// Perceptual hashes fail for files that are not images.
+fn compute_hash(data: &[u8], algorithm: &HashAlgorithm) -> Result<(String, &'static str), Error> {
+    Ok(match algorithm {
         HashAlgorithm::Sha256 => {
             let mut hasher = Sha256::new();
             hasher.update(data);
@@ -42,7 +78,20 @@ fn compute_hash(data: &[u8], algorithm: &HashAlgorithm) -> (String, &'static str
             let hash = blake3::hash(data);
             (hash.to_hex().to_string(), "BLAKE3")
         }
-    }
+        HashAlgorithm::AHash => compute_perceptual_hash(data, PerceptualHash::Average)?,
+        HashAlgorithm::DHash => compute_perceptual_hash(data, PerceptualHash::Difference)?,
+        HashAlgorithm::PHash => compute_perceptual_hash(data, PerceptualHash::Perceptual)?,
+    })
+}
+
+/// Send an error embed when a hash could not be computed.
+async fn send_hash_error(ctx: Context<'_>, error: &Error) -> Result<(), Error> {
+    let embed = CreateEmbed::new()
+        .title("❌ Hashing Failed")
+        .description(error.to_string())
+        .color(0xff4444);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
 }
 
 /// Download an attachment, returning its bytes or sending an error embed on failure.
@@ -84,7 +133,10 @@ pub async fn hash(
     };
 
     let (hash_result, algorithm_name) =
-        tokio::task::spawn_blocking(move || compute_hash(&file_data, &algorithm)).await?;
+        match tokio::task::spawn_blocking(move || compute_hash(&file_data, &algorithm)).await? {
+            Ok(result) => result,
+            Err(e) => return send_hash_error(ctx, &e).await,
+        };
 
     let embed = CreateEmbed::new()
         .title("🔐 File Hash Generated")
@@ -117,10 +169,30 @@ pub async fn verify_hash(
         None => return Ok(()),
     };
 
+    let perceptual = algorithm.perceptual().is_some();
     let (actual_hash, algorithm_name) =
-        tokio::task::spawn_blocking(move || compute_hash(&file_data, &algorithm)).await?;
+        match tokio::task::spawn_blocking(move || compute_hash(&file_data, &algorithm)).await? {
+            Ok(result) => result,
+            Err(e) => return send_hash_error(ctx, &e).await,
+        };
 
-    let matches = actual_hash == expected_hash;
+    // Perceptual hashes shift slightly on re-encode, so they match within a distance
+    let distance = if perceptual {
+        u64::from_str_radix(&expected_hash, 16)
+            .ok()
+            .zip(u64::from_str_radix(&actual_hash, 16).ok())
+            .map(|(expected, actual)| hash_distance(expected, actual))
+    } else {
+        None
+    };
+    let matches = match distance {
+        Some(distance) => distance <= SIMILAR_DISTANCE,
+        None => actual_hash == expected_hash,
+    };
+    let footer = match distance {
+        Some(distance) => format!("Algorithm: {} • Distance: {}/64", algorithm_name, distance),
+        None => format!("Algorithm: {}", algorithm_name),
+    };
     let embed = CreateEmbed::new()
         .title(if matches {
             "✅ Valid Checksum"
@@ -129,10 +201,7 @@ pub async fn verify_hash(
         })
         .field("Expected Hash", format!("```{}```", expected_hash), true)
         .field("Actual Hash", format!("```{}```", actual_hash), true)
-        .footer(serenity::CreateEmbedFooter::new(format!(
-            "Algorithm: {}",
-            algorithm_name
-        )))
+        .footer(serenity::CreateEmbedFooter::new(footer))
         .color(if matches { 0x27ae60 } else { 0xff4444 });
 
     ctx.send(poise::CreateReply::default().embed(embed)).await?;
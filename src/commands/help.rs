@@ -6,6 +6,7 @@ use poise::serenity_prelude as serenity;
 enum HelpPage {
     Overview,
     Conversion,
+    Images,
     Encryption,
     Encoding,
     Compression,
@@ -17,6 +18,7 @@ impl HelpPage {
         match custom_id {
             "help_overview" => Some(Self::Overview),
             "help_conversion" => Some(Self::Conversion),
+            "help_images" => Some(Self::Images),
             "help_encryption" => Some(Self::Encryption),
             "help_encoding" => Some(Self::Encoding),
             "help_compression" => Some(Self::Compression),
@@ -39,6 +41,7 @@ impl HelpPage {
                         "Convert documents and images between formats",
                         false,
                     )
+                    .field("🖼️ Image Tools", "Resize, compress and edit images", false)
                     .field(
                         "🔒 Encryption",
                         "Secure your files with encryption/decryption",
@@ -63,10 +66,42 @@ impl HelpPage {
                     .title("Conversia Help - Conversion")
                     .description("Convert your files between different formats")
                     .color(0x00D166)  // Green
-                    .field("/convert_document", "Convert documents to formats like PDF, Markdown, HTML, and Word.\n• Supports: MD, HTML, PDF, TEX, DOCX, RTF, ODT, EPUB", false)
-                    .field("/convert_image", "Convert images between formats.\n• Supports: JPG, PNG, WEBP, GIF, BMP, TIFF", false)
+                    .field("/convert_document", "Convert documents to formats like PDF, Markdown, HTML, and Word.\n• Supports: MD, HTML, PDF, TEX, DOCX, RTF, ODT, EPUB, RST, AsciiDoc, Org, Textile, MediaWiki, DokuWiki, Jira, TXT, IPYNB\n• Slides: PowerPoint, Beamer PDF and reveal.js\n• Paste Markdown with `text`, or set `input_format` for .txt files\n• Markdown to HTML still works without pandoc via the built-in `renderer`\n• Upload a ZIP with images and a .bib file; pick the document with `main_file`\n• Options: table of contents, numbered sections, title/author, PDF engine, paper size, margins, highlight style and a reference .docx/.odt/.pptx", false)
+                    .field("/convert_image", "Convert images between formats.\n• Output: JPG, PNG, WEBP, GIF, BMP, TIFF, AVIF, ICO, QOI, TGA, PPM\n• Also reads SVG, HDR and EXR (tone-mapped)\n• `colors` reduces the palette, with optional dithering", false)
+                    .field("/images_to_pdf", "Combine up to 10 images into one PDF, one per page.\n• Page size, margins and fit/fill/stretch\n• JPEG scans are embedded without re-compression", false)
+                    .field("/pdf_to_images", "Render PDF pages as images.\n• Page range (e.g. `1-3,5`) and DPI\n• More than 10 pages are returned as a ZIP", false)
+                    .field("/pdf", "PDF tools as subcommands.\n• `merge` up to 10 PDFs, `split` into pages or ranges (ZIP)\n• `extract` or reorder pages, `delete` pages, `rotate` pages\n• `info` shows pages, size and metadata; `set_info` edits title, author, subject and keywords", false)
+                    .field("/render", "Render Markdown, code or text to a PNG image to share snippets.\n• Syntax highlighting by detected language, with line numbers\n• Light, dark and Solarized themes, font size and width", false)
+                    .field("/extract_text", "Extract the plain text of a PDF, DOCX, ODT or EPUB document.\n• Short text is shown inline, longer text is attached as a .txt file\n• Page and section separators, plus a word count", false)
+                    .field("/convert_data", "Convert data between JSON, YAML, TOML, XML, CSV, MessagePack and CBOR.\n• Upload a file or paste `text`; the input format is detected\n• Pretty-printed by default, or `minify` the output\n• Parse errors show the line and column; small results are previewed inline", false)
+                    .field("/json", "Check and tidy JSON or YAML as subcommands.\n• `validate` shows the exact error location with a caret\n• `format`, `minify` and `sort_keys`\n• `query` with JSONPath (`$.items[*].name`) or jq-style filters (`.items[].name | length`)\n• `schema` validates against an uploaded JSON Schema", false)
+                    .field("/convert_sheet", "Convert spreadsheets and tables.\n• Reads XLSX, XLS, ODS, CSV and TSV; pick a `sheet` by name or number\n• Outputs CSV, TSV, JSON, Markdown or HTML tables, or XLSX\n• Up to 100,000 rows, 1,000 columns and 2 million cells", false)
                     .footer(serenity::builder::CreateEmbedFooter::new("Choose the format that best fits your needs"))
             }
+            HelpPage::Images => {
+                serenity::builder::CreateEmbed::default()
+                    .title("Conversia Help - Image Tools")
+                    .description("Resize, compress and edit your images")
+                    .color(0xE67E22) // Orange
+                    .field("/resize_image", "Resize an image", false)
+                    .field("/image_filter", "Apply a filter with an optional strength.\n• Blur, sharpen, brightness, contrast, hue rotate, saturation\n• Grayscale, invert, sepia and threshold", false)
+                    .field("/watermark", "Overlay text or a logo on an image.\n• Choose position, opacity, size, colour and angle\n• `tile:True` repeats it across the image", false)
+                    .field("/remove_background", "Make a solid background transparent.\n• Colour key with tolerance and feathered edges\n• Flood-fills from the corners so the subject is kept", false)
+                    .field("/pad_image", "Pad an image to an aspect ratio or exact size.\n• Transparent or coloured fill, PNG or WebP output", false)
+                    .field("/collage", "Arrange up to 10 images into a grid.\n• Columns, cell size, spacing and background colour\n• `crop:True` fills each cell instead of letterboxing", false)
+                    .field("/sprite_sheet", "Pack up to 10 frames into a sprite sheet with a JSON frame map.", false)
+                    .field("/slice_sheet", "Cut a sprite sheet or tileset into tiles, returned as a ZIP.\n• Cell size, margin and spacing", false)
+                    .field("/palette", "Show an image's dominant colours as hex codes with a swatch.\n• `count` picks 2 to 16 colours", false)
+                    .field("/compare_images", "Compare two images and get a highlighted diff.\n• aHash, dHash and pHash distances\n• SSIM, PSNR and the share of changed pixels", false)
+                    .field("/compress_image", "Compress an image to fit a target file size.\n• Example: `target_size:200KB`\n• Lowers quality first, then dimensions", false)
+                    .field("/image_info", "Show format, dimensions, colour type, ICC profile and metadata.\n• Full EXIF/XMP/IPTC dump attached as a file\n• Warns when GPS location is present", false)
+                    .field("/strip_metadata", "Remove EXIF, GPS, XMP and IPTC metadata from an image.\n• Lossless for JPEG, PNG, WebP and GIF", false)
+                    .field("/make_icons", "Generate a favicon and app icon set as a ZIP.\n• favicon.ico (16/32/48), Apple touch and Android icons\n• Includes site.webmanifest and a <head> snippet", false)
+                    .field("/make_emoji", "Fit an image or GIF to a 128×128 emoji under 256 KB", false)
+                    .field("/make_sticker", "Fit an image to a 320×320 sticker under 512 KB.\n• Animated GIFs become APNG", false)
+                    .field("/make_banner", "Fit an image to a server banner, profile banner or invite splash", false)
+                    .footer(serenity::builder::CreateEmbedFooter::new("Images above 16 megapixels are rejected"))
+            }
             HelpPage::Encryption => {
                 serenity::builder::CreateEmbed::default()
                     .title("Conversia Help - Encryption")
@@ -85,6 +120,9 @@ impl HelpPage {
                     .field("/base64_decode", "Decode a Base64-encoded file.", false)
                     .field("/hex_encode", "Encode a file to Hex format.", false)
                     .field("/hex_decode", "Decode a Hex-encoded file.", false)
+                    .field("/qr_encode", "Generate a QR code from text as PNG or SVG.\n• Choose error correction, size and colours", false)
+                    .field("/qr_decode", "Read every QR code in an image or screenshot.", false)
+                    .field("/text_encoding", "Detect a text file's charset and BOM and convert it.\n• UTF-8, UTF-16 and legacy encodings such as Windows-1252 and Shift_JIS\n• Normalize line endings, add or strip the BOM, NFC/NFD/NFKC/NFKD", false)
                     .footer(serenity::builder::CreateEmbedFooter::new(
                         "Encoding is useful for data transfer and storage",
                     ))
@@ -103,9 +141,8 @@ impl HelpPage {
                     .title("Conversia Help - Other Tools")
                     .description("Additional utilities and information")
                     .color(0x9B59B6)  // Purple
-                    .field("/resize_image", "Resize an image", false)
-                    .field("/hash", "Generate a hash for a file.\n• Supports: SHA-256, SHA-1, MD5, BLAKE3", false)
-                    .field("/verify_hash", "Verify a file’s hash against an expected checksum \n• Supports: SHA-256, SHA-1, MD5, BLAKE3", false)
+                    .field("/hash", "Generate a hash for a file.\n• Supports: SHA-256, SHA-1, MD5, BLAKE3\n• Perceptual aHash, dHash and pHash for images", false)
+                    .field("/verify_hash", "Verify a file’s hash against an expected checksum \n• Supports: SHA-256, SHA-1, MD5, BLAKE3\n• Perceptual hashes match within a distance of 10", false)
                     .field("/audio_meta", "Extract metadata from MP3 and FLAC files.\n• Shows: title, artist, album, year, genre\n• Works with most MP3 and FLAC files", false)
                     .field("/about", "Learn more about the Conversia bot.\n• Information about the bot\n• Legal information", false)
                     .field("/ping", "Check the bot's latency.\n• Useful for debugging connection issues", false)
@@ -133,6 +170,14 @@ impl HelpPage {
                     ButtonStyle::Secondary
                 })
                 .emoji('📄'),
+            CreateButton::new("help_images")
+                .label("Images")
+                .style(if matches!(self, HelpPage::Images) {
+                    ButtonStyle::Primary
+                } else {
+                    ButtonStyle::Secondary
+                })
+                .emoji('🖼'),
             CreateButton::new("help_encryption")
                 .label("Encryption")
                 .style(if matches!(self, HelpPage::Encryption) {
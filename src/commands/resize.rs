@@ -1,11 +1,11 @@
 use crate::attachments::{validate_attachment_size, validate_image_dimensions, validate_output_size};
+use crate::conversion::image::{OutputFormat, decode_image_at, encode_image};
 use crate::{Context, Error};
-use image::{DynamicImage, ImageFormat, RgbaImage, load_from_memory};
+use image::{DynamicImage, RgbaImage};
 use poise::serenity_prelude::CreateAttachment;
 use resize::{Pixel::RGBA8, Resizer, Type};
 use rgb::RGBA;
 use serenity::all::{Attachment, CreateEmbed, CreateEmbedFooter};
-use std::io::Cursor;
 
 const MAX_DIMENSION: u32 = 16_384;
 
@@ -36,18 +36,49 @@ fn normalized_extension(filename: &str) -> String {
         .map_or_else(|| String::from("png"), |(_, ext)| ext.to_ascii_lowercase())
 }
 
-fn image_format_for_extension(extension: &str) -> ImageFormat {
+/// Pick the output format for a resized image from the input extension.
+///
+/// Input-only formats such as SVG, HDR and EXR are written as PNG.
+fn image_format_for_extension(extension: &str) -> OutputFormat {
     match extension {
-        "jpg" | "jpeg" => ImageFormat::Jpeg,
-        "png" => ImageFormat::Png,
-        "webp" => ImageFormat::WebP,
-        "gif" => ImageFormat::Gif,
-        "bmp" => ImageFormat::Bmp,
-        "tif" | "tiff" => ImageFormat::Tiff,
-        _ => ImageFormat::Png,
+        "jpg" | "jpeg" => OutputFormat::Jpg,
+        "png" => OutputFormat::Png,
+        "webp" => OutputFormat::Webp,
+        "gif" => OutputFormat::Gif,
+        "bmp" => OutputFormat::Bmp,
+        "tif" | "tiff" => OutputFormat::Tiff,
+        "avif" => OutputFormat::Avif,
+        "ico" => OutputFormat::Ico,
+        "qoi" => OutputFormat::Qoi,
+        "tga" => OutputFormat::Tga,
+        "ppm" | "pnm" | "pgm" | "pbm" | "pam" => OutputFormat::Pnm,
+        _ => OutputFormat::Png,
     }
 }
 
+/// Pick the output format that keeps an uploaded image in its original format.
+pub fn output_format_for_filename(filename: &str) -> OutputFormat {
+    image_format_for_extension(&normalized_extension(filename))
+}
+
+/// Scale `width`×`height` down to fit inside the bounds, preserving aspect ratio.
+///
+/// Dimensions that already fit are returned unchanged.
+pub fn fit_within(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    if width <= max_width && height <= max_height {
+        return (width, height);
+    }
+
+    let scale = f64::min(
+        f64::from(max_width) / f64::from(width),
+        f64::from(max_height) / f64::from(height),
+    );
+    (
+        ((f64::from(width) * scale).round() as u32).clamp(1, max_width),
+        ((f64::from(height) * scale).round() as u32).clamp(1, max_height),
+    )
+}
+
 fn should_use_lanczos(
     source_width: u32,
     source_height: u32,
@@ -86,6 +117,51 @@ fn rgba_pixels_to_bytes(pixels: &[RGBA<u8>]) -> Vec<u8> {
     bytes
 }
 
+/// Resize RGBA pixels to the requested dimensions.
+///
+/// Lanczos3 is used when downscaling and Mitchell when upscaling.
+pub fn resize_rgba(source: &RgbaImage, width: u32, height: u32) -> Result<RgbaImage, Error> {
+    let (original_width, original_height) = source.dimensions();
+    let src_pixels = rgba_image_to_pixels(source);
+
+    // Create destination buffer with the requested size.
+    let mut dst_pixels = vec![
+        RGBA {
+            r: 0u8,
+            g: 0u8,
+            b: 0u8,
+            a: 0u8
+        };
+        (width * height) as usize
+    ];
+
+    // Choose the resize filter based on whether this is a downscale or upscale.
+    let resize_type = if should_use_lanczos(original_width, original_height, width, height) {
+        Type::Lanczos3
+    } else {
+        Type::Mitchell
+    };
+
+    let mut resizer = Resizer::new(
+        original_width as usize,
+        original_height as usize,
+        width as usize,
+        height as usize,
+        RGBA8,
+        resize_type,
+    )
+    .map_err(|_| Error::from("Failed to initialize the image resizer."))?;
+
+    resizer
+        .resize(&src_pixels[..], &mut dst_pixels[..])
+        .map_err(|_| Error::from("Image resizing failed."))?;
+
+    let dst_bytes = rgba_pixels_to_bytes(&dst_pixels);
+
+    RgbaImage::from_raw(width, height, dst_bytes)
+        .ok_or_else(|| Error::from("Failed to rebuild the resized image."))
+}
+
 /// Resize an image
 #[poise::command(slash_command)]
 pub async fn resize_image(
@@ -127,73 +203,21 @@ pub async fn resize_image(
     };
 
     let normalized_extension = normalized_extension(&attachment.filename);
-    let image_format = image_format_for_extension(&normalized_extension);
+    let output_format = image_format_for_extension(&normalized_extension);
     let (output_bytes, original_width, original_height, original_extension) =
         match tokio::task::spawn_blocking(move || -> Result<(Vec<u8>, u32, u32, String), Error> {
-            // Load image
-            let src_image = load_from_memory(&bytes).map_err(|_| {
-                Error::from("Invalid image format. Please upload a supported image file.")
-            })?;
+            // Load image, rasterizing SVG input at the requested width
+            let src_image = decode_image_at(&bytes, Some(width))?;
             let original_width = src_image.width();
             let original_height = src_image.height();
-            validate_image_dimensions(original_width, original_height).map_err(Error::from)?;
-            let rgba = src_image.to_rgba8();
-
-            let src_pixels = rgba_image_to_pixels(&rgba);
-
-            // Create destination buffer with the requested size.
-            let mut dst_pixels = vec![
-                RGBA {
-                    r: 0u8,
-                    g: 0u8,
-                    b: 0u8,
-                    a: 0u8
-                };
-                (width * height) as usize
-            ];
-
-            // Choose the resize filter based on whether this is a downscale or upscale.
-            let resize_type = if should_use_lanczos(original_width, original_height, width, height)
-            {
-                Type::Lanczos3
-            } else {
-                Type::Mitchell
-            };
-
-            let mut resizer = Resizer::new(
-                original_width as usize,
-                original_height as usize,
-                width as usize,
-                height as usize,
-                RGBA8,
-                resize_type,
-            )
-            .map_err(|_| Error::from("Failed to initialize the image resizer."))?;
-
-            resizer
-                .resize(&src_pixels[..], &mut dst_pixels[..])
-                .map_err(|_| Error::from("Image resizing failed."))?;
-
-            let dst_bytes = rgba_pixels_to_bytes(&dst_pixels);
-
-            let resized = RgbaImage::from_raw(width, height, dst_bytes)
-                .ok_or_else(|| Error::from("Failed to rebuild the resized image."))?;
-
-            let ext = normalized_extension;
-
-            let dyn_img = match image_format {
-                ImageFormat::Jpeg => {
-                    DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(resized).to_rgb8())
-                }
-                _ => DynamicImage::ImageRgba8(resized),
-            };
-
-            let mut buffer = Cursor::new(Vec::new());
-            dyn_img
-                .write_to(&mut buffer, image_format)
+            let resized = resize_rgba(&src_image.to_rgba8(), width, height)?;
+
+            let ext = output_format.extension().to_string();
+
+            let output = encode_image(DynamicImage::ImageRgba8(resized), output_format)
                 .map_err(|_| Error::from("Failed to encode the resized image."))?;
 
-            Ok((buffer.into_inner(), original_width, original_height, ext))
+            Ok((output, original_width, original_height, ext))
         })
         .await
         {
@@ -234,3 +258,15 @@ pub async fn resize_image(
     ctx.send(reply).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::fit_within;
+
+    #[test]
+    fn fit_within_preserves_aspect_ratio() {
+        assert_eq!(fit_within(512, 256, 256, 256), (256, 128));
+        assert_eq!(fit_within(100, 50, 256, 256), (100, 50));
+        assert_eq!(fit_within(1, 4000, 256, 256), (1, 256));
+    }
+}
@@ -5,29 +5,32 @@ use poise::serenity_prelude as serenity;
 use poise::serenity_prelude::{Attachment, CreateAttachment};
 use serenity::builder::CreateEmbed;
 use std::fs::File;
-use std::io::Write;
+use std::io::{Cursor, Write};
 use std::path::Path;
 use tempfile::Builder;
+use zip::write::SimpleFileOptions;
 use zip::{CompressionMethod, ZipWriter};
 
 /// Strip all extensions from a filename, returning only the stem.
 fn strip_all_extensions(filename: &str) -> String {
     let mut stem = filename.to_string();
-    loop {
-        match Path::new(&stem).file_stem() {
-            Some(s) => {
-                let s_str = s.to_string_lossy().to_string();
-                if s_str == stem {
-                    break;
-                }
-                stem = s_str;
-            }
-            None => break,
+    while let Some(s) = Path::new(&stem).file_stem() {
+        let s_str = s.to_string_lossy().to_string();
+        if s_str == stem {
+            break;
         }
+        stem = s_str;
     }
     stem
 }
 
+/// Options shared by every file written into a ZIP archive.
+fn zip_file_options() -> SimpleFileOptions {
+    SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(0o755)
+}
+
 /// Create a ZIP archive containing the given data as a single file.
 fn create_zip_archive(
     internal_filename: &str,
@@ -36,15 +39,24 @@ fn create_zip_archive(
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let file = File::create(output_path)?;
     let mut zip = ZipWriter::new(file);
-    let options = zip::write::SimpleFileOptions::default()
-        .compression_method(CompressionMethod::Deflated)
-        .unix_permissions(0o755);
-    zip.start_file(internal_filename, options)?;
+    zip.start_file(internal_filename, zip_file_options())?;
     zip.write_all(data)?;
     zip.finish()?;
     Ok(())
 }
 
+/// Create an in-memory ZIP archive from `(path, data)` entries.
+///
+/// Paths may contain `/` to place entries in folders.
+pub fn zip_entries(entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>, Error> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (path, data) in entries {
+        zip.start_file(path.as_str(), zip_file_options())?;
+        zip.write_all(data)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
 /// Compress a file into a zip archive
 #[poise::command(slash_command)]
 pub async fn zip(
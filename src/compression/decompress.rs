@@ -4,11 +4,68 @@ use crate::{Context, Error};
 use poise::serenity_prelude as serenity;
 use poise::serenity_prelude::{Attachment, CreateAttachment};
 use serenity::builder::CreateEmbed;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use tempfile::Builder;
 use zip::ZipArchive;
 
 const MAX_EXTRACTED_BYTES: u64 = 25 * 1024 * 1024;
+/// Most entries extracted from a multi-file archive
+const MAX_ARCHIVE_ENTRIES: usize = 500;
+
+/// Extract every regular file of a ZIP archive into `dir`.
+///
+/// Entries whose paths escape `dir` are rejected, symlinks are skipped and the
+/// total extracted size is capped. Returns the relative paths of the extracted
+/// files.
+pub fn extract_zip_to_dir(data: &[u8], dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut archive = ZipArchive::new(std::io::Cursor::new(data))?;
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        return Err(format!(
+            "The ZIP archive has more than {} entries.",
+            MAX_ARCHIVE_ENTRIES
+        )
+        .into());
+    }
+
+    let mut remaining = MAX_EXTRACTED_BYTES;
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let relative = entry.enclosed_name().ok_or_else(|| {
+            Error::from(format!(
+                "The ZIP archive entry `{}` has an unsafe path.",
+                entry.name()
+            ))
+        })?;
+        let target = dir.join(&relative);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            continue;
+        }
+        if !entry.is_file() {
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut output_file = std::fs::File::create(&target)?;
+        // Read one byte past the budget so lying size headers are still caught
+        let written = std::io::copy(&mut (&mut entry).take(remaining + 1), &mut output_file)?;
+        if written > remaining {
+            return Err(format!(
+                "The ZIP archive is too large to extract safely (over {}).",
+                format_file_size(MAX_EXTRACTED_BYTES)
+            )
+            .into());
+        }
+        remaining -= written;
+        files.push(relative);
+    }
+
+    Ok(files)
+}
 
 /// Extract the first file from a ZIP archive stored in memory.
 /// Returns the original filename from inside the archive.
@@ -20,7 +77,7 @@ async fn extract_first_zip_entry(
     tokio::task::spawn_blocking(move || -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         let cursor = std::io::Cursor::new(data);
         let mut archive = ZipArchive::new(cursor)?;
-        if archive.len() == 0 {
+        if archive.is_empty() {
             return Err("Empty archive".into());
         }
         if archive.len() > 1 {
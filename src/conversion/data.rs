@@ -0,0 +1,718 @@
+use crate::attachments::{download_attachment, sanitize_filename, validate_output_size};
+use crate::embeds::{EMBED_SUCCESS_COLOR, INLINE_PREVIEW_LIMIT, error_embed};
+use crate::utils::file_stem;
+use crate::{Context, Error};
+use poise::ChoiceParameter;
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed};
+use quick_xml::Reader;
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::Event;
+use serde_json::{Map, Value};
+
+/// Deepest element nesting accepted from XML input
+const MAX_XML_DEPTH: usize = 128;
+/// Key holding an XML element's text when it also has attributes or children
+const XML_TEXT_KEY: &str = "#text";
+/// Prefix marking keys that are XML attributes
+const XML_ATTRIBUTE_PREFIX: char = '@';
+/// Element wrapping the output when the data has no single top-level key
+const XML_DEFAULT_ROOT: &str = "root";
+/// Key TOML uses to pass dates and times through serde
+const TOML_DATETIME_KEY: &str = "$__toml_private_datetime";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum DataFormat {
+    #[name = "JSON"]
+    Json,
+    #[name = "YAML"]
+    Yaml,
+    #[name = "TOML"]
+    Toml,
+    #[name = "XML"]
+    Xml,
+    #[name = "CSV (array of records)"]
+    Csv,
+    #[name = "MessagePack"]
+    MessagePack,
+    #[name = "CBOR"]
+    Cbor,
+}
+
+impl DataFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Xml => "xml",
+            Self::Csv => "csv",
+            Self::MessagePack => "msgpack",
+            Self::Cbor => "cbor",
+        }
+    }
+
+    const fn is_binary(self) -> bool {
+        matches!(self, Self::MessagePack | Self::Cbor)
+    }
+
+    fn from_extension(filename: &str) -> Option<Self> {
+        let (_, extension) = filename.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "json" | "geojson" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "xml" => Some(Self::Xml),
+            "csv" => Some(Self::Csv),
+            "msgpack" | "mpk" => Some(Self::MessagePack),
+            "cbor" => Some(Self::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of text without a known extension.
+    ///
+    /// YAML is the fallback since it accepts most plain text.
+    fn guess(data: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(data.get(..256).unwrap_or(data));
+        match text
+            .trim_start_matches('\u{feff}')
+            .trim_start()
+            .chars()
+            .next()
+        {
+            Some('{') => Self::Json,
+            Some('[') if serde_json::from_slice::<Value>(data).is_ok() => Self::Json,
+            Some('<') => Self::Xml,
+            _ => Self::Yaml,
+        }
+    }
+}
+
+fn utf8(data: &[u8], format: DataFormat) -> Result<&str, String> {
+    std::str::from_utf8(data)
+        .map(|text| text.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("{} input must be UTF-8 text: {}", format.name(), e))
+}
+
+/// 1-based line and column of a byte offset.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+/// Replace the tables TOML uses for dates and times with plain strings.
+fn flatten_toml_datetimes(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if map.len() == 1
+                && let Some(Value::String(datetime)) = map.get(TOML_DATETIME_KEY)
+            {
+                *value = Value::String(datetime.clone());
+                return;
+            }
+            map.values_mut().for_each(flatten_toml_datetimes);
+        }
+        Value::Array(items) => items.iter_mut().for_each(flatten_toml_datetimes),
+        _ => {}
+    }
+}
+
+/// Read a CSV field as a number or boolean where that is unambiguous.
+pub fn csv_field(field: &str) -> Value {
+    let has_leading_zero = field.len() > 1
+        && field.trim_start_matches('-').starts_with('0')
+        && !field.trim_start_matches('-').starts_with("0.");
+    if !has_leading_zero {
+        if let Ok(number) = field.parse::<i64>() {
+            return Value::from(number);
+        }
+        if let Ok(number) = field.parse::<f64>()
+            && number.is_finite()
+            && field.contains(|c: char| c.is_ascii_digit())
+        {
+            return Value::from(number);
+        }
+    }
+    match field {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(field.to_string()),
+    }
+}
+
+fn parse_csv(text: &str) -> Result<Value, String> {
+    let mut reader = csv::ReaderBuilder::new().from_reader(text.as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let row: Map<String, Value> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, field)| (header.to_string(), csv_field(field)))
+            .collect();
+        records.push(Value::Object(row));
+    }
+    Ok(Value::Array(records))
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn write_csv(value: &Value) -> Result<Vec<u8>, String> {
+    let records: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![value],
+        _ => return Err("CSV output needs an array of records (objects) or rows (arrays).".into()),
+    };
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+
+    if records.iter().all(|record| record.is_array()) {
+        for record in &records {
+            let row = record.as_array().into_iter().flatten().map(csv_cell);
+            writer.write_record(row).map_err(|e| e.to_string())?;
+        }
+    } else if records.iter().all(|record| record.is_object()) {
+        // Columns are every key in the order it first appears
+        let mut columns: Vec<&str> = Vec::new();
+        for record in records.iter().filter_map(|record| record.as_object()) {
+            for key in record.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+        writer.write_record(&columns).map_err(|e| e.to_string())?;
+        for record in records.iter().filter_map(|record| record.as_object()) {
+            let row = columns
+                .iter()
+                .map(|column| record.get(*column).map(csv_cell).unwrap_or_default());
+            writer.write_record(row).map_err(|e| e.to_string())?;
+        }
+    } else {
+        return Err("CSV output needs every item to be a record (object) or a row (array).".into());
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+/// An XML element being read, with its attributes, children and text so far.
+struct XmlElement {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl XmlElement {
+    fn into_value(self) -> Value {
+        let text = self.text.trim();
+        if self.fields.is_empty() {
+            return match text {
+                "" => Value::Null,
+                text => Value::String(text.to_string()),
+            };
+        }
+        let mut fields = self.fields;
+        if !text.is_empty() {
+            fields.insert(XML_TEXT_KEY.to_string(), Value::String(text.to_string()));
+        }
+        Value::Object(fields)
+    }
+}
+
+/// Add a child under `name`, turning repeated names into an array.
+fn insert_child(fields: &mut Map<String, Value>, name: String, value: Value) {
+    match fields.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            fields.insert(name, value);
+        }
+    }
+}
+
+/// Read XML into objects keyed by element name, with `@`-prefixed attributes
+/// and `#text` for text next to attributes or children.
+fn parse_xml(text: &str) -> Result<Value, String> {
+    let mut reader = Reader::from_str(text);
+    let position_error = |reader: &Reader<&[u8]>, e: &dyn std::fmt::Display| {
+        let (line, column) = line_column(text, reader.error_position() as usize);
+        format!("{} at line {} column {}", e, line, column)
+    };
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root: Option<(String, Value)> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| position_error(&reader, &e))?;
+        let is_start = matches!(event, Event::Start(_));
+        match event {
+            Event::Start(element) | Event::Empty(element) => {
+                if root.is_some() {
+                    return Err("XML documents can only have one root element.".into());
+                }
+                if stack.len() >= MAX_XML_DEPTH {
+                    return Err(format!(
+                        "XML nested deeper than {} elements is not supported.",
+                        MAX_XML_DEPTH
+                    ));
+                }
+                let mut current = XmlElement {
+                    name: String::from_utf8_lossy(element.name().as_ref()).into_owned(),
+                    fields: Map::new(),
+                    text: String::new(),
+                };
+                for attribute in element.attributes() {
+                    let attribute = attribute.map_err(|e| position_error(&reader, &e))?;
+                    let value = attribute
+                        .decode_and_unescape_value(reader.decoder())
+                        .map_err(|e| position_error(&reader, &e))?;
+                    current.fields.insert(
+                        format!(
+                            "{}{}",
+                            XML_ATTRIBUTE_PREFIX,
+                            String::from_utf8_lossy(attribute.key.as_ref())
+                        ),
+                        Value::String(value.into_owned()),
+                    );
+                }
+                stack.push(current);
+                if is_start {
+                    continue;
+                }
+            }
+            Event::End(_) => {}
+            Event::Text(content) => {
+                if let Some(current) = stack.last_mut() {
+                    let content = content.decode().map_err(|e| position_error(&reader, &e))?;
+                    current.text.push_str(&content);
+                }
+                continue;
+            }
+            Event::CData(content) => {
+                if let Some(current) = stack.last_mut() {
+                    let content = content.decode().map_err(|e| position_error(&reader, &e))?;
+                    current.text.push_str(&content);
+                }
+                continue;
+            }
+            Event::GeneralRef(reference) => {
+                if let Some(current) = stack.last_mut() {
+                    if let Some(c) = reference
+                        .resolve_char_ref()
+                        .map_err(|e| position_error(&reader, &e))?
+                    {
+                        current.text.push(c);
+                    } else {
+                        let name = reference
+                            .decode()
+                            .map_err(|e| position_error(&reader, &e))?;
+                        let entity = resolve_predefined_entity(&name)
+                            .ok_or_else(|| format!("Unknown XML entity `&{};`", name))?;
+                        current.text.push_str(entity);
+                    }
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        }
+
+        // An element ended, so attach it to its parent
+        if let Some(finished) = stack.pop() {
+            let name = finished.name.clone();
+            let value = finished.into_value();
+            match stack.last_mut() {
+                Some(parent) => insert_child(&mut parent.fields, name, value),
+                None => root = Some((name, value)),
+            }
+        }
+    }
+
+    let (name, value) = root.ok_or("The XML document has no root element.")?;
+    let mut document = Map::new();
+    document.insert(name, value);
+    Ok(Value::Object(document))
+}
+
+/// Make a key usable as an XML element or attribute name.
+fn xml_name(key: &str) -> String {
+    let mut name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn xml_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn write_xml_element(output: &mut String, name: &str, value: &Value, depth: usize, pretty: bool) {
+    if let Value::Array(items) = value {
+        for item in items {
+            write_xml_element(output, name, item, depth, pretty);
+        }
+        return;
+    }
+
+    let name = xml_name(name);
+    let indent = if pretty {
+        "  ".repeat(depth)
+    } else {
+        String::new()
+    };
+    let newline = if pretty { "\n" } else { "" };
+    output.push_str(&indent);
+    output.push('<');
+    output.push_str(&name);
+
+    let Value::Object(fields) = value else {
+        match value {
+            Value::Null => output.push_str(&format!("/>{}", newline)),
+            scalar => output.push_str(&format!(
+                ">{}</{}>{}",
+                escape(xml_scalar(scalar).as_str()),
+                name,
+                newline
+            )),
+        }
+        return;
+    };
+
+    for (key, attribute) in fields {
+        if let Some(attribute_name) = key.strip_prefix(XML_ATTRIBUTE_PREFIX) {
+            output.push_str(&format!(
+                " {}=\"{}\"",
+                xml_name(attribute_name),
+                escape(xml_scalar(attribute).as_str())
+            ));
+        }
+    }
+    let text = fields.get(XML_TEXT_KEY).map(xml_scalar).unwrap_or_default();
+    let children: Vec<(&String, &Value)> = fields
+        .iter()
+        .filter(|(key, _)| !key.starts_with(XML_ATTRIBUTE_PREFIX) && *key != XML_TEXT_KEY)
+        .collect();
+
+    if children.is_empty() && text.is_empty() {
+        output.push_str(&format!("/>{}", newline));
+        return;
+    }
+    output.push('>');
+    output.push_str(&escape(text.as_str()));
+    if !children.is_empty() {
+        output.push_str(newline);
+        for (key, child) in children {
+            write_xml_element(output, key, child, depth + 1, pretty);
+        }
+        output.push_str(&indent);
+    }
+    output.push_str(&format!("</{}>{}", name, newline));
+}
+
+fn write_xml(value: &Value, pretty: bool) -> String {
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    match value {
+        Value::Object(fields)
+            if fields.len() == 1
+                && fields.iter().all(|(key, child)| {
+                    !key.starts_with(XML_ATTRIBUTE_PREFIX)
+                        && key != XML_TEXT_KEY
+                        && !child.is_array()
+                }) =>
+        {
+            for (key, child) in fields {
+                write_xml_element(&mut output, key, child, 0, pretty);
+            }
+        }
+        Value::Array(_) => {
+            let mut wrapper = Map::new();
+            wrapper.insert("item".to_string(), value.clone());
+            write_xml_element(
+                &mut output,
+                XML_DEFAULT_ROOT,
+                &Value::Object(wrapper),
+                0,
+                pretty,
+            );
+        }
+        _ => write_xml_element(&mut output, XML_DEFAULT_ROOT, value, 0, pretty),
+    }
+    if !pretty {
+        output.push('\n');
+    }
+    output
+}
+
+/// Parse data in `format` into a JSON value.
+pub fn parse_data(data: &[u8], format: DataFormat) -> Result<Value, String> {
+    match format {
+        DataFormat::Json => serde_json::from_str(utf8(data, format)?).map_err(|e| e.to_string()),
+        DataFormat::Yaml => serde_yaml::from_str(utf8(data, format)?).map_err(|e| e.to_string()),
+        DataFormat::Toml => {
+            let mut value = toml::from_str(utf8(data, format)?)
+                .map_err(|e| e.to_string().trim().to_string())?;
+            flatten_toml_datetimes(&mut value);
+            Ok(value)
+        }
+        DataFormat::Xml => parse_xml(utf8(data, format)?),
+        DataFormat::Csv => parse_csv(utf8(data, format)?),
+        DataFormat::MessagePack => rmp_serde::from_slice(data).map_err(|e| e.to_string()),
+        DataFormat::Cbor => ciborium::from_reader(data).map_err(|e| e.to_string()),
+    }
+}
+
+/// Serialize a JSON value as `format`. `pretty` has no effect on TOML, CSV
+/// and the binary formats; minified YAML is written in flow style.
+pub fn write_data(value: &Value, format: DataFormat, pretty: bool) -> Result<Vec<u8>, String> {
+    match format {
+        DataFormat::Json if pretty => serde_json::to_vec_pretty(value).map_err(|e| e.to_string()),
+        DataFormat::Yaml if pretty => serde_yaml::to_string(value)
+            .map(String::into_bytes)
+            .map_err(|e| e.to_string()),
+        // JSON is valid flow-style YAML, which is as compact as YAML gets
+        DataFormat::Json | DataFormat::Yaml => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        DataFormat::Toml => {
+            if !value.is_object() {
+                return Err("TOML documents must be a table (an object) at the top level.".into());
+            }
+            toml::to_string_pretty(value)
+                .map(String::into_bytes)
+                .map_err(|e| match e.to_string() {
+                    message if message.contains("None") || message.contains("null") => {
+                        "TOML has no null value; remove the null entries or pick another format."
+                            .to_string()
+                    }
+                    message => message,
+                })
+        }
+        DataFormat::Xml => Ok(write_xml(value, pretty).into_bytes()),
+        DataFormat::Csv => write_csv(value),
+        DataFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        DataFormat::Cbor => {
+            let mut output = Vec::new();
+            ciborium::into_writer(value, &mut output).map_err(|e| e.to_string())?;
+            Ok(output)
+        }
+    }
+}
+
+/// Convert structured data between JSON, YAML, TOML, XML, CSV, MessagePack and CBOR
+#[poise::command(slash_command)]
+pub async fn convert_data(
+    ctx: Context<'_>,
+    #[description = "Format to convert to"] output_format: DataFormat,
+    #[description = "Data file to convert"] file: Option<Attachment>,
+    #[description = "Text to convert instead of a file, e.g. pasted JSON"] text: Option<String>,
+    #[description = "Format of the input (default: from the file extension, else guessed)"]
+    input_format: Option<DataFormat>,
+    #[description = "Minify JSON, YAML and XML output (default: pretty-printed)"] minify: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let input = match (&file, text) {
+        (Some(file), None) => download_attachment(file).await.map_err(|e| e.to_string()),
+        (None, Some(_)) if input_format.is_some_and(DataFormat::is_binary) => {
+            Err("That input format is binary and must be uploaded, not pasted.".to_string())
+        }
+        (None, Some(text)) if !text.trim().is_empty() => Ok(text.into_bytes()),
+        (Some(_), Some(_)) | (None, _) => {
+            Err("Provide either a `file` or some `text` to convert.".to_string())
+        }
+    };
+    let data = match input {
+        Ok(data) => data,
+        Err(message) => {
+            let embed = error_embed("❌ Conversion Failed", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let input_format = input_format
+        .or_else(|| {
+            file.as_ref()
+                .and_then(|file| DataFormat::from_extension(&file.filename))
+        })
+        .unwrap_or_else(|| DataFormat::guess(&data));
+    let pretty = !minify.unwrap_or(false);
+    let input_size = data.len();
+
+    let result = tokio::task::spawn_blocking(move || {
+        let value = parse_data(&data, input_format).map_err(|e| {
+            format!(
+                "Could not parse the input as {}:\n```\n{}\n```",
+                input_format.name(),
+                e.replace("```", "`\u{200b}``")
+            )
+        })?;
+        write_data(&value, output_format, pretty)
+            .map_err(|e| format!("Could not write {}: {}", output_format.name(), e))
+    })
+    .await
+    .map_err(|e| format!("Conversion task failed: {}", e))
+    .and_then(|result| result)
+    .and_then(|output| validate_output_size(output.len(), "Converted data").map(|()| output));
+    let output = match result {
+        Ok(output) => output,
+        Err(message) => {
+            let embed = error_embed("❌ Conversion Failed", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let stem = file
+        .as_ref()
+        .map(|file| sanitize_filename(&file_stem(&file.filename)))
+        .unwrap_or_else(|| "data".to_string());
+    let filename = format!("{}.{}", stem, output_format.extension());
+    let mut description = format!(
+        "**Input:** {} ({} bytes)\n**Output:** {} ({} bytes)",
+        input_format.name(),
+        input_size,
+        output_format.name(),
+        output.len()
+    );
+    if !output_format.is_binary()
+        && let Ok(preview) = std::str::from_utf8(&output)
+        && preview.len() <= INLINE_PREVIEW_LIMIT
+    {
+        description.push_str(&format!(
+            "\n\n```{}\n{}\n```",
+            output_format.extension(),
+            preview.trim_end().replace("```", "`\u{200b}``")
+        ));
+    }
+
+    let embed = CreateEmbed::new()
+        .title("✅ Data Converted")
+        .description(description)
+        .color(EMBED_SUCCESS_COLOR);
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(output, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DataFormat, parse_data, write_data};
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_through_every_format() {
+        let value =
+            json!({"name": "conversia", "version": 2, "tags": ["bot", "files"], "ok": true});
+        for format in [
+            DataFormat::Json,
+            DataFormat::Yaml,
+            DataFormat::Toml,
+            DataFormat::MessagePack,
+            DataFormat::Cbor,
+        ] {
+            for pretty in [true, false] {
+                let written = write_data(&value, format, pretty).unwrap();
+                assert_eq!(parse_data(&written, format).unwrap(), value, "{:?}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn xml_uses_attribute_and_text_keys() {
+        let xml = br#"<?xml version="1.0"?>
+            <library name="main"><book id="1">Dune &amp; more</book><book id="2"/><open/></library>"#;
+        let value = parse_data(xml, DataFormat::Xml).unwrap();
+        assert_eq!(
+            value,
+            json!({"library": {
+                "@name": "main",
+                "book": [{"@id": "1", "#text": "Dune & more"}, {"@id": "2"}],
+                "open": null
+            }})
+        );
+
+        let written = write_data(&value, DataFormat::Xml, false).unwrap();
+        assert_eq!(
+            String::from_utf8(written.clone()).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<library name=\"main\">\
+             <book id=\"1\">Dune &amp; more</book><book id=\"2\"/><open/></library>\n"
+        );
+        assert_eq!(parse_data(&written, DataFormat::Xml).unwrap(), value);
+
+        let error = parse_data(b"<a>\n  <b></a>", DataFormat::Xml).unwrap_err();
+        assert!(error.contains("line 2"), "{}", error);
+    }
+
+    #[test]
+    fn csv_records_infer_types_and_union_columns() {
+        let value = parse_data(
+            b"id,zip,name,active\n1,01234,Ann,true\n2,90210,Bo,false\n",
+            DataFormat::Csv,
+        )
+        .unwrap();
+        assert_eq!(
+            value[0],
+            json!({"id": 1, "zip": "01234", "name": "Ann", "active": true})
+        );
+
+        let records = json!([{"a": 1, "b": {"c": null}}, {"b": "x", "d": null}]);
+        let written = write_data(&records, DataFormat::Csv, true).unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "a,b,d\n1,\"{\"\"c\"\":null}\",\n,x,\n"
+        );
+    }
+
+    #[test]
+    fn toml_dates_become_strings_and_errors_show_position() {
+        let value = parse_data(b"released = 2024-05-01\n", DataFormat::Toml).unwrap();
+        assert_eq!(value, json!({"released": "2024-05-01"}));
+
+        let error = parse_data(b"{\n  \"a\": 1,\n}", DataFormat::Json).unwrap_err();
+        assert!(error.contains("line 3 column 1"), "{}", error);
+        assert!(write_data(&json!({"a": null}), DataFormat::Toml, true).is_err());
+    }
+}
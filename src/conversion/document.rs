@@ -1,14 +1,265 @@
 use crate::attachments::{sanitize_filename, validate_attachment_size, validate_output_size};
+use crate::compression::decompress::extract_zip_to_dir;
+use crate::conversion::markdown;
 use pandoc;
+use pandoc::PandocOption;
+use poise::ChoiceParameter;
 use poise::serenity_prelude::{Attachment, CreateAttachment};
 use serenity::builder::CreateEmbed;
+use std::path::{Path, PathBuf};
 use tempfile::Builder;
 use tokio::fs;
 
 use crate::utils::file_stem;
 use crate::{Context, Error};
 
-#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum InputFormat {
+    #[name = "Markdown (pandoc)"]
+    Markdown,
+    #[name = "GitHub-Flavored Markdown"]
+    Gfm,
+    #[name = "CommonMark"]
+    Commonmark,
+    #[name = "HTML"]
+    Html,
+    #[name = "LaTeX"]
+    Latex,
+    #[name = "reStructuredText"]
+    Rst,
+    #[name = "Org mode"]
+    Org,
+    #[name = "Word Document (docx)"]
+    Docx,
+    #[name = "OpenDocument Text (odt)"]
+    Odt,
+    #[name = "EPUB"]
+    Epub,
+    #[name = "Rich Text Format (rtf)"]
+    Rtf,
+    #[name = "Textile"]
+    Textile,
+    #[name = "MediaWiki"]
+    MediaWiki,
+    #[name = "DocBook"]
+    DocBook,
+    #[name = "Jupyter Notebook (ipynb)"]
+    Jupyter,
+    #[name = "CSV"]
+    Csv,
+}
+
+impl InputFormat {
+    /// Guess the reader from a file extension.
+    ///
+    /// Plain text is read as Markdown, which treats it as paragraphs.
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "md" | "markdown" | "mkd" | "txt" | "text" => Some(Self::Markdown),
+            "html" | "htm" | "xhtml" => Some(Self::Html),
+            "tex" | "latex" => Some(Self::Latex),
+            "rst" => Some(Self::Rst),
+            "org" => Some(Self::Org),
+            "docx" => Some(Self::Docx),
+            "odt" => Some(Self::Odt),
+            "epub" => Some(Self::Epub),
+            "rtf" => Some(Self::Rtf),
+            "textile" => Some(Self::Textile),
+            "wiki" | "mediawiki" => Some(Self::MediaWiki),
+            "dbk" | "docbook" => Some(Self::DocBook),
+            "ipynb" => Some(Self::Jupyter),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    /// Extension given to the temporary input file
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown | Self::Gfm | Self::Commonmark => "md",
+            Self::Html => "html",
+            Self::Latex => "tex",
+            Self::Rst => "rst",
+            Self::Org => "org",
+            Self::Docx => "docx",
+            Self::Odt => "odt",
+            Self::Epub => "epub",
+            Self::Rtf => "rtf",
+            Self::Textile => "textile",
+            Self::MediaWiki => "wiki",
+            Self::DocBook => "xml",
+            Self::Jupyter => "ipynb",
+            Self::Csv => "csv",
+        }
+    }
+
+    /// Whether the format is a binary container that cannot be pasted as text
+    const fn is_binary(self) -> bool {
+        matches!(self, Self::Docx | Self::Odt | Self::Epub)
+    }
+
+    fn pandoc_input_format(self) -> pandoc::InputFormat {
+        match self {
+            Self::Markdown => pandoc::InputFormat::Markdown,
+            Self::Gfm => pandoc::InputFormat::Other("gfm".to_string()),
+            Self::Commonmark => pandoc::InputFormat::Commonmark,
+            Self::Html => pandoc::InputFormat::Html,
+            Self::Latex => pandoc::InputFormat::Latex,
+            Self::Rst => pandoc::InputFormat::Rst,
+            Self::Org => pandoc::InputFormat::Org,
+            Self::Docx => pandoc::InputFormat::Docx,
+            Self::Odt => pandoc::InputFormat::Other("odt".to_string()),
+            Self::Epub => pandoc::InputFormat::Epub,
+            Self::Rtf => pandoc::InputFormat::Rtf,
+            Self::Textile => pandoc::InputFormat::Textile,
+            Self::MediaWiki => pandoc::InputFormat::MediaWiki,
+            Self::DocBook => pandoc::InputFormat::DocBook,
+            Self::Jupyter => pandoc::InputFormat::Other("ipynb".to_string()),
+            Self::Csv => pandoc::InputFormat::Other("csv".to_string()),
+        }
+    }
+}
+
+/// Where the document to convert comes from
+pub enum DocumentInput<'a> {
+    /// An uploaded file
+    File(&'a Attachment),
+    /// Text pasted into the command
+    Text(String),
+}
+
+/// Filename used for pasted text when naming the output
+const TEXT_INPUT_NAME: &str = "document";
+/// File stems preferred, in order, when an archive holds several documents
+const MAIN_FILE_STEMS: [&str; 6] = ["main", "index", "paper", "document", "thesis", "readme"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum PdfEngine {
+    #[name = "pdfLaTeX (fastest, limited Unicode)"]
+    Pdflatex,
+    #[name = "XeLaTeX (full Unicode and system fonts)"]
+    Xelatex,
+    #[name = "LuaLaTeX (full Unicode)"]
+    Lualatex,
+}
+
+impl PdfEngine {
+    const fn program(self) -> &'static str {
+        match self {
+            Self::Pdflatex => "pdflatex",
+            Self::Xelatex => "xelatex",
+            Self::Lualatex => "lualatex",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum PaperSize {
+    #[name = "A4"]
+    A4,
+    #[name = "A5"]
+    A5,
+    #[name = "US Letter"]
+    Letter,
+    #[name = "US Legal"]
+    Legal,
+}
+
+impl PaperSize {
+    /// Value of the LaTeX `papersize` variable
+    const fn latex_name(self) -> &'static str {
+        match self {
+            Self::A4 => "a4",
+            Self::A5 => "a5",
+            Self::Letter => "letter",
+            Self::Legal => "legal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum HighlightStyle {
+    #[name = "Pygments"]
+    Pygments,
+    #[name = "Tango"]
+    Tango,
+    #[name = "Kate"]
+    Kate,
+    #[name = "Espresso"]
+    Espresso,
+    #[name = "Zenburn"]
+    Zenburn,
+    #[name = "Breeze Dark"]
+    BreezeDark,
+    #[name = "Haddock"]
+    Haddock,
+    #[name = "Monochrome"]
+    Monochrome,
+    #[name = "None (no highlighting)"]
+    None,
+}
+
+impl HighlightStyle {
+    /// Pandoc's name for the style, or `None` to turn highlighting off
+    const fn pandoc_name(self) -> Option<&'static str> {
+        match self {
+            Self::Pygments => Some("pygments"),
+            Self::Tango => Some("tango"),
+            Self::Kate => Some("kate"),
+            Self::Espresso => Some("espresso"),
+            Self::Zenburn => Some("zenburn"),
+            Self::BreezeDark => Some("breezedark"),
+            Self::Haddock => Some("haddock"),
+            Self::Monochrome => Some("monochrome"),
+            Self::None => None,
+        }
+    }
+
+    /// Closest theme available to the built-in renderer
+    const fn builtin_theme(self) -> Option<&'static str> {
+        match self {
+            Self::Espresso | Self::Zenburn | Self::BreezeDark => Some("base16-ocean.dark"),
+            Self::Monochrome | Self::None => None,
+            _ => Some(markdown::DEFAULT_CODE_THEME),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Renderer {
+    #[name = "Automatic (pandoc, built-in when pandoc is missing)"]
+    Auto,
+    #[name = "Pandoc"]
+    Pandoc,
+    #[name = "Built-in (Markdown to HTML only)"]
+    BuiltIn,
+}
+
+/// Explains what the built-in renderer can convert
+const BUILTIN_UNSUPPORTED: &str =
+    "The built-in renderer only converts Markdown, GFM or CommonMark to HTML.";
+
+/// Longest title or author accepted as document metadata
+const MAX_METADATA_CHARS: usize = 200;
+
+/// Optional pandoc settings chosen on `/convert_document`
+#[derive(Debug, Clone, Default)]
+pub struct DocumentOptions {
+    pub table_of_contents: bool,
+    pub number_sections: bool,
+    pub standalone: bool,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub pdf_engine: Option<PdfEngine>,
+    pub paper_size: Option<PaperSize>,
+    pub margin_mm: Option<u32>,
+    pub highlight_style: Option<HighlightStyle>,
+    pub reference_doc: Option<Attachment>,
+    pub main_file: Option<String>,
+    pub renderer: Option<Renderer>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
 pub enum OutputFormat {
     #[name = "Markdown (md)"]
     Markdown,
@@ -26,6 +277,30 @@ pub enum OutputFormat {
     Odt,
     #[name = "EPUB"]
     Epub,
+    #[name = "PowerPoint slides (pptx)"]
+    Pptx,
+    #[name = "Beamer slides (PDF)"]
+    Beamer,
+    #[name = "reveal.js slides (HTML)"]
+    RevealJs,
+    #[name = "reStructuredText (rst)"]
+    Rst,
+    #[name = "AsciiDoc"]
+    AsciiDoc,
+    #[name = "Org mode"]
+    Org,
+    #[name = "Textile"]
+    Textile,
+    #[name = "MediaWiki"]
+    MediaWiki,
+    #[name = "DokuWiki"]
+    DokuWiki,
+    #[name = "Jira wiki markup"]
+    Jira,
+    #[name = "Plain text (txt)"]
+    PlainText,
+    #[name = "Jupyter Notebook (ipynb)"]
+    Jupyter,
 }
 
 impl OutputFormat {
@@ -39,10 +314,22 @@ impl OutputFormat {
             Self::Rtf => "rtf",
             Self::Odt => "odt",
             Self::Epub => "epub",
+            Self::Pptx => "pptx",
+            Self::Beamer => "pdf",
+            Self::RevealJs => "html",
+            Self::Rst => "rst",
+            Self::AsciiDoc => "adoc",
+            Self::Org => "org",
+            Self::Textile => "textile",
+            Self::MediaWiki => "wiki",
+            // DokuWiki stores its pages as plain .txt files
+            Self::DokuWiki | Self::PlainText => "txt",
+            Self::Jira => "jira",
+            Self::Jupyter => "ipynb",
         }
     }
 
-    const fn pandoc_output_format(self) -> pandoc::OutputFormat {
+    fn pandoc_output_format(self) -> pandoc::OutputFormat {
         match self {
             Self::Markdown => pandoc::OutputFormat::Markdown,
             Self::Html => pandoc::OutputFormat::Html,
@@ -52,6 +339,42 @@ impl OutputFormat {
             Self::Rtf => pandoc::OutputFormat::Rtf,
             Self::Odt => pandoc::OutputFormat::Odt,
             Self::Epub => pandoc::OutputFormat::Epub,
+            Self::Pptx => pandoc::OutputFormat::Other("pptx".to_string()),
+            Self::Beamer => pandoc::OutputFormat::Beamer,
+            Self::RevealJs => pandoc::OutputFormat::Revealjs,
+            Self::Rst => pandoc::OutputFormat::Rst,
+            Self::AsciiDoc => pandoc::OutputFormat::Asciidoc,
+            Self::Org => pandoc::OutputFormat::Org,
+            Self::Textile => pandoc::OutputFormat::Textile,
+            Self::MediaWiki => pandoc::OutputFormat::MediaWiki,
+            Self::DokuWiki => pandoc::OutputFormat::Dokuwiki,
+            Self::Jira => pandoc::OutputFormat::Other("jira".to_string()),
+            Self::PlainText => pandoc::OutputFormat::Plain,
+            Self::Jupyter => pandoc::OutputFormat::Other("ipynb".to_string()),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Whether pandoc takes a `--reference-doc` for this format
+    const fn accepts_reference_doc(self) -> bool {
+        matches!(self, Self::Docx | Self::Odt | Self::Pptx)
+    }
+
+    /// Whether the output is only usable as a standalone file.
+    ///
+    /// A reveal.js fragment lacks the page that loads the slide framework.
+    const fn requires_standalone(self) -> bool {
+        matches!(self, Self::RevealJs)
+    }
+
+    /// Oldest pandoc release with a writer for the format, when it is recent enough to matter
+    const fn minimum_pandoc_version(self) -> Option<&'static str> {
+        match self {
+            Self::Pptx => Some("2.1"),
+            Self::Jupyter => Some("2.6"),
+            Self::Jira => Some("2.7.3"),
+            _ => None,
         }
     }
 }
@@ -69,18 +392,52 @@ fn document_error_message(error: &str, output_format: OutputFormat) -> String {
         || normalized.contains("lualatex")
         || normalized.contains("latex")
     {
-        return "PDF conversion requires a working LaTeX engine such as pdfTeX, XeLaTeX, or LuaLaTeX.".to_string();
+        let target = if output_format == OutputFormat::Beamer {
+            "Beamer slides require"
+        } else {
+            "PDF conversion requires"
+        };
+        return format!(
+            "{} a working LaTeX engine such as pdfTeX, XeLaTeX, or LuaLaTeX.",
+            target
+        );
+    }
+
+    // Archive and built-in renderer problems are described by our own messages
+    if normalized.contains("zip archive") || normalized.contains("built-in renderer") {
+        return error.to_string();
     }
 
     if normalized.contains("pandoc") && normalized.contains("not found") {
-        return "Pandoc is not installed or is not available on the bot host.".to_string();
+        return "Pandoc is not installed or is not available on the bot host, so only Markdown to HTML conversion works.".to_string();
     }
 
     if normalized.contains("unknown reader")
         || normalized.contains("could not find reader")
         || normalized.contains("unknown input format")
     {
-        return "Pandoc could not determine how to read this document. Try a different source format.".to_string();
+        return "Pandoc could not determine how to read this document. Pick an `input_format` that matches it.".to_string();
+    }
+
+    if normalized.contains("unknown writer")
+        || normalized.contains("unknown output format")
+        || normalized.contains("could not find writer")
+    {
+        return match output_format.minimum_pandoc_version() {
+            Some(version) => format!(
+                "{} output needs pandoc {} or newer on the bot host.",
+                output_format.name(),
+                version
+            ),
+            None => format!(
+                "The pandoc on the bot host cannot write {}.",
+                output_format.name()
+            ),
+        };
+    }
+
+    if output_format == OutputFormat::Jupyter && normalized.contains("json") {
+        return "Pandoc could not build a notebook from this document. Code blocks become cells, so check that they are fenced correctly.".to_string();
     }
 
     format!(
@@ -89,9 +446,265 @@ fn document_error_message(error: &str, output_format: OutputFormat) -> String {
     )
 }
 
+/// Check a title or author before it is passed to pandoc as metadata.
+fn validate_metadata(value: &str, label: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() || value.chars().count() > MAX_METADATA_CHARS {
+        return Err(format!(
+            "The {} must be between 1 and {} characters.",
+            label, MAX_METADATA_CHARS
+        ));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(format!("The {} cannot contain line breaks.", label));
+    }
+    Ok(value.to_string())
+}
+
+impl DocumentOptions {
+    /// Check user supplied values against the output format.
+    fn validate(&self, output_format: OutputFormat) -> Result<(), String> {
+        if self.renderer == Some(Renderer::BuiltIn) && output_format != OutputFormat::Html {
+            return Err(BUILTIN_UNSUPPORTED.to_string());
+        }
+        if let Some(title) = &self.title {
+            validate_metadata(title, "title")?;
+        }
+        if let Some(author) = &self.author {
+            validate_metadata(author, "author")?;
+        }
+        if let Some(reference_doc) = &self.reference_doc {
+            let extension = reference_doc
+                .filename
+                .rsplit_once('.')
+                .map(|(_, extension)| extension.to_ascii_lowercase());
+            if !output_format.accepts_reference_doc()
+                || extension.as_deref() != Some(output_format.extension())
+            {
+                return Err(
+                    "A reference document must be a .docx for Word, an .odt for OpenDocument or a .pptx for PowerPoint output."
+                        .to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Translate the options into pandoc arguments.
+    fn pandoc_options(&self, reference_doc: Option<&Path>) -> Vec<PandocOption> {
+        let mut options = Vec::new();
+        // A table of contents is only written into standalone documents
+        if self.standalone || self.table_of_contents {
+            options.push(PandocOption::Standalone);
+        }
+        if self.table_of_contents {
+            options.push(PandocOption::TableOfContents);
+        }
+        if self.number_sections {
+            options.push(PandocOption::NumberSections);
+        }
+        if let Some(title) = &self.title {
+            options.push(PandocOption::Meta(
+                "title".to_string(),
+                Some(title.trim().to_string()),
+            ));
+        }
+        if let Some(author) = &self.author {
+            options.push(PandocOption::Meta(
+                "author".to_string(),
+                Some(author.trim().to_string()),
+            ));
+        }
+        if let Some(engine) = self.pdf_engine {
+            options.push(PandocOption::PdfEngine(PathBuf::from(engine.program())));
+        }
+        if let Some(paper_size) = self.paper_size {
+            options.push(PandocOption::Var(
+                "papersize".to_string(),
+                Some(paper_size.latex_name().to_string()),
+            ));
+        }
+        if let Some(margin) = self.margin_mm {
+            options.push(PandocOption::Var(
+                "geometry".to_string(),
+                Some(format!("margin={}mm", margin)),
+            ));
+        }
+        match self.highlight_style.map(HighlightStyle::pandoc_name) {
+            Some(Some(style)) => options.push(PandocOption::HighlightStyle(style.to_string())),
+            Some(None) => options.push(PandocOption::NoHighlight),
+            None => {}
+        }
+        if let Some(path) = reference_doc {
+            options.push(PandocOption::ReferenceDoc(path.to_path_buf()));
+        }
+        options
+    }
+}
+
+fn is_zip_name(filename: &str) -> bool {
+    filename.to_ascii_lowercase().ends_with(".zip")
+}
+
+fn has_extension(path: &Path, wanted: &str) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(wanted))
+}
+
+/// Choose the document to convert among the files extracted from an archive.
+///
+/// An explicit `main_file` may be a relative path or a bare filename. Otherwise
+/// the shallowest readable document wins, with common names such as `main.md`
+/// breaking ties.
+fn find_main_file(files: &[PathBuf], main_file: Option<&str>) -> Result<PathBuf, String> {
+    if let Some(requested) = main_file {
+        let requested = requested.trim().trim_start_matches("./");
+        return files
+            .iter()
+            .find(|path| {
+                path.to_string_lossy().replace('\\', "/") == requested
+                    || path.file_name().is_some_and(|name| name == requested)
+            })
+            .cloned()
+            .ok_or_else(|| format!("The ZIP archive has no file named `{}`.", requested));
+    }
+
+    let candidates: Vec<&PathBuf> = files
+        .iter()
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(InputFormat::from_extension)
+                .is_some_and(|format| format != InputFormat::Csv)
+        })
+        .collect();
+    let depth = candidates
+        .iter()
+        .map(|path| path.components().count())
+        .min()
+        .unwrap_or(0);
+    let top: Vec<&PathBuf> = candidates
+        .into_iter()
+        .filter(|path| path.components().count() == depth)
+        .collect();
+
+    match top.as_slice() {
+        [] => Err("The ZIP archive contains no document pandoc can read.".to_string()),
+        [only] => Ok((*only).clone()),
+        several => MAIN_FILE_STEMS
+            .iter()
+            .find_map(|stem| {
+                several.iter().find(|path| {
+                    path.file_stem()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.eq_ignore_ascii_case(stem))
+                })
+            })
+            .map(|path| (*path).clone())
+            .ok_or_else(|| {
+                let names: Vec<String> = several
+                    .iter()
+                    .take(5)
+                    .map(|path| format!("`{}`", path.display()))
+                    .collect();
+                format!(
+                    "The ZIP archive contains several documents ({}). Set `main_file` to pick one.",
+                    names.join(", ")
+                )
+            }),
+    }
+}
+
+/// Pandoc arguments that let a document find the other files of its archive.
+///
+/// Relative links resolve from the main file's folder and then the archive
+/// root. When the archive has `.bib` files, citations are processed with
+/// them and any `.csl` style.
+fn archive_options(dir: &Path, main_file: &Path, files: &[PathBuf]) -> Vec<PandocOption> {
+    let main_dir = dir.join(main_file.parent().unwrap_or(Path::new("")));
+    let mut options = vec![PandocOption::ResourcePath(vec![
+        main_dir,
+        dir.to_path_buf(),
+    ])];
+
+    let bibliographies: Vec<PandocOption> = files
+        .iter()
+        .filter(|path| has_extension(path, "bib"))
+        .map(|path| PandocOption::Bibliography(dir.join(path)))
+        .collect();
+    // `--citeproc` needs pandoc 2.11, so only ask for it when it is used
+    if bibliographies.is_empty() {
+        return options;
+    }
+    options.push(PandocOption::Citeproc);
+    options.extend(bibliographies);
+    if let Some(style) = files.iter().find(|path| has_extension(path, "csl")) {
+        options.push(PandocOption::Csl(dir.join(style)));
+    }
+    options
+}
+
+/// Whether the built-in renderer can stand in for pandoc
+fn builtin_supports(input_format: Option<InputFormat>, output_format: OutputFormat) -> bool {
+    matches!(
+        input_format,
+        Some(InputFormat::Markdown | InputFormat::Gfm | InputFormat::Commonmark)
+    ) && output_format == OutputFormat::Html
+}
+
+/// Convert Markdown to HTML without pandoc.
+///
+/// Only the standalone, title, author and highlight options apply; a table of
+/// contents still produces a standalone page as it does with pandoc.
+fn render_builtin(markdown: &str, input_name: &str, options: &DocumentOptions) -> Vec<u8> {
+    let theme = match options.highlight_style {
+        Some(style) => style.builtin_theme(),
+        None => Some(markdown::DEFAULT_CODE_THEME),
+    };
+    let body = markdown::render_html(markdown, theme);
+    if !(options.standalone || options.table_of_contents) {
+        return body.into_bytes();
+    }
+    let title = match &options.title {
+        Some(title) => title.trim().to_string(),
+        None => file_stem(input_name),
+    };
+    markdown::standalone_page(&body, &title, options.author.as_deref().map(str::trim)).into_bytes()
+}
+
+/// Pick the reader from the explicit choice or the filename.
+///
+/// Names without an extension are read as Markdown. Unlisted extensions give
+/// `None`, leaving pandoc to recognise the format itself.
+fn resolve_input_format(filename: &str, input_format: Option<InputFormat>) -> Option<InputFormat> {
+    input_format.or_else(|| match filename.rsplit_once('.') {
+        Some((_, extension)) => InputFormat::from_extension(extension),
+        None => Some(InputFormat::Markdown),
+    })
+}
+
+/// Name of the temporary input file, keeping the original extension when
+/// pandoc has to detect the format from it.
+fn input_file_name(filename: &str, input_format: Option<InputFormat>) -> String {
+    match (input_format, filename.rsplit_once('.')) {
+        (Some(format), _) => format!("input.{}", format.extension()),
+        (None, Some((_, extension))) => format!("input.{}", sanitize_filename(extension)),
+        (None, None) => "input".to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{OutputFormat, document_error_message, output_filename};
+    use super::{
+        DocumentInput, DocumentOptions, HighlightStyle, InputFormat, OutputFormat, PdfEngine,
+        Renderer, archive_options, convert_document_inner, document_error_message, find_main_file,
+        has_extension, input_file_name, output_filename, resolve_input_format, validate_metadata,
+    };
+    use crate::compression::compress::zip_entries;
+    use crate::compression::decompress::extract_zip_to_dir;
+    use pandoc::PandocOption;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn output_filename_preserves_multi_dot_stem() {
@@ -106,6 +719,168 @@ mod tests {
     fn document_error_message_maps_latex_failures() {
         let message = document_error_message("xelatex not found", OutputFormat::Pdf);
         assert!(message.contains("LaTeX engine"));
+        let message = document_error_message("! LaTeX Error: beamer.cls", OutputFormat::Beamer);
+        assert!(message.starts_with("Beamer slides"));
+    }
+
+    #[test]
+    fn slide_and_markup_targets_use_their_own_extensions() {
+        assert_eq!(output_filename("deck.md", OutputFormat::Pptx), "deck.pptx");
+        assert_eq!(output_filename("deck.md", OutputFormat::Beamer), "deck.pdf");
+        assert_eq!(
+            output_filename("deck.md", OutputFormat::RevealJs),
+            "deck.html"
+        );
+        assert_eq!(
+            output_filename("notes.md", OutputFormat::AsciiDoc),
+            "notes.adoc"
+        );
+        assert_eq!(
+            output_filename("notes.md", OutputFormat::DokuWiki),
+            "notes.txt"
+        );
+
+        let message = document_error_message("Unknown writer: jira", OutputFormat::Jira);
+        assert!(message.contains("pandoc 2.7.3"));
+    }
+
+    #[test]
+    fn input_format_falls_back_to_markdown_for_plain_text() {
+        assert_eq!(
+            resolve_input_format("notes.txt", None),
+            Some(InputFormat::Markdown)
+        );
+        assert_eq!(
+            resolve_input_format("README", None),
+            Some(InputFormat::Markdown)
+        );
+        assert_eq!(
+            resolve_input_format("Report.DOCX", None),
+            Some(InputFormat::Docx)
+        );
+        assert_eq!(
+            resolve_input_format("notes.txt", Some(InputFormat::Rst)),
+            Some(InputFormat::Rst)
+        );
+    }
+
+    #[test]
+    fn unlisted_extensions_are_left_for_pandoc_to_detect() {
+        assert_eq!(resolve_input_format("outline.opml", None), None);
+        assert_eq!(input_file_name("outline.opml", None), "input.opml");
+        assert_eq!(input_file_name("ls.1", None), "input.1");
+        assert_eq!(
+            input_file_name("outline.opml", Some(InputFormat::Rst)),
+            "input.rst"
+        );
+    }
+
+    #[test]
+    fn document_options_map_to_pandoc_arguments() {
+        let options = DocumentOptions {
+            table_of_contents: true,
+            title: Some(" Report ".to_string()),
+            pdf_engine: Some(PdfEngine::Xelatex),
+            margin_mm: Some(20),
+            highlight_style: Some(HighlightStyle::None),
+            ..DocumentOptions::default()
+        };
+        let arguments = options.pandoc_options(Some(Path::new("/tmp/reference.docx")));
+        assert_eq!(
+            arguments,
+            vec![
+                PandocOption::Standalone,
+                PandocOption::TableOfContents,
+                PandocOption::Meta("title".to_string(), Some("Report".to_string())),
+                PandocOption::PdfEngine(PathBuf::from("xelatex")),
+                PandocOption::Var("geometry".to_string(), Some("margin=20mm".to_string())),
+                PandocOption::NoHighlight,
+                PandocOption::ReferenceDoc(PathBuf::from("/tmp/reference.docx")),
+            ]
+        );
+        assert!(DocumentOptions::default().pandoc_options(None).is_empty());
+    }
+
+    #[test]
+    fn metadata_rejects_line_breaks_and_long_values() {
+        assert_eq!(validate_metadata(" Jane ", "author").unwrap(), "Jane");
+        assert!(validate_metadata("a\nb", "title").is_err());
+        assert!(validate_metadata(&"x".repeat(201), "title").is_err());
+    }
+
+    #[tokio::test]
+    async fn builtin_renderer_converts_markdown_without_pandoc() {
+        let options = DocumentOptions {
+            standalone: true,
+            title: Some("Notes".to_string()),
+            renderer: Some(Renderer::BuiltIn),
+            ..DocumentOptions::default()
+        };
+        let input = DocumentInput::Text("| a |\n|---|\n| 1 |".to_string());
+        let (html, filename) = convert_document_inner(input, None, OutputFormat::Html, &options)
+            .await
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert_eq!(filename, "document.html");
+        assert!(html.contains("<title>Notes</title>") && html.contains("<table>"));
+
+        let input = DocumentInput::Text("= Title".to_string());
+        let error =
+            convert_document_inner(input, Some(InputFormat::Rst), OutputFormat::Html, &options)
+                .await
+                .unwrap_err();
+        assert!(
+            document_error_message(&error.to_string(), OutputFormat::Html).contains("built-in")
+        );
+    }
+
+    #[test]
+    fn main_file_is_detected_or_requested() {
+        let files: Vec<PathBuf> = ["chapters/intro.md", "main.md", "notes.md", "refs.bib"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(
+            find_main_file(&files, None).unwrap(),
+            PathBuf::from("main.md")
+        );
+        assert_eq!(
+            find_main_file(&files, Some("intro.md")).unwrap(),
+            PathBuf::from("chapters/intro.md")
+        );
+        assert!(find_main_file(&files, Some("missing.md")).is_err());
+
+        let ambiguous = [PathBuf::from("a.md"), PathBuf::from("b.md")];
+        let message = find_main_file(&ambiguous, None).unwrap_err();
+        assert!(message.contains("main_file"));
+        assert!(document_error_message(&message, OutputFormat::Pdf).contains("`a.md`"));
+    }
+
+    #[test]
+    fn archives_extract_safely_with_citations_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = zip_entries(&[
+            ("paper.md".to_string(), b"# Title".to_vec()),
+            ("images/plot.png".to_string(), vec![0; 4]),
+            ("refs.bib".to_string(), b"@book{x}".to_vec()),
+        ])
+        .unwrap();
+        let files = extract_zip_to_dir(&archive, dir.path()).unwrap();
+        assert!(dir.path().join("images/plot.png").is_file());
+
+        let options = archive_options(dir.path(), Path::new("paper.md"), &files);
+        assert!(options.contains(&PandocOption::Citeproc));
+        assert!(options.contains(&PandocOption::Bibliography(dir.path().join("refs.bib"))));
+        let sources: Vec<PathBuf> = files
+            .iter()
+            .filter(|path| !has_extension(path, "bib"))
+            .cloned()
+            .collect();
+        let without_bib = archive_options(dir.path(), Path::new("paper.md"), &sources);
+        assert!(!without_bib.contains(&PandocOption::Citeproc));
+
+        let escaping = zip_entries(&[("../escape.md".to_string(), Vec::new())]).unwrap();
+        assert!(extract_zip_to_dir(&escaping, dir.path()).is_err());
     }
 }
 
@@ -113,25 +888,78 @@ mod tests {
 ///
 /// Returns the converted file bytes and the output filename.
 pub async fn convert_document_inner(
-    file: &Attachment,
+    input: DocumentInput<'_>,
+    input_format: Option<InputFormat>,
     output_format: OutputFormat,
+    options: &DocumentOptions,
 ) -> Result<(Vec<u8>, String), Error> {
-    validate_attachment_size(file).map_err(Error::from)?;
-
-    let original_extension = file.filename.rsplit('.').next().unwrap_or("tmp");
-    let input_temp_file = Builder::new()
-        .suffix(&format!(".{}", original_extension))
-        .tempfile()
-        .map_err(|e| Error::from(format!("Failed to create temporary file: {}", e)))?;
-    let input_path = input_temp_file.path().to_path_buf();
+    let (input_name, input_data) = match input {
+        DocumentInput::File(file) => {
+            validate_attachment_size(file).map_err(Error::from)?;
+            let data = file
+                .download()
+                .await
+                .map_err(|e| Error::from(format!("Failed to download file: {}", e)))?;
+            (file.filename.clone(), data)
+        }
+        DocumentInput::Text(text) => (TEXT_INPUT_NAME.to_string(), text.into_bytes()),
+    };
 
-    let file_data = file
-        .download()
-        .await
-        .map_err(|e| Error::from(format!("Failed to download file: {}", e)))?;
-    fs::write(&input_path, file_data)
+    // Holds the input, or the extracted archive, until pandoc has finished
+    let workspace = Builder::new()
+        .prefix("conversia_doc_")
+        .tempdir()
+        .map_err(|e| Error::from(format!("Failed to create temporary directory: {}", e)))?;
+    let (format_name, input_path, archive_options) = if is_zip_name(&input_name) {
+        let dir = workspace.path().to_path_buf();
+        let main_file = options.main_file.clone();
+        tokio::task::spawn_blocking(move || {
+            let files = extract_zip_to_dir(&input_data, &dir)?;
+            let main = find_main_file(&files, main_file.as_deref()).map_err(Error::from)?;
+            let archive_options = archive_options(&dir, &main, &files);
+            Ok::<_, Error>((
+                main.to_string_lossy().to_string(),
+                dir.join(&main),
+                archive_options,
+            ))
+        })
         .await
-        .map_err(|e| Error::from(format!("Failed to write file: {}", e)))?;
+        .map_err(|e| Error::from(format!("Task failed: {}", e)))??
+    } else {
+        let input_file =
+            input_file_name(&input_name, resolve_input_format(&input_name, input_format));
+        let input_path = workspace.path().join(input_file);
+        fs::write(&input_path, input_data)
+            .await
+            .map_err(|e| Error::from(format!("Failed to write file: {}", e)))?;
+        (input_name.clone(), input_path, Vec::new())
+    };
+    let input_format = resolve_input_format(&format_name, input_format);
+
+    // The reference document must outlive the pandoc run, so its temp file is kept here
+    let reference_temp_file = match &options.reference_doc {
+        Some(reference_doc) => {
+            validate_attachment_size(reference_doc).map_err(Error::from)?;
+            let data = reference_doc.download().await.map_err(|e| {
+                Error::from(format!("Failed to download reference document: {}", e))
+            })?;
+            let temp_file = Builder::new()
+                .suffix(&format!(".{}", output_format.extension()))
+                .tempfile()
+                .map_err(|e| Error::from(format!("Failed to create temporary file: {}", e)))?;
+            fs::write(temp_file.path(), data)
+                .await
+                .map_err(|e| Error::from(format!("Failed to write file: {}", e)))?;
+            Some(temp_file)
+        }
+        None => None,
+    };
+    let mut pandoc_options =
+        options.pandoc_options(reference_temp_file.as_ref().map(|file| file.path()));
+    pandoc_options.extend(archive_options);
+    if output_format.requires_standalone() && !pandoc_options.contains(&PandocOption::Standalone) {
+        pandoc_options.push(PandocOption::Standalone);
+    }
 
     let output_temp_file = Builder::new()
         .suffix(&format!(".{}", output_format.extension()))
@@ -139,41 +967,143 @@ pub async fn convert_document_inner(
         .map_err(|e| Error::from(format!("Failed to create output file: {}", e)))?;
     let output_path = output_temp_file.path().to_path_buf();
 
+    let renderer = options.renderer.unwrap_or(Renderer::Auto);
+    let builtin_possible = builtin_supports(input_format, output_format);
+    if renderer == Renderer::BuiltIn && !builtin_possible {
+        return Err(Error::from(BUILTIN_UNSUPPORTED));
+    }
+
     let input_path_clone = input_path.clone();
     let output_path_clone = output_path.clone();
     let output_format_clone = output_format;
 
-    tokio::task::spawn_blocking(move || {
-        let mut pandoc = pandoc::new();
-        pandoc.add_input(&input_path_clone);
-        pandoc.set_output(pandoc::OutputKind::File(output_path_clone));
+    let pandoc_result = if renderer == Renderer::BuiltIn {
+        Err(pandoc::PandocError::PandocNotFound)
+    } else {
+        tokio::task::spawn_blocking(move || {
+            let mut pandoc = pandoc::new();
+            pandoc.add_input(&input_path_clone);
+            pandoc.set_output(pandoc::OutputKind::File(output_path_clone));
 
-        pandoc.set_output_format(output_format_clone.pandoc_output_format(), Vec::new());
-        pandoc.execute()
-    })
-    .await
-    .map_err(|e| Error::from(format!("Task failed: {}", e)))??;
+            if let Some(input_format) = input_format {
+                pandoc.set_input_format(input_format.pandoc_input_format(), Vec::new());
+            }
+            pandoc.set_output_format(output_format_clone.pandoc_output_format(), Vec::new());
+            pandoc.add_options(&pandoc_options);
+            pandoc.execute()
+        })
+        .await
+        .map_err(|e| Error::from(format!("Task failed: {}", e)))?
+    };
+    match pandoc_result {
+        Ok(_) => {}
+        // Slim hosts without pandoc can still turn Markdown into HTML
+        Err(pandoc::PandocError::PandocNotFound)
+            if builtin_possible && renderer != Renderer::Pandoc =>
+        {
+            let source = fs::read(&input_path)
+                .await
+                .map_err(|e| Error::from(format!("Failed to read file: {}", e)))?;
+            let input_name = input_name.clone();
+            let options = options.clone();
+            let html = tokio::task::spawn_blocking(move || {
+                render_builtin(&String::from_utf8_lossy(&source), &input_name, &options)
+            })
+            .await
+            .map_err(|e| Error::from(format!("Task failed: {}", e)))?;
+            fs::write(&output_path, html)
+                .await
+                .map_err(|e| Error::from(format!("Failed to write file: {}", e)))?;
+        }
+        Err(e) => return Err(e.into()),
+    }
 
     let converted_data = fs::read(&output_path)
         .await
         .map_err(|e| Error::from(format!("Failed to read converted file: {}", e)))?;
     validate_output_size(converted_data.len(), "Converted document").map_err(Error::from)?;
-    let output_filename = output_filename(&file.filename, output_format);
+    let output_filename = output_filename(&input_name, output_format);
 
     // Return converted bytes and output filename
     Ok((converted_data, output_filename))
 }
 
 /// Convert a document
+#[allow(clippy::too_many_arguments)]
 #[poise::command(slash_command)]
 pub async fn convert_document(
     ctx: Context<'_>,
-    #[description = "Document to convert"] file: Attachment,
     #[description = "Document format to convert to"] output_format: OutputFormat,
+    #[description = "Document to convert, or a ZIP with the document and its images"] file: Option<
+        Attachment,
+    >,
+    #[description = "Text to convert instead of a file, e.g. pasted Markdown"] text: Option<String>,
+    #[description = "Format of the input (default: detected from the file extension)"]
+    input_format: Option<InputFormat>,
+    #[description = "Add a table of contents"] toc: Option<bool>,
+    #[description = "Number the section headings"] number_sections: Option<bool>,
+    #[description = "Produce a complete document with header (HTML, LaTeX, Markdown)"]
+    standalone: Option<bool>,
+    #[description = "Document title"] title: Option<String>,
+    #[description = "Document author"] author: Option<String>,
+    #[description = "LaTeX engine for PDF output (XeLaTeX for Unicode text)"] pdf_engine: Option<
+        PdfEngine,
+    >,
+    #[description = "Paper size for PDF and LaTeX output"] paper_size: Option<PaperSize>,
+    #[description = "Page margin in millimetres for PDF and LaTeX output"]
+    #[min = 5]
+    #[max = 50]
+    margin: Option<u32>,
+    #[description = "Syntax highlighting style for code blocks"] highlight_style: Option<
+        HighlightStyle,
+    >,
+    #[description = "Styled .docx, .odt or .pptx whose styles the output should use"]
+    reference_doc: Option<Attachment>,
+    #[description = "Main document inside a ZIP upload, e.g. paper.md (default: detected)"]
+    main_file: Option<String>,
+    #[description = "Converter to use (default: pandoc, or the built-in one if it is missing)"]
+    renderer: Option<Renderer>,
 ) -> Result<(), Error> {
     ctx.defer().await?;
 
-    match convert_document_inner(&file, output_format).await {
+    let options = DocumentOptions {
+        table_of_contents: toc.unwrap_or(false),
+        number_sections: number_sections.unwrap_or(false),
+        standalone: standalone.unwrap_or(false),
+        title,
+        author,
+        pdf_engine,
+        paper_size,
+        margin_mm: margin,
+        highlight_style,
+        reference_doc,
+        main_file,
+        renderer,
+    };
+
+    let input = match (&file, text) {
+        (Some(file), None) => Ok(DocumentInput::File(file)),
+        (None, Some(_)) if input_format.is_some_and(InputFormat::is_binary) => {
+            Err("That input format is a binary file and must be uploaded, not pasted.")
+        }
+        (None, Some(text)) if !text.trim().is_empty() => Ok(DocumentInput::Text(text)),
+        (Some(_), Some(_)) | (None, _) => Err("Provide either a `file` or some `text` to convert."),
+    }
+    .map_err(str::to_string)
+    .and_then(|input| options.validate(output_format).map(|()| input));
+    let input = match input {
+        Ok(input) => input,
+        Err(message) => {
+            let embed = CreateEmbed::default()
+                .title("❌ Conversion Failed")
+                .description(message)
+                .color(0xff4444);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    match convert_document_inner(input, input_format, output_format, &options).await {
         Ok((converted_data, output_filename)) => {
             let attachment = CreateAttachment::bytes(converted_data, &output_filename);
 
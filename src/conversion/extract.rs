@@ -0,0 +1,771 @@
+use crate::attachments::{download_attachment, sanitize_filename, validate_output_size};
+use crate::conversion::pdf_tools::load_pdf;
+use crate::embeds::{EMBED_SUCCESS_COLOR, INLINE_PREVIEW_LIMIT, error_embed};
+use crate::utils::file_stem;
+use crate::{Context, Error};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// Most bytes decompressed from a document container or a whole PDF
+const MAX_EXTRACTED_BYTES: u64 = 25 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Odt,
+    Epub,
+}
+
+impl DocumentKind {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Pdf => "PDF",
+            Self::Docx => "Word (DOCX)",
+            Self::Odt => "OpenDocument Text (ODT)",
+            Self::Epub => "EPUB",
+        }
+    }
+}
+
+/// How text is pulled out of one XML dialect. Element names are local names.
+struct XmlRules {
+    /// When not empty, only text inside these elements is kept
+    text_elements: &'static [&'static str],
+    /// Elements that start a new line
+    blocks: &'static [&'static str],
+    line_breaks: &'static [&'static str],
+    tabs: &'static [&'static str],
+    /// Elements that mark the start of a new page
+    page_breaks: &'static [&'static str],
+    /// Elements whose content is ignored entirely
+    skipped: &'static [&'static str],
+    /// Element standing for `c` spaces (ODF's `text:s`)
+    spaces: Option<&'static str>,
+    /// Collapse runs of whitespace as HTML does
+    collapse_whitespace: bool,
+}
+
+const DOCX_RULES: XmlRules = XmlRules {
+    text_elements: &["t"],
+    blocks: &["p"],
+    line_breaks: &["br", "cr"],
+    tabs: &["tab"],
+    page_breaks: &["lastRenderedPageBreak"],
+    // `pPr` holds tab stop definitions, which are empty `tab` elements too
+    skipped: &["instrText", "delText", "pPr"],
+    spaces: None,
+    collapse_whitespace: false,
+};
+
+const ODT_RULES: XmlRules = XmlRules {
+    text_elements: &["p", "h"],
+    blocks: &["p", "h"],
+    line_breaks: &["line-break"],
+    tabs: &["tab"],
+    page_breaks: &["soft-page-break"],
+    skipped: &[
+        "annotation",
+        "tracked-changes",
+        "automatic-styles",
+        "font-face-decls",
+    ],
+    spaces: Some("s"),
+    collapse_whitespace: false,
+};
+
+const XHTML_RULES: XmlRules = XmlRules {
+    text_elements: &[],
+    blocks: &[
+        "p",
+        "div",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "li",
+        "tr",
+        "blockquote",
+        "pre",
+        "section",
+        "article",
+        "dt",
+        "dd",
+        "figcaption",
+    ],
+    line_breaks: &["br"],
+    tabs: &["td", "th"],
+    page_breaks: &[],
+    skipped: &["head", "script", "style"],
+    spaces: None,
+    collapse_whitespace: true,
+};
+
+fn xml_error(e: impl std::fmt::Display) -> Error {
+    Error::from(format!("Failed to read the document XML: {}", e))
+}
+
+fn separator(label: &str, number: usize) -> String {
+    format!("--- {} {} ---", label, number)
+}
+
+/// Words in extracted text, leaving out the lines added by [`separator`].
+fn count_words(text: &str) -> usize {
+    let is_separator = |line: &str| {
+        line.strip_prefix("--- ")
+            .and_then(|line| line.strip_suffix(" ---"))
+            .and_then(|line| line.strip_prefix("Page ").or(line.strip_prefix("Section ")))
+            .is_some_and(|number| number.parse::<usize>().is_ok())
+    };
+    text.lines()
+        .filter(|line| !is_separator(line))
+        .flat_map(str::split_whitespace)
+        .count()
+}
+
+fn attribute(reader: &Reader<&[u8]>, element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|attribute| {
+            attribute
+                .decode_and_unescape_value(reader.decoder())
+                .ok()
+                .map(|value| value.into_owned())
+        })
+}
+
+/// Accumulates extracted text and the page separators between it.
+struct TextWriter {
+    text: String,
+    collapse_whitespace: bool,
+    page: usize,
+    /// Whether any text was written since the last page break
+    page_has_text: bool,
+}
+
+impl TextWriter {
+    fn push(&mut self, text: &str) {
+        if self.collapse_whitespace {
+            for (index, word) in text.split_whitespace().enumerate() {
+                let starts_with_space = text.starts_with(char::is_whitespace);
+                if (index > 0 || starts_with_space) && !self.text.ends_with([' ', '\n', '\t']) {
+                    self.text.push(' ');
+                }
+                self.text.push_str(word);
+            }
+            if text.ends_with(char::is_whitespace) && !self.text.ends_with([' ', '\n', '\t']) {
+                self.text.push(' ');
+            }
+        } else {
+            self.text.push_str(text);
+        }
+        self.page_has_text |= !text.trim().is_empty();
+    }
+
+    fn new_line(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn page_break(&mut self) {
+        // Word marks explicit breaks twice, so a break without text in between is ignored
+        if !self.page_has_text {
+            return;
+        }
+        self.page += 1;
+        self.page_has_text = false;
+        self.new_line();
+        self.text
+            .push_str(&format!("\n{}\n\n", separator("Page", self.page)));
+    }
+}
+
+/// Extract readable text from an XML document following `rules`.
+fn xml_text(xml: &[u8], rules: &XmlRules) -> Result<String, Error> {
+    let is = |list: &[&str], name: &[u8]| list.iter().any(|item| item.as_bytes() == name);
+    let mut reader = Reader::from_reader(xml);
+    let mut writer = TextWriter {
+        text: String::new(),
+        collapse_whitespace: rules.collapse_whitespace,
+        page: 1,
+        page_has_text: false,
+    };
+    let mut skip_depth = 0usize;
+    let mut text_depth = 0usize;
+
+    loop {
+        let event = reader.read_event().map_err(xml_error)?;
+        let capturing = skip_depth == 0 && (rules.text_elements.is_empty() || text_depth > 0);
+        match event {
+            Event::Start(element) => {
+                let name = element.local_name();
+                let name = name.as_ref();
+                if skip_depth > 0 || is(rules.skipped, name) {
+                    skip_depth += 1;
+                    continue;
+                }
+                if is(rules.text_elements, name) {
+                    text_depth += 1;
+                }
+                if is(rules.blocks, name) || is(rules.line_breaks, name) {
+                    writer.new_line();
+                }
+            }
+            Event::Empty(element) if skip_depth == 0 => {
+                let name = element.local_name();
+                let name = name.as_ref();
+                if is(rules.page_breaks, name) {
+                    writer.page_break();
+                } else if is(rules.line_breaks, name) {
+                    if attribute(&reader, &element, "type").as_deref() == Some("page") {
+                        writer.page_break();
+                    } else {
+                        writer.text.push('\n');
+                    }
+                } else if is(rules.tabs, name) {
+                    writer.text.push('\t');
+                } else if rules.spaces.is_some_and(|spaces| spaces.as_bytes() == name) {
+                    let count = attribute(&reader, &element, "c")
+                        .and_then(|count| count.parse::<usize>().ok())
+                        .unwrap_or(1)
+                        .min(100);
+                    writer.text.push_str(&" ".repeat(count));
+                } else if is(rules.blocks, name) {
+                    writer.new_line();
+                }
+            }
+            Event::End(element) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                    continue;
+                }
+                let name = element.local_name();
+                let name = name.as_ref();
+                if is(rules.text_elements, name) {
+                    text_depth = text_depth.saturating_sub(1);
+                }
+                if is(rules.blocks, name) {
+                    writer.new_line();
+                } else if is(rules.tabs, name) {
+                    writer.text.push('\t');
+                }
+            }
+            Event::Text(text) if capturing => writer.push(&text.decode().map_err(xml_error)?),
+            Event::CData(text) if capturing => writer.push(&text.decode().map_err(xml_error)?),
+            Event::GeneralRef(reference) if capturing => {
+                if let Some(c) = reference.resolve_char_ref().map_err(xml_error)? {
+                    writer.push(c.encode_utf8(&mut [0; 4]));
+                } else if let Some(entity) =
+                    resolve_predefined_entity(&reference.decode().map_err(xml_error)?)
+                {
+                    writer.push(entity);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(writer.text)
+}
+
+/// Trim trailing spaces and collapse runs of blank lines.
+fn tidy(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output.trim().to_string()
+}
+
+/// Read one entry of a ZIP container, within the shared decompression budget.
+fn read_entry(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+    budget: &mut u64,
+) -> Result<Vec<u8>, Error> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| Error::from(format!("The document is missing `{}`.", name)))?;
+    let mut data = Vec::new();
+    entry
+        .take(*budget + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| Error::from(format!("Failed to read `{}`: {}", name, e)))?;
+    if data.len() as u64 > *budget {
+        return Err(Error::from(
+            "The document is too large to extract safely once decompressed.",
+        ));
+    }
+    *budget -= data.len() as u64;
+    Ok(data)
+}
+
+/// Identify the document type from its content rather than its filename.
+pub fn detect_kind(data: &[u8]) -> Option<DocumentKind> {
+    if data
+        .get(..1024)
+        .unwrap_or(data)
+        .windows(5)
+        .any(|window| window == b"%PDF-")
+    {
+        return Some(DocumentKind::Pdf);
+    }
+    let mut archive = ZipArchive::new(Cursor::new(data)).ok()?;
+    if archive.by_name("word/document.xml").is_ok() {
+        return Some(DocumentKind::Docx);
+    }
+    let mut mimetype = String::new();
+    if let Ok(entry) = archive.by_name("mimetype") {
+        entry.take(256).read_to_string(&mut mimetype).ok()?;
+    }
+    match mimetype.trim() {
+        "application/vnd.oasis.opendocument.text" => Some(DocumentKind::Odt),
+        "application/epub+zip" => Some(DocumentKind::Epub),
+        _ if archive.by_name("META-INF/container.xml").is_ok() => Some(DocumentKind::Epub),
+        _ => None,
+    }
+}
+
+/// Resolve a manifest `href` against the folder of the package file.
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut decoded = Vec::with_capacity(href.len());
+    let bytes = href.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = href
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            index += 3;
+            continue;
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    let href = String::from_utf8_lossy(&decoded);
+
+    let mut parts: Vec<&str> = base_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Chapter files of an EPUB in reading order.
+fn epub_spine(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    budget: &mut u64,
+) -> Result<Vec<String>, Error> {
+    let container = read_entry(archive, "META-INF/container.xml", budget)?;
+    let mut reader = Reader::from_reader(container.as_slice());
+    let mut package_path = None;
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref() == b"rootfile" =>
+            {
+                package_path = attribute(&reader, &element, "full-path");
+                break;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let package_path =
+        package_path.ok_or_else(|| Error::from("The EPUB does not name its package file."))?;
+
+    let package = read_entry(archive, &package_path, budget)?;
+    let base_dir = package_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut reader = Reader::from_reader(package.as_slice());
+    let mut manifest = Vec::new();
+    let mut spine = Vec::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) = (
+                        attribute(&reader, &element, "id"),
+                        attribute(&reader, &element, "href"),
+                    ) {
+                        manifest.push((id, href));
+                    }
+                }
+                b"itemref" => spine.extend(attribute(&reader, &element, "idref")),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(spine
+        .iter()
+        .filter_map(|idref| manifest.iter().find(|(id, _)| id == idref))
+        .map(|(_, href)| resolve_href(base_dir, href))
+        .collect())
+}
+
+/// Text of a document with its separators, and the number of pages or sections.
+pub struct ExtractedText {
+    pub text: String,
+    /// Pages for PDFs, chapters for EPUBs, page breaks + 1 otherwise
+    pub parts: usize,
+}
+
+fn extract_pdf(data: &[u8]) -> Result<ExtractedText, Error> {
+    let mut doc = load_pdf(data)?;
+    let pages = doc.get_pages();
+    let mut text = String::new();
+    // Content streams are decompressed in place, so extraction does not inflate
+    // them again and a stream shared by several pages is only charged once.
+    let mut budget = MAX_EXTRACTED_BYTES as usize;
+    for (&page, &page_id) in &pages {
+        let mut readable = true;
+        for stream_id in doc.get_page_contents(page_id) {
+            let Ok(stream) = doc
+                .get_object_mut(stream_id)
+                .and_then(lopdf::Object::as_stream_mut)
+            else {
+                continue;
+            };
+            if !stream.is_compressed() {
+                continue;
+            }
+            match stream.decompress_with_limit(budget) {
+                Ok(()) => budget = budget.saturating_sub(stream.content.len()),
+                Err(lopdf::Error::Decompress(lopdf::DecompressError::MemoryLimitExceeded {
+                    ..
+                })) => {
+                    return Err(Error::from(
+                        "The document is too large to extract safely once decompressed.",
+                    ));
+                }
+                Err(_) => readable = false,
+            }
+        }
+        let page_text = if readable {
+            doc.extract_text_with_limit(&[page], MAX_EXTRACTED_BYTES as usize)
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        text.push_str(&format!(
+            "{}\n\n{}\n\n",
+            separator("Page", page as usize),
+            page_text.trim()
+        ));
+    }
+    Ok(ExtractedText {
+        text,
+        parts: pages.len(),
+    })
+}
+
+/// Extract the text of a PDF, DOCX, ODT or EPUB document.
+pub fn extract_document_text(data: &[u8], kind: DocumentKind) -> Result<ExtractedText, Error> {
+    let extracted = match kind {
+        DocumentKind::Pdf => extract_pdf(data)?,
+        DocumentKind::Docx | DocumentKind::Odt => {
+            let mut archive = ZipArchive::new(Cursor::new(data)).map_err(xml_error)?;
+            let mut budget = MAX_EXTRACTED_BYTES;
+            let (entry, rules) = match kind {
+                DocumentKind::Docx => ("word/document.xml", &DOCX_RULES),
+                _ => ("content.xml", &ODT_RULES),
+            };
+            let xml = read_entry(&mut archive, entry, &mut budget)?;
+            let text = xml_text(&xml, rules)?;
+            let parts = text.matches("\n--- Page ").count() + 1;
+            ExtractedText { text, parts }
+        }
+        DocumentKind::Epub => {
+            let mut archive = ZipArchive::new(Cursor::new(data)).map_err(xml_error)?;
+            let mut budget = MAX_EXTRACTED_BYTES;
+            let chapters = epub_spine(&mut archive, &mut budget)?;
+            let mut text = String::new();
+            for (index, chapter) in chapters.iter().enumerate() {
+                let xhtml = read_entry(&mut archive, chapter, &mut budget)?;
+                let chapter_text = xml_text(&xhtml, &XHTML_RULES)?;
+                text.push_str(&format!(
+                    "{}\n\n{}\n\n",
+                    separator("Section", index + 1),
+                    chapter_text.trim()
+                ));
+            }
+            ExtractedText {
+                text,
+                parts: chapters.len(),
+            }
+        }
+    };
+    Ok(ExtractedText {
+        text: tidy(&extracted.text),
+        parts: extracted.parts,
+    })
+}
+
+/// Extract the plain text of a PDF, DOCX, ODT or EPUB document
+#[poise::command(slash_command)]
+pub async fn extract_text(
+    ctx: Context<'_>,
+    #[description = "PDF, DOCX, ODT or EPUB document"] file: Attachment,
+    #[description = "Always attach the text as a .txt file"] as_file: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => {
+            let embed = error_embed("❌ Extraction Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let Some(kind) = detect_kind(&data) else {
+        let embed = error_embed(
+            "❌ Unsupported File",
+            "Text can be extracted from PDF, DOCX, ODT and EPUB documents.",
+        );
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let result = tokio::task::spawn_blocking(move || extract_document_text(&data, kind))
+        .await
+        .map_err(|e| Error::from(format!("Extraction task failed: {}", e)))
+        .and_then(|result| result);
+    let extracted = match result {
+        Ok(extracted) => extracted,
+        Err(e) => {
+            let embed = error_embed("❌ Extraction Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let words = count_words(&extracted.text);
+    if words == 0 {
+        let message = match kind {
+            DocumentKind::Pdf => "No text was found. The PDF may contain only scanned images.",
+            _ => "No text was found in this document.",
+        };
+        let embed = error_embed("❌ No Text Found", message);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let parts_label = match kind {
+        DocumentKind::Pdf | DocumentKind::Docx | DocumentKind::Odt => "Pages",
+        DocumentKind::Epub => "Sections",
+    };
+    let summary = format!(
+        "**File:** `{}`\n**Format:** {}\n**{}:** {}\n**Words:** {}\n**Characters:** {}",
+        file.filename,
+        kind.label(),
+        parts_label,
+        extracted.parts,
+        words,
+        extracted.text.chars().count()
+    );
+
+    // Keep code fences in the text from closing the preview block early
+    let preview = extracted.text.replace("```", "`\u{200b}``");
+    if !as_file.unwrap_or(false) && preview.len() <= INLINE_PREVIEW_LIMIT {
+        let embed = CreateEmbed::new()
+            .title("✅ Text Extracted")
+            .description(format!("{}\n\n```\n{}\n```", summary, preview))
+            .color(EMBED_SUCCESS_COLOR);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    validate_output_size(extracted.text.len(), "Extracted text").map_err(Error::from)?;
+    let embed = CreateEmbed::new()
+        .title("✅ Text Extracted")
+        .description(summary)
+        .color(EMBED_SUCCESS_COLOR)
+        .footer(CreateEmbedFooter::new(
+            "Extracted text is attached as a file.",
+        ));
+    let filename = format!("{}.txt", sanitize_filename(&file_stem(&file.filename)));
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                extracted.text.into_bytes(),
+                filename,
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DocumentKind, count_words, detect_kind, extract_document_text, resolve_href};
+    use crate::compression::compress::zip_entries;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{Document, Object, Stream, dictionary};
+
+    fn container(entries: &[(&str, &str)]) -> Vec<u8> {
+        let entries: Vec<(String, Vec<u8>)> = entries
+            .iter()
+            .map(|(name, content)| (name.to_string(), content.as_bytes().to_vec()))
+            .collect();
+        zip_entries(&entries).unwrap()
+    }
+
+    #[test]
+    fn docx_text_keeps_paragraphs_tabs_and_page_breaks() {
+        let docx = container(&[(
+            "word/document.xml",
+            r#"<w:document xmlns:w="w"><w:body>
+                <w:p><w:pPr><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr><w:r><w:t>Fish &amp; chips</w:t><w:tab/><w:t xml:space="preserve"> £5</w:t></w:r></w:p>
+                <w:p><w:r><w:instrText>PAGE</w:instrText><w:br w:type="page"/><w:t>Second</w:t></w:r></w:p>
+            </w:body></w:document>"#,
+        )]);
+        assert_eq!(detect_kind(&docx), Some(DocumentKind::Docx));
+        let extracted = extract_document_text(&docx, DocumentKind::Docx).unwrap();
+        assert_eq!(
+            extracted.text,
+            "Fish & chips\t £5\n\n--- Page 2 ---\n\nSecond"
+        );
+        assert_eq!(extracted.parts, 2);
+    }
+
+    #[test]
+    fn odt_text_expands_spaces_and_skips_annotations() {
+        let odt = container(&[
+            ("mimetype", "application/vnd.oasis.opendocument.text"),
+            (
+                "content.xml",
+                r#"<office:document-content xmlns:office="o" xmlns:text="t"><office:body><office:text>
+                    <text:h>Title</text:h>
+                    <text:p>a<text:s text:c="3"/>b<office:annotation><text:p>note</text:p></office:annotation></text:p>
+                </office:text></office:body></office:document-content>"#,
+            ),
+        ]);
+        assert_eq!(detect_kind(&odt), Some(DocumentKind::Odt));
+        let extracted = extract_document_text(&odt, DocumentKind::Odt).unwrap();
+        assert_eq!(extracted.text, "Title\na   b");
+    }
+
+    #[test]
+    fn epub_chapters_follow_the_spine() {
+        let epub = container(&[
+            ("mimetype", "application/epub+zip"),
+            (
+                "META-INF/container.xml",
+                r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<package><manifest>
+                    <item id="b" href="text/two.xhtml"/><item id="a" href="text/one%20a.xhtml"/>
+                </manifest><spine><itemref idref="a"/><itemref idref="b"/></spine></package>"#,
+            ),
+            (
+                "OEBPS/text/one a.xhtml",
+                "<html><head><title>skip</title></head><body><h1>One</h1><p>first\n   line</p></body></html>",
+            ),
+            (
+                "OEBPS/text/two.xhtml",
+                "<html><body><p>Two<br/>lines</p></body></html>",
+            ),
+        ]);
+        assert_eq!(detect_kind(&epub), Some(DocumentKind::Epub));
+        let extracted = extract_document_text(&epub, DocumentKind::Epub).unwrap();
+        assert_eq!(
+            extracted.text,
+            "--- Section 1 ---\n\nOne\nfirst line\n\n--- Section 2 ---\n\nTwo\nlines"
+        );
+        assert_eq!(resolve_href("OEBPS", "../images/a.png#x"), "images/a.png");
+    }
+
+    #[test]
+    fn pdf_pages_sharing_a_content_stream_keep_their_text() {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                Operation::new("Tj", vec![Object::string_literal("Shared words")]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        let mut stream = Stream::new(dictionary! {}, content.encode().unwrap());
+        stream.compress().unwrap();
+        let content_id = doc.add_object(stream);
+        let kids: Vec<Object> = (0..2)
+            .map(|_| {
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+                    "MediaBox" => vec![0.into(), 0.into(), 200.into(), 200.into()],
+                })
+                .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => 2 }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        let mut pdf = Vec::new();
+        doc.save_to(&mut pdf).unwrap();
+
+        let extracted = extract_document_text(&pdf, DocumentKind::Pdf).unwrap();
+        assert_eq!(extracted.parts, 2);
+        assert_eq!(
+            extracted.text,
+            "--- Page 1 ---\n\nShared words\n\n--- Page 2 ---\n\nShared words"
+        );
+    }
+
+    #[test]
+    fn word_count_skips_only_inserted_separators() {
+        let text = "--- Page 1 ---\n\nOne two\n---\n--- she said ---\n\n--- Section 12 ---";
+        assert_eq!(count_words(text), 7);
+    }
+}
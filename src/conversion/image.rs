@@ -1,8 +1,12 @@
 use crate::attachments::{
     sanitize_filename, validate_attachment_size, validate_image_dimensions, validate_output_size,
 };
+use crate::commands::resize::{fit_within, resize_rgba};
+use crate::conversion::svg::{is_svg, rasterize_svg};
+use crate::imaging::palette::{Dither, quantize};
 use crate::{Context, Error};
-use image::{DynamicImage, ImageOutputFormat};
+use image::codecs::pnm::{PnmSubtype, SampleEncoding};
+use image::{DynamicImage, ImageFormat, ImageOutputFormat, Rgba, RgbaImage};
 use poise::serenity_prelude::CreateAttachment;
 use serenity::all::{Attachment, CreateEmbed};
 use std::io::Cursor;
@@ -21,11 +25,30 @@ pub enum OutputFormat {
     Bmp,
     #[name = "TIFF"]
     Tiff,
+    #[name = "AVIF"]
+    Avif,
+    #[name = "ICO (max 256×256)"]
+    Ico,
+    #[name = "QOI"]
+    Qoi,
+    #[name = "TGA"]
+    Tga,
+    #[name = "PNM (PPM)"]
+    Pnm,
 }
 
+/// Quality used for lossy formats when the caller does not request one
+pub const DEFAULT_QUALITY: u8 = 85;
+/// Width used to rasterize SVG input when the caller does not request one
+pub const DEFAULT_SVG_WIDTH: u32 = 1024;
+/// Largest width and height an ICO entry can store
+const MAX_ICO_DIMENSION: u32 = 256;
+/// AVIF encoder speed from 1 (slowest) to 10; favours latency over size
+const AVIF_SPEED: u8 = 8;
+
 impl OutputFormat {
     /// Get the file extension for this format
-    const fn extension(self) -> &'static str {
+    pub const fn extension(self) -> &'static str {
         match self {
             Self::Jpg => "jpg",
             Self::Png => "png",
@@ -33,18 +56,37 @@ impl OutputFormat {
             Self::Gif => "gif",
             Self::Bmp => "bmp",
             Self::Tiff => "tiff",
+            Self::Avif => "avif",
+            Self::Ico => "ico",
+            Self::Qoi => "qoi",
+            Self::Tga => "tga",
+            Self::Pnm => "ppm",
         }
     }
 
-    /// Convert to image output format with optimized settings
-    const fn to_image_format(self) -> ImageOutputFormat {
+    /// Whether the encoder exposes a quality setting for this format
+    pub const fn supports_quality(self) -> bool {
+        matches!(self, Self::Jpg | Self::Avif)
+    }
+
+    /// Convert to image output format with optimized settings.
+    ///
+    /// Returns `None` for formats that are not encoded by the `image` crate.
+    const fn to_image_format(self, quality: u8) -> Option<ImageOutputFormat> {
         match self {
-            Self::Jpg => ImageOutputFormat::Jpeg(85),
-            Self::Png => ImageOutputFormat::Png,
-            Self::Webp => ImageOutputFormat::WebP,
-            Self::Gif => ImageOutputFormat::Gif,
-            Self::Bmp => ImageOutputFormat::Bmp,
-            Self::Tiff => ImageOutputFormat::Tiff,
+            Self::Jpg => Some(ImageOutputFormat::Jpeg(quality)),
+            Self::Png => Some(ImageOutputFormat::Png),
+            Self::Webp => Some(ImageOutputFormat::WebP),
+            Self::Gif => Some(ImageOutputFormat::Gif),
+            Self::Bmp => Some(ImageOutputFormat::Bmp),
+            Self::Tiff => Some(ImageOutputFormat::Tiff),
+            Self::Ico => Some(ImageOutputFormat::Ico),
+            Self::Qoi => Some(ImageOutputFormat::Qoi),
+            Self::Tga => Some(ImageOutputFormat::Tga),
+            Self::Pnm => Some(ImageOutputFormat::Pnm(PnmSubtype::Pixmap(
+                SampleEncoding::Binary,
+            ))),
+            Self::Avif => None,
         }
     }
 }
@@ -58,8 +100,11 @@ fn generate_output_filename(input_filename: &str, format: OutputFormat) -> Strin
 }
 
 /// Optimize image based on output format
-fn optimize_image_for_format(img: DynamicImage, format: OutputFormat) -> DynamicImage {
-    match format {
+fn optimize_image_for_format(
+    img: DynamicImage,
+    format: OutputFormat,
+) -> Result<DynamicImage, Error> {
+    let optimized = match format {
         // Convert to RGB for formats that don't support transparency
         OutputFormat::Jpg | OutputFormat::Bmp => {
             if img.color().has_alpha() {
@@ -68,9 +113,28 @@ fn optimize_image_for_format(img: DynamicImage, format: OutputFormat) -> Dynamic
                 img
             }
         }
+        // The PPM and QOI encoders only accept 8-bit RGB(A) buffers
+        OutputFormat::Pnm => DynamicImage::ImageRgb8(img.to_rgb8()),
+        OutputFormat::Qoi => DynamicImage::ImageRgba8(img.to_rgba8()),
+        // ICO entries cannot exceed 256×256, so larger images are scaled down
+        OutputFormat::Ico => {
+            let (width, height) = fit_within(
+                img.width(),
+                img.height(),
+                MAX_ICO_DIMENSION,
+                MAX_ICO_DIMENSION,
+            );
+            if (width, height) == (img.width(), img.height()) {
+                DynamicImage::ImageRgba8(img.to_rgba8())
+            } else {
+                DynamicImage::ImageRgba8(resize_rgba(&img.to_rgba8(), width, height)?)
+            }
+        }
         // Keep original for formats that support transparency
         _ => img,
-    }
+    };
+
+    Ok(optimized)
 }
 
 /// Estimate output buffer size to reduce allocations
@@ -83,11 +147,134 @@ fn estimate_output_size(img: &DynamicImage, format: OutputFormat) -> usize {
         OutputFormat::Bmp => pixel_count * 3, // ~300% for BMP (uncompressed)
         OutputFormat::Gif => pixel_count,     // ~100% for GIF
         OutputFormat::Tiff => pixel_count * 2, // ~200% for TIFF
+        OutputFormat::Avif => pixel_count / 8, // ~12% for AVIF
+        OutputFormat::Ico => pixel_count * 2, // ~200% for ICO (PNG entries)
+        OutputFormat::Qoi => pixel_count * 2, // ~200% for QOI
+        OutputFormat::Tga => pixel_count * 4, // ~400% for TGA (uncompressed RGBA)
+        OutputFormat::Pnm => pixel_count * 3, // ~300% for PPM (uncompressed)
     };
 
     estimated.min(usize::MAX as u64) as usize
 }
 
+/// Map one linear HDR channel to 8 bits with Reinhard tone mapping and gamma.
+fn tone_map_channel(value: f32) -> u8 {
+    let value = value.max(0.0);
+    let mapped = (value / (1.0 + value)).powf(1.0 / 2.2);
+    (mapped * 255.0).round() as u8
+}
+
+/// Tone-map floating point (Radiance HDR, OpenEXR) images to a viewable 8-bit preview.
+///
+/// A plain conversion clamps every value above 1.0, which blows out highlights.
+fn tone_map_hdr(img: DynamicImage) -> DynamicImage {
+    match img {
+        DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+            let linear = img.to_rgba32f();
+            let mapped = RgbaImage::from_fn(linear.width(), linear.height(), |x, y| {
+                let [r, g, b, a] = linear.get_pixel(x, y).0;
+                Rgba([
+                    tone_map_channel(r),
+                    tone_map_channel(g),
+                    tone_map_channel(b),
+                    (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+                ])
+            });
+            DynamicImage::ImageRgba8(mapped)
+        }
+        other => other,
+    }
+}
+
+/// Decode image bytes and reject images that exceed the pixel limit.
+pub fn decode_image(data: &[u8]) -> Result<DynamicImage, Error> {
+    decode_image_at(data, None)
+}
+
+/// Decode image bytes, rasterizing SVG input `svg_width` pixels wide.
+pub fn decode_image_at(data: &[u8], svg_width: Option<u32>) -> Result<DynamicImage, Error> {
+    let img = if is_svg(data) {
+        rasterize_svg(data, svg_width.unwrap_or(DEFAULT_SVG_WIDTH))?
+    } else {
+        let img = match image::load_from_memory(data) {
+            Ok(img) => img,
+            // TGA has no magic number, so it is only tried once detection fails
+            Err(e) => image::load_from_memory_with_format(data, ImageFormat::Tga)
+                .map_err(|_| Error::from(format!("Failed to load image: {}", e)))?,
+        };
+        tone_map_hdr(img)
+    };
+    validate_image_dimensions(img.width(), img.height()).map_err(Error::from)?;
+    Ok(img)
+}
+
+/// Encode RGBA pixels as AVIF with the pure-Rust rav1e encoder.
+fn encode_avif(img: &DynamicImage, quality: u8) -> Result<Vec<u8>, Error> {
+    let rgba = img.to_rgba8();
+    let pixels: Vec<ravif::RGBA8> = rgba
+        .pixels()
+        .map(|pixel| ravif::RGBA8::new(pixel[0], pixel[1], pixel[2], pixel[3]))
+        .collect();
+
+    let encoded = ravif::Encoder::new()
+        .with_quality(f32::from(quality))
+        .with_speed(AVIF_SPEED)
+        .encode_rgba(ravif::Img::new(
+            &pixels[..],
+            rgba.width() as usize,
+            rgba.height() as usize,
+        ))
+        .map_err(|e| Error::from(format!("Failed to encode image: {}", e)))?;
+
+    Ok(encoded.avif_file)
+}
+
+/// Encode an image using the default quality for the target format.
+pub fn encode_image(img: DynamicImage, format: OutputFormat) -> Result<Vec<u8>, Error> {
+    encode_image_with_quality(img, format, DEFAULT_QUALITY)
+}
+
+/// Encode an image, using `quality` for formats that support it.
+pub fn encode_image_with_quality(
+    img: DynamicImage,
+    format: OutputFormat,
+    quality: u8,
+) -> Result<Vec<u8>, Error> {
+    let optimized_img = optimize_image_for_format(img, format)?;
+    let Some(image_format) = format.to_image_format(quality) else {
+        return encode_avif(&optimized_img, quality);
+    };
+
+    let estimated_size = estimate_output_size(&optimized_img, format);
+    let mut buf = Cursor::new(Vec::with_capacity(estimated_size));
+
+    optimized_img
+        .write_to(&mut buf, image_format)
+        .map_err(|e| Error::from(format!("Failed to encode image: {}", e)))?;
+
+    Ok(buf.into_inner())
+}
+
+/// Download an attachment and decode it as an image.
+///
+/// SVG attachments are rasterized `svg_width` pixels wide.
+pub async fn download_image(
+    file: &Attachment,
+    svg_width: Option<u32>,
+) -> Result<DynamicImage, Error> {
+    validate_attachment_size(file).map_err(Error::from)?;
+
+    let file_data = file
+        .download()
+        .await
+        .map_err(|e| Error::from(format!("Failed to download image: {}", e)))?;
+
+    // Decode in a blocking task to avoid blocking the async runtime
+    tokio::task::spawn_blocking(move || decode_image_at(&file_data, svg_width))
+        .await
+        .map_err(|e| Error::from(format!("Image loading task failed: {}", e)))?
+}
+
 /// Create error embed for conversion failure
 fn create_error_embed(error: &Error) -> CreateEmbed {
     CreateEmbed::default()
@@ -100,39 +287,23 @@ fn create_error_embed(error: &Error) -> CreateEmbed {
 pub async fn convert_image_inner(
     file: &Attachment,
     output_format: OutputFormat,
+    svg_width: Option<u32>,
+    colors: Option<u32>,
+    dither: Option<Dither>,
 ) -> Result<(Vec<u8>, String), Error> {
-    validate_attachment_size(file).map_err(Error::from)?;
-
-    // Download file data
-    let file_data = file
-        .download()
-        .await
-        .map_err(|e| Error::from(format!("Failed to download image: {}", e)))?;
-
-    // Load image in blocking task to avoid blocking the async runtime
-    let img = tokio::task::spawn_blocking(move || {
-        image::load_from_memory(&file_data)
-            .map_err(|e| Error::from(format!("Failed to load image: {}", e)))
-    })
-    .await
-    .map_err(|e| Error::from(format!("Image loading task failed: {}", e)))??;
+    let img = download_image(file, svg_width).await?;
 
-    validate_image_dimensions(img.width(), img.height()).map_err(Error::from)?;
-
-    // Optimize image for target format
-    let optimized_img = optimize_image_for_format(img, output_format);
-
-    // Perform encoding in blocking task
+    // Perform quantization and encoding in blocking task
     let output_bytes = tokio::task::spawn_blocking(move || {
-        let estimated_size = estimate_output_size(&optimized_img, output_format);
-        let mut buf = Cursor::new(Vec::with_capacity(estimated_size));
-
-        let image_format = output_format.to_image_format();
-        optimized_img
-            .write_to(&mut buf, image_format)
-            .map_err(|e| Error::from(format!("Failed to encode image: {}", e)))?;
-
-        Ok::<Vec<u8>, Error>(buf.into_inner())
+        let img = match colors {
+            Some(colors) => DynamicImage::ImageRgba8(quantize(
+                &img.to_rgba8(),
+                colors as usize,
+                dither.unwrap_or(Dither::FloydSteinberg),
+            )),
+            None => img,
+        };
+        encode_image(img, output_format)
     })
     .await
     .map_err(|e| Error::from(format!("Image encoding task failed: {}", e)))??;
@@ -151,10 +322,19 @@ pub async fn convert_image(
     ctx: Context<'_>,
     #[description = "Image to convert"] file: Attachment,
     #[description = "Image format to convert to"] output_format: OutputFormat,
+    #[description = "Width to rasterize SVG input at (default 1024)"]
+    #[min = 1]
+    #[max = 16384]
+    svg_width: Option<u32>,
+    #[description = "Reduce the image to this many colours, e.g. for GIF or pixel art"]
+    #[min = 2]
+    #[max = 256]
+    colors: Option<u32>,
+    #[description = "Dithering used with colors (default Floyd–Steinberg)"] dither: Option<Dither>,
 ) -> Result<(), Error> {
     ctx.defer().await?;
 
-    match convert_image_inner(&file, output_format).await {
+    match convert_image_inner(&file, output_format, svg_width, colors, dither).await {
         Ok((converted_bytes, output_filename)) => {
             let attachment = CreateAttachment::bytes(converted_bytes, &output_filename);
 
@@ -171,3 +351,44 @@ pub async fn convert_image(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{OutputFormat, decode_image, encode_image, tone_map_channel, tone_map_hdr};
+    use image::{DynamicImage, Rgb32FImage, RgbaImage};
+
+    #[test]
+    fn tone_mapping_compresses_highlights_instead_of_clipping() {
+        assert_eq!(tone_map_channel(0.0), 0);
+        assert!(tone_map_channel(4.0) < tone_map_channel(16.0));
+        assert!(tone_map_channel(16.0) < 255);
+
+        let hdr = DynamicImage::ImageRgb32F(Rgb32FImage::from_pixel(2, 2, [8.0, 1.0, 0.0].into()));
+        let preview = tone_map_hdr(hdr).to_rgba8();
+        assert_eq!(preview.get_pixel(0, 0)[3], 255);
+    }
+
+    #[test]
+    fn ico_output_is_scaled_to_fit_the_format_limit() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(512, 256));
+        let encoded = encode_image(img, OutputFormat::Ico).unwrap();
+        let decoded = image::load_from_memory(&encoded).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (256, 128));
+    }
+
+    #[test]
+    fn avif_output_has_avif_brand() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(8, 8));
+        let encoded = encode_image(img, OutputFormat::Avif).unwrap();
+        assert_eq!(&encoded[4..12], b"ftypavif");
+    }
+
+    #[test]
+    fn niche_formats_round_trip() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(8, 8));
+        for format in [OutputFormat::Qoi, OutputFormat::Tga, OutputFormat::Pnm] {
+            let encoded = encode_image(img.clone(), format).unwrap();
+            assert!(decode_image(&encoded).is_ok());
+        }
+    }
+}
@@ -0,0 +1,743 @@
+use crate::attachments::{download_attachment, sanitize_filename, validate_output_size};
+use crate::embeds::{EMBED_SUCCESS_COLOR, INLINE_PREVIEW_LIMIT, error_embed};
+use crate::utils::file_stem;
+use crate::{Context, Error};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use serde::Serialize;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+/// Characters of the offending line shown around a parse error
+const SNIPPET_WIDTH: usize = 80;
+/// Schema violations listed before the rest are summarised
+const MAX_SCHEMA_ERRORS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Indent {
+    #[name = "2 spaces"]
+    Two,
+    #[name = "4 spaces"]
+    Four,
+    #[name = "Tabs"]
+    Tab,
+}
+
+impl Indent {
+    const fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Two => b"  ",
+            Self::Four => b"    ",
+            Self::Tab => b"\t",
+        }
+    }
+}
+
+/// Where and why the input could not be parsed.
+#[derive(Debug)]
+pub struct ParseFailure {
+    pub message: String,
+    /// 1-based, when the parser reports a position
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Input to a `/json` subcommand, parsed as JSON or YAML.
+struct JsonInput {
+    value: Value,
+    yaml: bool,
+    stem: String,
+}
+
+/// Drop the position a parser appends to its message, since it is shown separately.
+fn strip_position(message: String) -> String {
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    }
+}
+
+pub fn parse_json(text: &str) -> Result<Value, ParseFailure> {
+    serde_json::from_str(text.trim_start_matches('\u{feff}')).map_err(|e| ParseFailure {
+        line: Some(e.line()),
+        column: Some(e.column().max(1)),
+        message: strip_position(e.to_string()),
+    })
+}
+
+pub fn parse_yaml(text: &str) -> Result<Value, ParseFailure> {
+    serde_yaml::from_str(text.trim_start_matches('\u{feff}')).map_err(|e| {
+        let location = e.location();
+        ParseFailure {
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: strip_position(e.to_string()),
+        }
+    })
+}
+
+/// Show the line with the error and the one before it, with a caret under
+/// the column, in the style of compiler diagnostics.
+pub fn error_snippet(text: &str, line: usize, column: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if line == 0 {
+        return String::new();
+    }
+    let gutter = line.to_string().len();
+    let column = column.max(1) - 1;
+    // Keep long lines, such as minified JSON, centred on the error
+    let start = column.saturating_sub(SNIPPET_WIDTH / 2);
+    let excerpt = |text: &str| -> String {
+        text.chars()
+            .skip(start)
+            .take(SNIPPET_WIDTH)
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .collect()
+    };
+
+    let mut snippet = String::new();
+    for number in line.saturating_sub(1).max(1)..=line {
+        let content = lines.get(number - 1).copied().unwrap_or_default();
+        snippet.push_str(&format!(
+            "{:>width$} | {}\n",
+            number,
+            excerpt(content),
+            width = gutter
+        ));
+    }
+    snippet.push_str(&format!(
+        "{:>width$} | {}^",
+        "",
+        " ".repeat(column - start),
+        width = gutter
+    ));
+    snippet
+}
+
+/// Sort the keys of every object, at every depth.
+pub fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.sort_keys();
+            map.values_mut().for_each(sort_keys);
+        }
+        Value::Array(items) => items.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
+pub fn to_pretty_json(value: &Value, indent: Indent) -> String {
+    let mut output = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    // Serializing a `Value` into memory cannot fail
+    let _ = value.serialize(&mut serializer);
+    String::from_utf8(output).unwrap_or_default()
+}
+
+/// One step of a jq-style path such as `.items[0].name`.
+#[derive(Debug, PartialEq)]
+enum PathStep {
+    Key(String),
+    Index(i64),
+    Iterate,
+}
+
+fn parse_jq_path(path: &str) -> Result<Vec<PathStep>, String> {
+    let invalid = || format!("`{}` is not a valid path, e.g. `.items[0].name`", path);
+    let mut steps = Vec::new();
+    let mut rest = path.strip_prefix('.').ok_or_else(invalid)?;
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            let end = inner.find(']').ok_or_else(invalid)?;
+            let index = inner[..end].trim();
+            steps.push(if index.is_empty() {
+                PathStep::Iterate
+            } else if let Some(key) = index.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+                PathStep::Key(key.to_string())
+            } else {
+                PathStep::Index(index.parse().map_err(|_| invalid())?)
+            });
+            rest = &inner[end + 1..];
+        } else if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').ok_or_else(invalid)?;
+            steps.push(PathStep::Key(quoted[..end].to_string()));
+            rest = &quoted[end + 1..];
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let key = &rest[..end];
+            if key.is_empty() || !key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(invalid());
+            }
+            steps.push(PathStep::Key(key.to_string()));
+            rest = &rest[end..];
+        }
+        rest = rest.strip_prefix('.').unwrap_or(rest);
+    }
+    Ok(steps)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn apply_jq_stage(stage: &str, values: Vec<Value>) -> Result<Vec<Value>, String> {
+    match stage {
+        "keys" => values
+            .into_iter()
+            .map(|value| match value {
+                Value::Object(map) => {
+                    let mut keys: Vec<String> = map.keys().cloned().collect();
+                    keys.sort();
+                    Ok(Value::from(keys))
+                }
+                Value::Array(items) => Ok(Value::from((0..items.len()).collect::<Vec<_>>())),
+                other => Err(format!("{} has no keys", type_name(&other))),
+            })
+            .collect(),
+        "length" => values
+            .into_iter()
+            .map(|value| match value {
+                Value::Object(map) => Ok(Value::from(map.len())),
+                Value::Array(items) => Ok(Value::from(items.len())),
+                Value::String(text) => Ok(Value::from(text.chars().count())),
+                Value::Null => Ok(Value::from(0)),
+                other => Err(format!("{} has no length", type_name(&other))),
+            })
+            .collect(),
+        path => {
+            let mut values = values;
+            for step in parse_jq_path(path)? {
+                let mut next = Vec::new();
+                for value in values {
+                    match (&step, value) {
+                        (PathStep::Key(key), Value::Object(mut map)) => {
+                            next.push(map.remove(key).unwrap_or(Value::Null));
+                        }
+                        (PathStep::Index(index), Value::Array(mut items)) => {
+                            let position = if *index < 0 {
+                                items.len().checked_sub(index.unsigned_abs() as usize)
+                            } else {
+                                Some(*index as usize)
+                            };
+                            next.push(
+                                position
+                                    .filter(|&position| position < items.len())
+                                    .map(|position| items.swap_remove(position))
+                                    .unwrap_or(Value::Null),
+                            );
+                        }
+                        (PathStep::Iterate, Value::Array(items)) => next.extend(items),
+                        (PathStep::Iterate, Value::Object(map)) => next.extend(map.into_values()),
+                        (_, Value::Null) => next.push(Value::Null),
+                        (PathStep::Key(key), other) => {
+                            return Err(format!(
+                                "Cannot read `{}` from {}",
+                                key,
+                                type_name(&other)
+                            ));
+                        }
+                        (step, other) => {
+                            let action = match step {
+                                PathStep::Iterate => "iterate over",
+                                _ => "index",
+                            };
+                            return Err(format!("Cannot {} {}", action, type_name(&other)));
+                        }
+                    }
+                }
+                values = next;
+            }
+            Ok(values)
+        }
+    }
+}
+
+/// Run a JSONPath expression (starting with `$`) or a jq-style filter
+/// (starting with `.`, with `|`, `keys` and `length`) and return the matches.
+pub fn query(value: &Value, expression: &str) -> Result<Vec<Value>, String> {
+    let expression = expression.trim();
+    if expression.starts_with('$') {
+        let path = JsonPath::parse(expression).map_err(|e| e.to_string())?;
+        return Ok(path.query(value).all().into_iter().cloned().collect());
+    }
+    if !expression.starts_with('.') {
+        return Err(
+            "Start the expression with `$` for JSONPath (e.g. `$.items[*].name`) \
+             or `.` for a jq-style filter (e.g. `.items[].name`)."
+                .to_string(),
+        );
+    }
+    expression
+        .split('|')
+        .map(str::trim)
+        .try_fold(vec![value.clone()], |values, stage| {
+            apply_jq_stage(stage, values)
+        })
+}
+
+/// Check `instance` against a JSON Schema, returning the violations found.
+pub fn schema_errors(schema: &Value, instance: &Value) -> Result<Vec<String>, String> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| format!("The schema itself is invalid: {}", e))?;
+    Ok(validator
+        .iter_errors(instance)
+        .map(|error| {
+            let path = error.instance_path().to_string();
+            let path = if path.is_empty() {
+                "/".to_string()
+            } else {
+                path
+            };
+            format!("`{}`: {}", path, error)
+        })
+        .collect())
+}
+
+fn failure_message(failure: &ParseFailure, text: &str) -> String {
+    let mut message = format!("**Error:** {}", failure.message);
+    if let (Some(line), Some(column)) = (failure.line, failure.column) {
+        message.push_str(&format!(
+            "\n**Line:** {}, **Column:** {}\n```\n{}\n```",
+            line,
+            column,
+            error_snippet(text, line, column).replace("```", "`\u{200b}``")
+        ));
+    }
+    message
+}
+
+/// Download or take the pasted input. YAML is read when asked for or when the
+/// file has a YAML extension.
+async fn read_input(
+    file: Option<&Attachment>,
+    text: Option<String>,
+    yaml: Option<bool>,
+) -> Result<(String, bool, String), String> {
+    let (data, stem, is_yaml_file) = match (file, text) {
+        (Some(file), None) => {
+            let data = download_attachment(file).await.map_err(|e| e.to_string())?;
+            let name = file.filename.to_ascii_lowercase();
+            let is_yaml = name.ends_with(".yaml") || name.ends_with(".yml");
+            (data, sanitize_filename(&file_stem(&file.filename)), is_yaml)
+        }
+        (None, Some(text)) if !text.trim().is_empty() => {
+            (text.into_bytes(), "data".to_string(), false)
+        }
+        _ => return Err("Provide either a `file` or some `text`.".to_string()),
+    };
+    let text = String::from_utf8(data).map_err(|_| "The input is not UTF-8 text.".to_string())?;
+    Ok((text, yaml.unwrap_or(is_yaml_file), stem))
+}
+
+/// Read and parse the input, replying with the parse error when it is invalid.
+async fn load_input(
+    ctx: Context<'_>,
+    file: Option<&Attachment>,
+    text: Option<String>,
+    yaml: Option<bool>,
+) -> Result<Option<JsonInput>, Error> {
+    let (text, yaml, stem) = match read_input(file, text, yaml).await {
+        Ok(input) => input,
+        Err(message) => {
+            let embed = error_embed("❌ Invalid Input", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(None);
+        }
+    };
+    let parsed = tokio::task::spawn_blocking(move || {
+        let parsed = if yaml {
+            parse_yaml(&text)
+        } else {
+            parse_json(&text)
+        };
+        parsed.map_err(|failure| failure_message(&failure, &text))
+    })
+    .await
+    .map_err(|e| format!("Parsing task failed: {}", e))
+    .and_then(|result| result);
+    match parsed {
+        Ok(value) => Ok(Some(JsonInput { value, yaml, stem })),
+        Err(message) => {
+            let title = if yaml {
+                "❌ Invalid YAML"
+            } else {
+                "❌ Invalid JSON"
+            };
+            let embed = error_embed(title, message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Reply with `output` inline when it is short, or as an attachment.
+async fn send_output(
+    ctx: Context<'_>,
+    title: &str,
+    summary: String,
+    output: String,
+    filename: String,
+) -> Result<(), Error> {
+    let language = filename.rsplit('.').next().unwrap_or_default().to_string();
+    if output.len() <= INLINE_PREVIEW_LIMIT {
+        let embed = CreateEmbed::new()
+            .title(title)
+            .description(format!(
+                "{}\n\n```{}\n{}\n```",
+                summary,
+                language,
+                output.trim_end().replace("```", "`\u{200b}``")
+            ))
+            .color(EMBED_SUCCESS_COLOR);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    validate_output_size(output.len(), "Output").map_err(Error::from)?;
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(summary)
+        .color(EMBED_SUCCESS_COLOR)
+        .footer(CreateEmbedFooter::new("Output is attached as a file."));
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(output.into_bytes(), filename)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Write the value back in the format it was read in.
+fn reformat(input: &JsonInput, indent: Indent) -> Result<(String, String), String> {
+    if input.yaml {
+        let output = serde_yaml::to_string(&input.value).map_err(|e| e.to_string())?;
+        Ok((output, format!("{}.yaml", input.stem)))
+    } else {
+        Ok((
+            to_pretty_json(&input.value, indent),
+            format!("{}.json", input.stem),
+        ))
+    }
+}
+
+/// Validate, format and query JSON or YAML
+#[poise::command(
+    slash_command,
+    subcommands(
+        "validate",
+        "format",
+        "minify",
+        "sort_keys_command",
+        "query_command",
+        "schema"
+    ),
+    subcommand_required
+)]
+pub async fn json(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Check that JSON or YAML parses, and show where it does not
+#[poise::command(slash_command)]
+pub async fn validate(
+    ctx: Context<'_>,
+    #[description = "JSON or YAML file"] file: Option<Attachment>,
+    #[description = "Text to check instead of a file"] text: Option<String>,
+    #[description = "Read the input as YAML (default: from the file extension)"] yaml: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    let Some(input) = load_input(ctx, file.as_ref(), text, yaml).await? else {
+        return Ok(());
+    };
+
+    let shape = match &input.value {
+        Value::Object(map) => format!("object with {} keys", map.len()),
+        Value::Array(items) => format!("array of {} items", items.len()),
+        other => type_name(other).to_string(),
+    };
+    let embed = CreateEmbed::new()
+        .title(if input.yaml {
+            "✅ Valid YAML"
+        } else {
+            "✅ Valid JSON"
+        })
+        .description(format!("**Top level:** {}", shape))
+        .color(EMBED_SUCCESS_COLOR);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Pretty-print JSON or YAML
+#[poise::command(slash_command)]
+pub async fn format(
+    ctx: Context<'_>,
+    #[description = "JSON or YAML file"] file: Option<Attachment>,
+    #[description = "Text to format instead of a file"] text: Option<String>,
+    #[description = "Indentation for JSON (default: 2 spaces)"] indent: Option<Indent>,
+    #[description = "Read the input as YAML (default: from the file extension)"] yaml: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    let Some(input) = load_input(ctx, file.as_ref(), text, yaml).await? else {
+        return Ok(());
+    };
+    let indent = indent.unwrap_or(Indent::Two);
+    let result = tokio::task::spawn_blocking(move || reformat(&input, indent))
+        .await
+        .map_err(|e| format!("Formatting task failed: {}", e))
+        .and_then(|result| result);
+    match result {
+        Ok((output, filename)) => {
+            send_output(ctx, "✅ Formatted", String::new(), output, filename).await
+        }
+        Err(message) => {
+            let embed = error_embed("❌ Formatting Failed", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Minify JSON, or YAML into single-line JSON
+#[poise::command(slash_command)]
+pub async fn minify(
+    ctx: Context<'_>,
+    #[description = "JSON or YAML file"] file: Option<Attachment>,
+    #[description = "Text to minify instead of a file"] text: Option<String>,
+    #[description = "Read the input as YAML (default: from the file extension)"] yaml: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    let Some(input) = load_input(ctx, file.as_ref(), text, yaml).await? else {
+        return Ok(());
+    };
+    let stem = input.stem.clone();
+    let output = tokio::task::spawn_blocking(move || input.value.to_string())
+        .await
+        .map_err(|e| Error::from(format!("Minifying task failed: {}", e)))?;
+    let summary = format!("**Size:** {} bytes", output.len());
+    let filename = format!("{}.min.json", stem);
+    send_output(ctx, "✅ Minified", summary, output, filename).await
+}
+
+/// Sort the keys of every object in JSON or YAML
+#[poise::command(slash_command, rename = "sort_keys")]
+pub async fn sort_keys_command(
+    ctx: Context<'_>,
+    #[description = "JSON or YAML file"] file: Option<Attachment>,
+    #[description = "Text to sort instead of a file"] text: Option<String>,
+    #[description = "Indentation for JSON (default: 2 spaces)"] indent: Option<Indent>,
+    #[description = "Read the input as YAML (default: from the file extension)"] yaml: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    let Some(mut input) = load_input(ctx, file.as_ref(), text, yaml).await? else {
+        return Ok(());
+    };
+    let indent = indent.unwrap_or(Indent::Two);
+    let result = tokio::task::spawn_blocking(move || {
+        sort_keys(&mut input.value);
+        reformat(&input, indent)
+    })
+    .await
+    .map_err(|e| format!("Formatting task failed: {}", e))
+    .and_then(|result| result);
+    match result {
+        Ok((output, filename)) => {
+            send_output(ctx, "✅ Keys Sorted", String::new(), output, filename).await
+        }
+        Err(message) => {
+            let embed = error_embed("❌ Formatting Failed", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Query JSON or YAML with JSONPath or a jq-style filter
+#[poise::command(slash_command, rename = "query")]
+pub async fn query_command(
+    ctx: Context<'_>,
+    #[description = "JSONPath like `$.items[*].name` or jq-style like `.items[].name | length`"]
+    expression: String,
+    #[description = "JSON or YAML file"] file: Option<Attachment>,
+    #[description = "Text to query instead of a file"] text: Option<String>,
+    #[description = "Read the input as YAML (default: from the file extension)"] yaml: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    let Some(input) = load_input(ctx, file.as_ref(), text, yaml).await? else {
+        return Ok(());
+    };
+
+    let filename = format!("{}-query.json", input.stem);
+    let query_expression = expression.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let matches = query(&input.value, &query_expression)?;
+        let count = matches.len();
+        let output = match matches.as_slice() {
+            [single] => to_pretty_json(single, Indent::Two),
+            _ => to_pretty_json(&Value::Array(matches), Indent::Two),
+        };
+        Ok((count, output))
+    })
+    .await
+    .map_err(|e| format!("Query task failed: {}", e))
+    .and_then(|result| result);
+    let (count, output) = match result {
+        Ok(result) => result,
+        Err(message) => {
+            let embed = error_embed("❌ Query Failed", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let summary = format!(
+        "**Expression:** `{}`\n**Matches:** {}",
+        expression.replace('`', "'"),
+        count
+    );
+    send_output(ctx, "✅ Query Results", summary, output, filename).await
+}
+
+/// Validate JSON or YAML against an uploaded JSON Schema
+#[poise::command(slash_command)]
+pub async fn schema(
+    ctx: Context<'_>,
+    #[description = "JSON Schema to validate against"] schema: Attachment,
+    #[description = "JSON or YAML file"] file: Option<Attachment>,
+    #[description = "Text to validate instead of a file"] text: Option<String>,
+    #[description = "Read the input as YAML (default: from the file extension)"] yaml: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    let Some(input) = load_input(ctx, file.as_ref(), text, yaml).await? else {
+        return Ok(());
+    };
+    let schema_text = match read_input(Some(&schema), None, None).await {
+        Ok((text, _, _)) => text,
+        Err(message) => {
+            let embed = error_embed("❌ Invalid Schema", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let stem = input.stem.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let schema_value =
+            parse_json(&schema_text).map_err(|failure| failure_message(&failure, &schema_text))?;
+        schema_errors(&schema_value, &input.value)
+    })
+    .await
+    .map_err(|e| format!("Validation task failed: {}", e))
+    .and_then(|result| result);
+
+    let errors = match result {
+        Ok(errors) => errors,
+        Err(message) => {
+            let embed = error_embed("❌ Invalid Schema", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let embed = if errors.is_empty() {
+        CreateEmbed::new()
+            .title("✅ Matches Schema")
+            .description(format!(
+                "`{}` is valid against `{}`.",
+                stem, schema.filename
+            ))
+            .color(EMBED_SUCCESS_COLOR)
+    } else {
+        let mut description = errors
+            .iter()
+            .take(MAX_SCHEMA_ERRORS)
+            .map(|error| format!("• {}", error))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if errors.len() > MAX_SCHEMA_ERRORS {
+            description.push_str(&format!("\n…and {} more", errors.len() - MAX_SCHEMA_ERRORS));
+        }
+        if description.len() > 4000 {
+            description = format!("{}…", &description[..description.floor_char_boundary(4000)]);
+        }
+        error_embed(
+            &format!("❌ {} Schema Violations", errors.len()),
+            description,
+        )
+    };
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{error_snippet, parse_json, parse_yaml, query, schema_errors, sort_keys};
+    use serde_json::json;
+
+    #[test]
+    fn parse_errors_point_at_the_problem() {
+        let text = "{\n  \"a\": 1,\n  \"b\" 2\n}";
+        let failure = parse_json(text).unwrap_err();
+        assert_eq!((failure.line, failure.column), (Some(3), Some(7)));
+        assert_eq!(failure.message, "expected `:`");
+        assert_eq!(
+            error_snippet(text, 3, 7),
+            "2 |   \"a\": 1,\n3 |   \"b\" 2\n  |       ^"
+        );
+
+        let failure = parse_yaml("a: 1\nb: [1, 2\n").unwrap_err();
+        assert_eq!(failure.line, Some(3));
+    }
+
+    #[test]
+    fn queries_support_jsonpath_and_jq_filters() {
+        let value = json!({"items": [{"name": "a", "n": 1}, {"name": "b", "n": 2}]});
+        assert_eq!(
+            query(&value, "$.items[?@.n > 1].name").unwrap(),
+            vec![json!("b")]
+        );
+        assert_eq!(
+            query(&value, ".items[].name").unwrap(),
+            vec![json!("a"), json!("b")]
+        );
+        assert_eq!(
+            query(&value, ".items[-1] | keys").unwrap(),
+            vec![json!(["n", "name"])]
+        );
+        assert_eq!(query(&value, ".items | length").unwrap(), vec![json!(2)]);
+        assert!(query(&value, ".items.name").is_err());
+        assert!(query(&value, "items").is_err());
+    }
+
+    #[test]
+    fn sort_keys_is_recursive() {
+        let mut value = json!({"b": {"z": 1, "y": 2}, "a": [{"d": 1, "c": 2}]});
+        sort_keys(&mut value);
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":[{"c":2,"d":1}],"b":{"y":2,"z":1}}"#
+        );
+    }
+
+    #[test]
+    fn schema_violations_name_the_location() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"age": {"type": "integer", "minimum": 0}},
+            "required": ["name"]
+        });
+        let errors = schema_errors(&schema, &json!({"age": -1})).unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().any(|error| error.starts_with("`/age`")));
+        assert!(
+            schema_errors(&schema, &json!({"name": "x", "age": 3}))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(schema_errors(&json!({"type": 5}), &json!(1)).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, html};
+use std::sync::OnceLock;
+use syntect::highlighting::ThemeSet;
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+/// Theme used for code blocks when no highlight style is chosen
+pub const DEFAULT_CODE_THEME: &str = "InspiredGitHub";
+
+/// Styling for standalone pages, kept small so the output stays self-contained
+const STANDALONE_CSS: &str = "body{max-width:46em;margin:2em auto;padding:0 1em;\
+font-family:system-ui,sans-serif;line-height:1.6;color:#222}\
+pre{padding:.8em;overflow-x:auto;border-radius:4px;background:#f6f8fa}\
+code{font-family:ui-monospace,monospace;font-size:.9em}\
+table{border-collapse:collapse}th,td{border:1px solid #ccc;padding:.3em .6em}\
+blockquote{margin-left:0;padding-left:1em;border-left:4px solid #ddd;color:#555}\
+img{max-width:100%}";
+
+/// Syntax definitions are parsed once and shared by every render.
+pub fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+pub fn theme_set() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+/// Find the syntax named by a fenced block's info string, e.g. `rust` in "```rust,ignore".
+pub fn code_syntax(info: &str) -> Option<&'static SyntaxReference> {
+    let language = info
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()
+        .filter(|language| !language.is_empty())?;
+    syntax_set().find_syntax_by_token(language)
+}
+
+/// Render Markdown to an HTML fragment.
+///
+/// Supports CommonMark plus the GitHub extensions for tables, task lists,
+/// strikethrough and footnotes. Fenced code in a known language is highlighted
+/// with `code_theme`, or left plain when it is `None`.
+pub fn render_html(markdown: &str, code_theme: Option<&str>) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES;
+    let theme = code_theme.and_then(|name| theme_set().themes.get(name));
+
+    let mut events = Vec::new();
+    // The block being collected for highlighting, with its syntax and source
+    let mut code_block: Option<(&SyntaxReference, String)> = None;
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if theme.is_some() => {
+                match code_syntax(&info) {
+                    Some(syntax) => code_block = Some((syntax, String::new())),
+                    None => events.push(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))),
+                }
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = &mut code_block {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) if code_block.is_some() => {
+                if let (Some((syntax, code)), Some(theme)) = (code_block.take(), theme) {
+                    let highlighted =
+                        highlighted_html_for_string(&code, syntax_set(), syntax, theme)
+                            .unwrap_or_else(|_| escape_html(&code));
+                    events.push(Event::Html(CowStr::from(highlighted)));
+                }
+            }
+            event => events.push(event),
+        }
+    }
+
+    let mut output = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut output, events.into_iter());
+    output
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    html::push_html(&mut escaped, std::iter::once(Event::Text(text.into())));
+    escaped
+}
+
+/// Wrap an HTML fragment in a complete page with a title and basic styling.
+pub fn standalone_page(body: &str, title: &str, author: Option<&str>) -> String {
+    let author = author
+        .map(|author| {
+            format!(
+                "<meta name=\"author\" content=\"{}\">\n",
+                escape_html(author)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         {}<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        author,
+        escape_html(title),
+        STANDALONE_CSS,
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_CODE_THEME, render_html, standalone_page};
+
+    #[test]
+    fn renders_github_extensions() {
+        let html = render_html(
+            "| a | b |\n|---|---|\n| 1 | 2 |\n\n- [x] done\n\n~~old~~ text[^1]\n\n[^1]: note",
+            None,
+        );
+        assert!(html.contains("<table>"));
+        assert!(html.contains("type=\"checkbox\""));
+        assert!(html.contains("<del>old</del>"));
+        assert!(html.contains("footnote-definition"));
+    }
+
+    #[test]
+    fn highlights_known_languages_only() {
+        let source = "```rust\nfn main() {}\n```\n\n```nonsense\n<x>\n```";
+        let html = render_html(source, Some(DEFAULT_CODE_THEME));
+        assert!(html.contains("style=\"color:"));
+        assert!(html.contains("<code class=\"language-nonsense\">&lt;x&gt;"));
+
+        let plain = render_html(source, None);
+        assert!(plain.contains("<code class=\"language-rust\">"));
+    }
+
+    #[test]
+    fn standalone_page_escapes_metadata() {
+        let page = standalone_page("<p>hi</p>\n", "Q&A <draft>", Some("Jane"));
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>Q&amp;A &lt;draft&gt;</title>"));
+        assert!(page.contains("content=\"Jane\""));
+    }
+}
@@ -1,2 +1,11 @@
+pub mod data;
 pub mod document;
+pub mod extract;
 pub mod image;
+pub mod json;
+pub mod markdown;
+pub mod pdf;
+pub mod pdf_tools;
+pub mod render;
+pub mod sheet;
+pub mod svg;
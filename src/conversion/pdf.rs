@@ -0,0 +1,738 @@
+use crate::attachments::{
+    download_attachment, sanitize_filename, validate_combined_attachment_size,
+    validate_image_dimensions, validate_output_size,
+};
+use crate::compression::compress::zip_entries;
+use crate::conversion::image::{OutputFormat, decode_image, encode_image};
+use crate::embeds::error_embed;
+use crate::imaging::metadata::jpeg_component_count;
+use crate::utils::{file_stem, format_file_size};
+use crate::{Context, Error};
+use hayro::hayro_syntax::Pdf;
+use hayro::vello_cpu::color::palette::css::WHITE;
+use hayro::{PixmapSettings, RenderCache, RenderSettings};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, Stream, dictionary};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+const POINTS_PER_INCH: f32 = 72.0;
+const MM_PER_INCH: f32 = 25.4;
+/// Screen resolution assumed when sizing a page to match an image
+const IMAGE_DPI: f32 = 96.0;
+const DEFAULT_MARGIN_MM: f32 = 10.0;
+const DEFAULT_DPI: u32 = 150;
+const MAX_RENDERED_PAGES: usize = 20;
+/// Discord accepts at most this many attachments per message
+const MAX_MESSAGE_ATTACHMENTS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum PageSize {
+    #[name = "A4"]
+    A4,
+    #[name = "US Letter"]
+    Letter,
+    #[name = "US Legal"]
+    Legal,
+    #[name = "A3"]
+    A3,
+    #[name = "A5"]
+    A5,
+    #[name = "Match each image"]
+    MatchImage,
+}
+
+impl PageSize {
+    /// Portrait page dimensions in points, or `None` when pages follow the image.
+    const fn points(self) -> Option<(f32, f32)> {
+        match self {
+            Self::A4 => Some((595.28, 841.89)),
+            Self::Letter => Some((612.0, 792.0)),
+            Self::Legal => Some((612.0, 1008.0)),
+            Self::A3 => Some((841.89, 1190.55)),
+            Self::A5 => Some((419.53, 595.28)),
+            Self::MatchImage => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum FitMode {
+    #[name = "Fit (show the whole image)"]
+    Fit,
+    #[name = "Fill (crop to fill the page)"]
+    Fill,
+    #[name = "Stretch (ignore aspect ratio)"]
+    Stretch,
+}
+
+/// An image ready to be embedded as a PDF XObject.
+pub struct PdfImage {
+    width: u32,
+    height: u32,
+    color_space: &'static str,
+    filter: Option<&'static str>,
+    data: Vec<u8>,
+}
+
+/// A rectangle in PDF user space, with the origin at the bottom left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+fn mm_to_points(mm: f32) -> f32 {
+    mm / MM_PER_INCH * POINTS_PER_INCH
+}
+
+/// Prepare an uploaded image for embedding.
+///
+/// Baseline greyscale and RGB JPEGs are embedded as-is; everything else is
+/// flattened onto white and stored losslessly.
+pub fn pdf_image(data: &[u8], img: &DynamicImage) -> Result<PdfImage, Error> {
+    let (width, height) = (img.width(), img.height());
+
+    if image::guess_format(data).ok() == Some(ImageFormat::Jpeg) {
+        let color_space = match jpeg_component_count(data) {
+            Some(1) => Some("DeviceGray"),
+            Some(3) => Some("DeviceRGB"),
+            _ => None,
+        };
+        if let Some(color_space) = color_space {
+            return Ok(PdfImage {
+                width,
+                height,
+                color_space,
+                filter: Some("DCTDecode"),
+                data: data.to_vec(),
+            });
+        }
+    }
+
+    let is_gray = !img.color().has_color();
+    let flattened = flatten_onto_white(img);
+    let (color_space, data) = if is_gray {
+        (
+            "DeviceGray",
+            DynamicImage::ImageRgb8(flattened).into_luma8().into_raw(),
+        )
+    } else {
+        ("DeviceRGB", flattened.into_raw())
+    };
+
+    Ok(PdfImage {
+        width,
+        height,
+        color_space,
+        filter: None,
+        data,
+    })
+}
+
+/// Composite an image over a white background, dropping alpha.
+pub fn flatten_onto_white(img: &DynamicImage) -> image::RgbImage {
+    if !img.color().has_alpha() {
+        return img.to_rgb8();
+    }
+
+    let rgba = img.to_rgba8();
+    image::RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |channel: u8| {
+            ((u32::from(channel) * u32::from(a) + 255 * (255 - u32::from(a))) / 255) as u8
+        };
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Where an image of the given aspect lands inside `area`.
+fn place_image(image: (u32, u32), area: Rect, fit: FitMode) -> Rect {
+    if fit == FitMode::Stretch {
+        return area;
+    }
+
+    let (width, height) = (image.0 as f32, image.1 as f32);
+    let scale_x = area.width / width;
+    let scale_y = area.height / height;
+    let scale = match fit {
+        FitMode::Fill => scale_x.max(scale_y),
+        _ => scale_x.min(scale_y),
+    };
+    let (placed_width, placed_height) = (width * scale, height * scale);
+
+    Rect {
+        x: area.x + (area.width - placed_width) / 2.0,
+        y: area.y + (area.height - placed_height) / 2.0,
+        width: placed_width,
+        height: placed_height,
+    }
+}
+
+/// Page dimensions in points for one image.
+///
+/// Fixed page sizes turn landscape when the image is wider than it is tall.
+fn page_dimensions(image: (u32, u32), page_size: PageSize, margin: f32) -> (f32, f32) {
+    match page_size.points() {
+        Some((width, height)) if image.0 > image.1 => (height, width),
+        Some(dimensions) => dimensions,
+        None => (
+            image.0 as f32 / IMAGE_DPI * POINTS_PER_INCH + margin * 2.0,
+            image.1 as f32 / IMAGE_DPI * POINTS_PER_INCH + margin * 2.0,
+        ),
+    }
+}
+
+fn real(value: f32) -> Object {
+    Object::Real(value)
+}
+
+/// Build a PDF with one page per image. `margin` is in points.
+pub fn build_image_pdf(
+    images: Vec<PdfImage>,
+    page_size: PageSize,
+    margin: f32,
+    fit: FitMode,
+) -> Result<Vec<u8>, Error> {
+    fn pdf_error(e: impl std::fmt::Display) -> Error {
+        Error::from(format!("Failed to build PDF: {}", e))
+    }
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let mut kids = Vec::new();
+
+    for image in images {
+        let (page_width, page_height) =
+            page_dimensions((image.width, image.height), page_size, margin);
+        let area = Rect {
+            x: margin,
+            y: margin,
+            width: (page_width - margin * 2.0).max(1.0),
+            height: (page_height - margin * 2.0).max(1.0),
+        };
+        let placed = place_image((image.width, image.height), area, fit);
+
+        let mut image_dict = dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => i64::from(image.width),
+            "Height" => i64::from(image.height),
+            "ColorSpace" => image.color_space,
+            "BitsPerComponent" => 8,
+        };
+        if let Some(filter) = image.filter {
+            image_dict.set("Filter", filter);
+        }
+        let mut image_stream = Stream::new(image_dict, image.data);
+        image_stream.compress().map_err(pdf_error)?;
+        let image_id = doc.add_object(image_stream);
+
+        let mut operations = vec![Operation::new("q", vec![])];
+        if fit == FitMode::Fill {
+            operations.extend([
+                Operation::new(
+                    "re",
+                    vec![
+                        real(area.x),
+                        real(area.y),
+                        real(area.width),
+                        real(area.height),
+                    ],
+                ),
+                Operation::new("W", vec![]),
+                Operation::new("n", vec![]),
+            ]);
+        }
+        operations.extend([
+            Operation::new(
+                "cm",
+                vec![
+                    real(placed.width),
+                    real(0.0),
+                    real(0.0),
+                    real(placed.height),
+                    real(placed.x),
+                    real(placed.y),
+                ],
+            ),
+            Operation::new("Do", vec![Object::Name(b"Im0".to_vec())]),
+            Operation::new("Q", vec![]),
+        ]);
+        let content = Content { operations }.encode().map_err(pdf_error)?;
+        let content_id = doc.add_object(Stream::new(Dictionary::new(), content));
+
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![real(0.0), real(0.0), real(page_width), real(page_height)],
+            "Contents" => content_id,
+            "Resources" => dictionary! {
+                "XObject" => dictionary! { "Im0" => image_id },
+            },
+        });
+        kids.push(Object::Reference(page_id));
+    }
+
+    let page_count = kids.len() as i64;
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => page_count,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut output = Vec::new();
+    doc.save_to(&mut output).map_err(pdf_error)?;
+    Ok(output)
+}
+
+/// Parse a 1-based page selection such as `1-3,5,8-` into 0-based indices.
+///
+/// An empty selection means every page.
+pub fn parse_page_range(input: &str, page_count: usize) -> Result<Vec<usize>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok((0..page_count).collect());
+    }
+
+    let invalid = || {
+        format!(
+            "Invalid page range `{}`. Use pages between 1 and {}, e.g. `1-3,5`.",
+            input, page_count
+        )
+    };
+    let parse_page = |value: &str| -> Result<usize, String> {
+        let page = value.trim().parse::<usize>().map_err(|_| invalid())?;
+        if page == 0 || page > page_count {
+            return Err(invalid());
+        }
+        Ok(page)
+    };
+
+    let mut pages = Vec::new();
+    let mut seen = vec![false; page_count];
+    for part in input.split(',') {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => {
+                let start = if start.trim().is_empty() {
+                    1
+                } else {
+                    parse_page(start)?
+                };
+                let end = if end.trim().is_empty() {
+                    page_count
+                } else {
+                    parse_page(end)?
+                };
+                (start, end)
+            }
+            None => {
+                let page = parse_page(part)?;
+                (page, page)
+            }
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        for page in start..=end {
+            if !std::mem::replace(&mut seen[page - 1], true) {
+                pages.push(page - 1);
+            }
+        }
+    }
+
+    Ok(pages)
+}
+
+/// Rasterize the selected pages of a PDF at `dpi`.
+pub fn render_pdf_pages(
+    data: Vec<u8>,
+    pages: Option<&str>,
+    dpi: u32,
+) -> Result<(usize, Vec<(usize, RgbaImage)>), Error> {
+    let pdf = Pdf::new(data).map_err(|e| {
+        Error::from(format!(
+            "Failed to read PDF ({:?}). Password-protected PDFs are not supported.",
+            e
+        ))
+    })?;
+    let all_pages = pdf.pages();
+    if all_pages.is_empty() {
+        return Err(Error::from("The PDF has no pages."));
+    }
+    let selection =
+        parse_page_range(pages.unwrap_or_default(), all_pages.len()).map_err(Error::from)?;
+    if selection.len() > MAX_RENDERED_PAGES {
+        return Err(Error::from(format!(
+            "At most {} pages can be rendered at once. Use `pages` to pick a range.",
+            MAX_RENDERED_PAGES
+        )));
+    }
+
+    let scale = dpi as f32 / POINTS_PER_INCH;
+    let cache = RenderCache::new();
+    let interpreter_settings = Default::default();
+    let pixmap_settings = PixmapSettings {
+        x_scale: scale,
+        y_scale: scale,
+        bg_color: WHITE,
+    };
+
+    let mut rendered = Vec::with_capacity(selection.len());
+    for index in selection {
+        let page = &all_pages[index];
+        let (width, height) = page.render_dimensions();
+        let (width, height) = ((width * scale) as u32, (height * scale) as u32);
+        validate_image_dimensions(width, height).map_err(Error::from)?;
+        if width > u32::from(u16::MAX) || height > u32::from(u16::MAX) {
+            return Err(Error::from("This page is too large to render."));
+        }
+
+        let pixmap = hayro::render(
+            page,
+            &cache,
+            &interpreter_settings,
+            &RenderSettings::default(),
+            &pixmap_settings,
+        );
+        // The white background makes every pixel opaque, so premultiplied
+        // and straight alpha are identical.
+        let image = RgbaImage::from_raw(
+            u32::from(pixmap.width()),
+            u32::from(pixmap.height()),
+            pixmap.data_as_u8_slice().to_vec(),
+        )
+        .ok_or_else(|| Error::from("Failed to read the rendered page."))?;
+        rendered.push((index + 1, image));
+    }
+
+    Ok((all_pages.len(), rendered))
+}
+
+/// Combine images into a PDF with one image per page
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn images_to_pdf(
+    ctx: Context<'_>,
+    #[description = "First image"] image1: Attachment,
+    #[description = "Second image"] image2: Option<Attachment>,
+    #[description = "Third image"] image3: Option<Attachment>,
+    #[description = "Fourth image"] image4: Option<Attachment>,
+    #[description = "Fifth image"] image5: Option<Attachment>,
+    #[description = "Sixth image"] image6: Option<Attachment>,
+    #[description = "Seventh image"] image7: Option<Attachment>,
+    #[description = "Eighth image"] image8: Option<Attachment>,
+    #[description = "Ninth image"] image9: Option<Attachment>,
+    #[description = "Tenth image"] image10: Option<Attachment>,
+    #[description = "Page size (defaults to A4)"] page_size: Option<PageSize>,
+    #[description = "Margin in millimetres (defaults to 10, or 0 when matching the image)"]
+    #[min = 0]
+    #[max = 50]
+    margin: Option<f64>,
+    #[description = "How images fill the page (defaults to Fit)"] fit: Option<FitMode>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let files: Vec<Attachment> = [
+        Some(image1),
+        image2,
+        image3,
+        image4,
+        image5,
+        image6,
+        image7,
+        image8,
+        image9,
+        image10,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let page_size = page_size.unwrap_or(PageSize::A4);
+    let fit = fit.unwrap_or(FitMode::Fit);
+    let default_margin = if page_size == PageSize::MatchImage {
+        0.0
+    } else {
+        DEFAULT_MARGIN_MM
+    };
+    let margin = mm_to_points(margin.map_or(default_margin, |margin| margin as f32));
+
+    if let Err(message) = validate_combined_attachment_size(&files) {
+        let embed = error_embed("❌ Files Too Large", message);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let mut uploads = Vec::with_capacity(files.len());
+    for file in &files {
+        match download_attachment(file).await {
+            Ok(data) => uploads.push((file.filename.clone(), data)),
+            Err(e) => {
+                let embed = error_embed("❌ PDF Creation Failed", e.to_string());
+                ctx.send(poise::CreateReply::default().embed(embed)).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let result = tokio::task::spawn_blocking(move || {
+        let images = uploads
+            .iter()
+            .map(|(filename, data)| {
+                decode_image(data)
+                    .and_then(|img| pdf_image(data, &img))
+                    .map_err(|e| Error::from(format!("`{}`: {}", filename, e)))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        build_image_pdf(images, page_size, margin, fit)
+    })
+    .await
+    .map_err(|e| Error::from(format!("PDF task failed: {}", e)))
+    .and_then(|result| result);
+
+    let pdf = match result {
+        Ok(pdf) => pdf,
+        Err(e) => {
+            let embed = error_embed("❌ PDF Creation Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(pdf.len(), "PDF").map_err(Error::from)?;
+
+    let embed = CreateEmbed::new()
+        .title("✅ PDF Created")
+        .description(format!(
+            "**Pages:** {}\n**Page size:** {}\n**Size:** {}",
+            files.len(),
+            poise::ChoiceParameter::name(&page_size),
+            format_file_size(pdf.len() as u64)
+        ))
+        .color(0x27ae60);
+
+    let filename = format!("{}.pdf", sanitize_filename(&file_stem(&files[0].filename)));
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(pdf, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Render PDF pages as images
+#[poise::command(slash_command)]
+pub async fn pdf_to_images(
+    ctx: Context<'_>,
+    #[description = "PDF to render"] file: Attachment,
+    #[description = "Pages to render, e.g. 1-3,5 (defaults to all)"] pages: Option<String>,
+    #[description = "Resolution in DPI (defaults to 150)"]
+    #[min = 36]
+    #[max = 300]
+    dpi: Option<u32>,
+    #[description = "Image format (defaults to PNG)"] output_format: Option<OutputFormat>,
+    #[description = "Always return the pages as a ZIP archive"] as_zip: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let dpi = dpi.unwrap_or(DEFAULT_DPI);
+    let output_format = output_format.unwrap_or(OutputFormat::Png);
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => {
+            let embed = error_embed("❌ PDF Rendering Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let result = tokio::task::spawn_blocking(move || {
+        let (page_count, rendered) = render_pdf_pages(data, pages.as_deref(), dpi)?;
+        let encoded = rendered
+            .into_iter()
+            .map(|(page, image)| {
+                encode_image(DynamicImage::ImageRgba8(image), output_format)
+                    .map(|bytes| (page, bytes))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok::<_, Error>((page_count, encoded))
+    })
+    .await
+    .map_err(|e| Error::from(format!("PDF rendering task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (page_count, encoded) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let embed = error_embed("❌ PDF Rendering Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let stem = sanitize_filename(&file_stem(&file.filename));
+    let entries: Vec<(String, Vec<u8>)> = encoded
+        .into_iter()
+        .map(|(page, bytes)| {
+            (
+                format!("{}_page{}.{}", stem, page, output_format.extension()),
+                bytes,
+            )
+        })
+        .collect();
+    let rendered_count = entries.len();
+
+    let embed = CreateEmbed::new()
+        .title("✅ PDF Rendered")
+        .description(format!(
+            "**File:** `{}`\n**Pages rendered:** {} of {}\n**Resolution:** {} DPI",
+            file.filename, rendered_count, page_count, dpi
+        ))
+        .color(0x27ae60);
+
+    let reply = if as_zip.unwrap_or(false) || rendered_count > MAX_MESSAGE_ATTACHMENTS {
+        let archive = zip_entries(&entries)?;
+        validate_output_size(archive.len(), "Page archive").map_err(Error::from)?;
+        poise::CreateReply::default()
+            .embed(embed.footer(CreateEmbedFooter::new(
+                "Pages are attached as a ZIP archive.",
+            )))
+            .attachment(CreateAttachment::bytes(
+                archive,
+                format!("{}_pages.zip", stem),
+            ))
+    } else {
+        let total: usize = entries.iter().map(|(_, bytes)| bytes.len()).sum();
+        validate_output_size(total, "Rendered pages").map_err(Error::from)?;
+        entries.into_iter().fold(
+            poise::CreateReply::default().embed(embed),
+            |reply, (name, bytes)| reply.attachment(CreateAttachment::bytes(bytes, name)),
+        )
+    };
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FitMode, PageSize, Rect, build_image_pdf, parse_page_range, pdf_image, place_image,
+        render_pdf_pages,
+    };
+    use crate::conversion::image::{OutputFormat, encode_image};
+    use image::{DynamicImage, Rgb, RgbImage};
+    use lopdf::{Document, Object, dictionary};
+
+    const AREA: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 100.0,
+        height: 100.0,
+    };
+
+    #[test]
+    fn page_ranges_accept_lists_and_open_ends() {
+        assert_eq!(parse_page_range("", 3).unwrap(), vec![0, 1, 2]);
+        assert_eq!(parse_page_range("3,1-2,2", 5).unwrap(), vec![2, 0, 1]);
+        assert_eq!(parse_page_range("4-", 5).unwrap(), vec![3, 4]);
+        assert_eq!(parse_page_range("1-,2-", 100_000).unwrap().len(), 100_000);
+        assert!(parse_page_range("0", 5).is_err());
+        assert!(parse_page_range("3-2", 5).is_err());
+        assert!(parse_page_range("6", 5).is_err());
+    }
+
+    #[test]
+    fn fit_and_fill_preserve_aspect_ratio() {
+        let fit = place_image((200, 100), AREA, FitMode::Fit);
+        assert_eq!((fit.width, fit.height, fit.y), (100.0, 50.0, 25.0));
+        let fill = place_image((200, 100), AREA, FitMode::Fill);
+        assert_eq!((fill.width, fill.height, fill.x), (200.0, 100.0, -50.0));
+        assert_eq!(place_image((200, 100), AREA, FitMode::Stretch), AREA);
+    }
+
+    #[test]
+    fn image_pdf_renders_back_to_pages() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 20, Rgb([255, 0, 0])));
+        let jpeg = encode_image(img.clone(), OutputFormat::Jpg).unwrap();
+        let png = encode_image(img.clone(), OutputFormat::Png).unwrap();
+        let images = vec![
+            pdf_image(&jpeg, &img).unwrap(),
+            pdf_image(&png, &img).unwrap(),
+        ];
+        assert_eq!(images[0].filter, Some("DCTDecode"));
+        assert_eq!(images[1].filter, None);
+
+        let pdf = build_image_pdf(images, PageSize::MatchImage, 0.0, FitMode::Fit).unwrap();
+        let (page_count, pages) = render_pdf_pages(pdf, Some("2"), 96).unwrap();
+        assert_eq!(page_count, 2);
+        let (number, page) = &pages[0];
+        assert_eq!(*number, 2);
+        assert_eq!(page.dimensions(), (40, 20));
+        assert_eq!(page.get_pixel(20, 10).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn jpeg_pages_embed_the_original_stream() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 20, Rgb([0, 0, 255])));
+        let jpeg = encode_image(img.clone(), OutputFormat::Jpg).unwrap();
+        let images = vec![pdf_image(&jpeg, &img).unwrap()];
+        let pdf = build_image_pdf(images, PageSize::MatchImage, 0.0, FitMode::Fit).unwrap();
+
+        let doc = Document::load_mem(&pdf).unwrap();
+        let stream = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_stream().ok())
+            .find(|stream| {
+                stream.dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image")
+            })
+            .unwrap();
+        assert_eq!(
+            stream
+                .dict
+                .get(b"Filter")
+                .and_then(Object::as_name)
+                .unwrap(),
+            b"DCTDecode"
+        );
+        assert_eq!(stream.content, jpeg);
+
+        let (_, pages) = render_pdf_pages(pdf, None, 96).unwrap();
+        let [red, green, blue, _] = pages[0].1.get_pixel(20, 10).0;
+        assert!(
+            red < 16 && green < 16 && blue > 239,
+            "{:?}",
+            (red, green, blue)
+        );
+    }
+
+    #[test]
+    fn empty_pdfs_are_rejected_before_the_page_range() {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.add_object(dictionary! {
+            "Type" => "Pages",
+            "Kids" => Vec::<Object>::new(),
+            "Count" => 0,
+        });
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        let mut pdf = Vec::new();
+        doc.save_to(&mut pdf).unwrap();
+
+        let error = render_pdf_pages(pdf, Some("1"), 96).unwrap_err();
+        assert_eq!(error.to_string(), "The PDF has no pages.");
+    }
+}
@@ -0,0 +1,879 @@
+use crate::attachments::{
+    download_attachment, sanitize_filename, validate_combined_attachment_size, validate_output_size,
+};
+use crate::compression::compress::zip_entries;
+use crate::conversion::pdf::parse_page_range;
+use crate::embeds::error_embed;
+use crate::utils::{file_stem, format_file_size};
+use crate::{Context, Error};
+use lopdf::{Document, Object, ObjectId, decode_text_string, dictionary, text_string};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed};
+
+const MAX_PDF_PAGES: usize = 1000;
+const MAX_PDF_OBJECTS: usize = 200_000;
+const MAX_SPLIT_PARTS: usize = 100;
+const MAX_INFO_CHARS: usize = 500;
+/// Deepest page tree walked when resolving inherited page attributes
+const MAX_TREE_DEPTH: usize = 64;
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// Page attributes a page may inherit from its ancestors in the page tree
+const INHERITABLE_KEYS: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Document info keys shown by `/pdf info`, with their labels
+const INFO_FIELDS: [(&[u8], &str); 8] = [
+    (b"Title", "Title"),
+    (b"Author", "Author"),
+    (b"Subject", "Subject"),
+    (b"Keywords", "Keywords"),
+    (b"Creator", "Creator"),
+    (b"Producer", "Producer"),
+    (b"CreationDate", "Created"),
+    (b"ModDate", "Modified"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Rotation {
+    #[name = "90° clockwise"]
+    Clockwise,
+    #[name = "180°"]
+    HalfTurn,
+    #[name = "90° counter-clockwise"]
+    CounterClockwise,
+}
+
+impl Rotation {
+    const fn degrees(self) -> i64 {
+        match self {
+            Self::Clockwise => 90,
+            Self::HalfTurn => 180,
+            Self::CounterClockwise => 270,
+        }
+    }
+}
+
+fn pdf_error(e: impl std::fmt::Display) -> Error {
+    Error::from(format!("Failed to process PDF: {}", e))
+}
+
+/// Parse a PDF, rejecting encrypted files and documents over the page or object limits.
+pub fn load_pdf(data: &[u8]) -> Result<Document, Error> {
+    let doc =
+        Document::load_mem(data).map_err(|e| Error::from(format!("Failed to read PDF: {}", e)))?;
+    if doc.is_encrypted() || doc.was_encrypted() {
+        return Err(Error::from("Password-protected PDFs are not supported."));
+    }
+    if doc.objects.len() > MAX_PDF_OBJECTS {
+        return Err(Error::from(format!(
+            "This PDF has too many objects to process (over {}).",
+            MAX_PDF_OBJECTS
+        )));
+    }
+    let pages = doc.get_pages().len();
+    if pages == 0 {
+        return Err(Error::from("The PDF has no pages."));
+    }
+    if pages > MAX_PDF_PAGES {
+        return Err(Error::from(format!(
+            "This PDF has {} pages; at most {} are supported.",
+            pages, MAX_PDF_PAGES
+        )));
+    }
+    Ok(doc)
+}
+
+pub fn save_pdf(mut doc: Document) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    doc.save_to(&mut output).map_err(pdf_error)?;
+    Ok(output)
+}
+
+/// Copy inherited attributes onto each page so it can leave its page tree.
+fn inherit_page_attributes(doc: &mut Document) -> Result<(), Error> {
+    for page_id in doc.get_pages().into_values() {
+        let page = doc.get_dictionary(page_id).map_err(pdf_error)?;
+        let mut missing: Vec<&[u8]> = INHERITABLE_KEYS
+            .into_iter()
+            .filter(|key| !page.has(key))
+            .collect();
+        let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+        let mut inherited = Vec::new();
+        let mut depth = 0;
+        while let Some(id) = parent
+            && !missing.is_empty()
+            && depth < MAX_TREE_DEPTH
+        {
+            let Ok(node) = doc.get_dictionary(id) else {
+                break;
+            };
+            missing.retain(|key| match node.get(key) {
+                Ok(value) => {
+                    inherited.push((key.to_vec(), value.clone()));
+                    false
+                }
+                Err(_) => true,
+            });
+            parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+            depth += 1;
+        }
+
+        let page = doc.get_dictionary_mut(page_id).map_err(pdf_error)?;
+        for (key, value) in inherited {
+            page.set(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Build one document from the selected pages (0-based, in order) of each input.
+///
+/// Each input keeps its page content and resources, but document-level
+/// structures such as outlines and form fields are dropped. The document
+/// info of the first input is carried over.
+pub fn assemble(parts: Vec<(Document, Vec<usize>)>) -> Result<Document, Error> {
+    let total: usize = parts.iter().map(|(_, pages)| pages.len()).sum();
+    if total == 0 {
+        return Err(Error::from("No pages were selected."));
+    }
+    if total > MAX_PDF_PAGES {
+        return Err(Error::from(format!(
+            "The result would have {} pages; at most {} are supported.",
+            total, MAX_PDF_PAGES
+        )));
+    }
+
+    let version = parts
+        .iter()
+        .map(|(doc, _)| doc.version.clone())
+        .max()
+        .unwrap_or_else(|| "1.7".to_string());
+    let mut merged = Document::with_version(version);
+    let mut next_id = 1;
+    let mut kids: Vec<ObjectId> = Vec::with_capacity(total);
+    let mut info = None;
+    for (mut doc, selection) in parts {
+        inherit_page_attributes(&mut doc)?;
+        doc.renumber_objects_with(next_id);
+        next_id = doc.max_id + 1;
+
+        let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+        for index in selection {
+            let page = pages
+                .get(index)
+                .ok_or_else(|| Error::from("A selected page does not exist."))?;
+            kids.push(*page);
+        }
+        if info.is_none() {
+            info = doc.trailer.get(b"Info").ok().cloned();
+        }
+        // The page trees are rebuilt below, and unused pages are pruned with them
+        merged.objects.extend(
+            doc.objects
+                .into_iter()
+                .filter(|(_, object)| !matches!(object.type_name(), Ok(b"Catalog" | b"Pages"))),
+        );
+    }
+    merged.max_id = next_id;
+
+    let pages_id = merged.new_object_id();
+    for &page_id in &kids {
+        merged
+            .get_dictionary_mut(page_id)
+            .map_err(pdf_error)?
+            .set("Parent", pages_id);
+    }
+    merged.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids.iter().map(|&id| Object::Reference(id)).collect::<Vec<_>>(),
+            "Count" => kids.len() as i64,
+        }),
+    );
+    let catalog_id = merged.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    merged.trailer.set("Root", catalog_id);
+    if let Some(info) = info {
+        merged.trailer.set("Info", info);
+    }
+    merged.prune_objects();
+    merged.renumber_objects();
+    Ok(merged)
+}
+
+/// Split a page count into parts: the comma-separated `ranges`, or runs of `per_file` pages.
+pub fn split_parts(
+    ranges: Option<&str>,
+    page_count: usize,
+    per_file: usize,
+) -> Result<Vec<Vec<usize>>, String> {
+    let parts: Vec<Vec<usize>> = match ranges.map(str::trim).filter(|ranges| !ranges.is_empty()) {
+        Some(ranges) => ranges
+            .split(',')
+            .map(|part| {
+                if part.trim().is_empty() {
+                    return Err("Each part needs a page or range, e.g. `1-3,4-6`.".to_string());
+                }
+                parse_page_range(part, page_count)
+            })
+            .collect::<Result<_, _>>()?,
+        None => (0..page_count)
+            .collect::<Vec<_>>()
+            .chunks(per_file.max(1))
+            .map(<[usize]>::to_vec)
+            .collect(),
+    };
+    if parts.len() > MAX_SPLIT_PARTS {
+        return Err(format!(
+            "That would create {} files; at most {} are supported.",
+            parts.len(),
+            MAX_SPLIT_PARTS
+        ));
+    }
+    Ok(parts)
+}
+
+/// Human readable label for a part, e.g. `p1-3` or `p5`.
+fn part_label(pages: &[usize]) -> String {
+    match (pages.first(), pages.last()) {
+        (Some(first), Some(last)) if first != last => format!("p{}-{}", first + 1, last + 1),
+        (Some(first), _) => format!("p{}", first + 1),
+        _ => "empty".to_string(),
+    }
+}
+
+/// Add `degrees` to the rotation of the selected pages.
+pub fn rotate_pages(doc: &mut Document, pages: &[usize], degrees: i64) -> Result<(), Error> {
+    inherit_page_attributes(doc)?;
+    let page_ids: Vec<ObjectId> = doc.get_pages().into_values().collect();
+    for &index in pages {
+        let page_id = *page_ids
+            .get(index)
+            .ok_or_else(|| Error::from("A selected page does not exist."))?;
+        let page = doc.get_dictionary_mut(page_id).map_err(pdf_error)?;
+        let current = page.get(b"Rotate").and_then(Object::as_i64).unwrap_or(0);
+        page.set("Rotate", (current + degrees).rem_euclid(360));
+    }
+    Ok(())
+}
+
+/// Summary shown by `/pdf info`
+pub struct PdfSummary {
+    pub pages: usize,
+    pub objects: usize,
+    pub version: String,
+    /// Width and height of the first page in points
+    pub page_size: Option<(f32, f32)>,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+fn info_dictionary(doc: &Document) -> Option<&lopdf::Dictionary> {
+    match doc.trailer.get(b"Info").ok()? {
+        Object::Reference(id) => doc.get_dictionary(*id).ok(),
+        Object::Dictionary(dict) => Some(dict),
+        _ => None,
+    }
+}
+
+/// Turn a PDF date such as `D:20240131120000+01'00'` into `2024-01-31 12:00`.
+fn format_pdf_date(value: &str) -> String {
+    let digits: String = value
+        .trim_start_matches("D:")
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    if digits.len() < 8 {
+        return value.to_string();
+    }
+    let mut formatted = format!("{}-{}-{}", &digits[0..4], &digits[4..6], &digits[6..8]);
+    if digits.len() >= 12 {
+        formatted.push_str(&format!(" {}:{}", &digits[8..10], &digits[10..12]));
+    }
+    formatted
+}
+
+pub fn summarize(doc: &Document) -> PdfSummary {
+    let pages = doc.get_pages();
+    let page_size = pages.values().next().and_then(|&page_id| {
+        // Walk up the tree in case the media box is inherited
+        let mut node = doc.get_dictionary(page_id).ok();
+        for _ in 0..MAX_TREE_DEPTH {
+            let dict = node?;
+            if let Ok(media_box) = dict.get(b"MediaBox").and_then(Object::as_array) {
+                let values: Vec<f32> = media_box
+                    .iter()
+                    .filter_map(|value| value.as_float().ok())
+                    .collect();
+                if let [x0, y0, x1, y1] = values[..] {
+                    return Some(((x1 - x0).abs(), (y1 - y0).abs()));
+                }
+                return None;
+            }
+            let parent = dict.get(b"Parent").and_then(Object::as_reference).ok()?;
+            node = doc.get_dictionary(parent).ok();
+        }
+        None
+    });
+
+    let fields = info_dictionary(doc)
+        .map(|info| {
+            INFO_FIELDS
+                .iter()
+                .filter_map(|&(key, label)| {
+                    let value = decode_text_string(info.get(key).ok()?).ok()?;
+                    let value = value.trim();
+                    if value.is_empty() {
+                        return None;
+                    }
+                    let value = if key.ends_with(b"Date") {
+                        format_pdf_date(value)
+                    } else {
+                        value.to_string()
+                    };
+                    Some((label, value))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    PdfSummary {
+        pages: pages.len(),
+        objects: doc.objects.len(),
+        version: doc.version.clone(),
+        page_size,
+        fields,
+    }
+}
+
+/// Set or clear document info entries. An empty value removes the entry.
+pub fn set_document_info(doc: &mut Document, entries: &[(&[u8], String)]) -> Result<(), Error> {
+    for (key, value) in entries {
+        if value.chars().count() > MAX_INFO_CHARS {
+            return Err(Error::from(format!(
+                "The {} must be at most {} characters.",
+                String::from_utf8_lossy(key).to_lowercase(),
+                MAX_INFO_CHARS
+            )));
+        }
+    }
+
+    let info_id = match doc.trailer.get(b"Info") {
+        Ok(Object::Reference(id)) if doc.get_dictionary(*id).is_ok() => *id,
+        Ok(Object::Dictionary(dict)) => {
+            let dict = dict.clone();
+            doc.add_object(dict)
+        }
+        _ => doc.add_object(lopdf::Dictionary::new()),
+    };
+    doc.trailer.set("Info", info_id);
+
+    let info = doc.get_dictionary_mut(info_id).map_err(pdf_error)?;
+    for (key, value) in entries {
+        let value = value.trim();
+        if value.is_empty() {
+            info.remove(key);
+        } else {
+            info.set(key.to_vec(), text_string(value));
+        }
+    }
+    Ok(())
+}
+
+async fn run_blocking<T: Send + 'static>(
+    job: impl FnOnce() -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    tokio::task::spawn_blocking(job)
+        .await
+        .map_err(|e| Error::from(format!("PDF task failed: {}", e)))
+        .and_then(|result| result)
+}
+
+async fn send_error(
+    ctx: Context<'_>,
+    title: &str,
+    message: impl Into<String>,
+) -> Result<(), Error> {
+    let embed = error_embed(title, message);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Send a processed PDF along with a summary embed.
+async fn send_pdf(
+    ctx: Context<'_>,
+    title: &str,
+    description: String,
+    pdf: Vec<u8>,
+    filename: String,
+) -> Result<(), Error> {
+    validate_output_size(pdf.len(), "PDF").map_err(Error::from)?;
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(format!(
+            "{}\n**Size:** {}",
+            description,
+            format_file_size(pdf.len() as u64)
+        ))
+        .color(0x27ae60);
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(pdf, filename)),
+    )
+    .await?;
+    Ok(())
+}
+
+fn output_filename(file: &Attachment, suffix: &str) -> String {
+    format!(
+        "{}_{}.pdf",
+        sanitize_filename(&file_stem(&file.filename)),
+        suffix
+    )
+}
+
+/// Merge, split, reorder, rotate and inspect PDFs
+#[poise::command(
+    slash_command,
+    subcommands("merge", "split", "extract", "delete", "rotate", "info", "set_info"),
+    subcommand_required
+)]
+pub async fn pdf(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Combine PDFs into one, in the order given
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn merge(
+    ctx: Context<'_>,
+    #[description = "First PDF"] file1: Attachment,
+    #[description = "Second PDF"] file2: Attachment,
+    #[description = "Third PDF"] file3: Option<Attachment>,
+    #[description = "Fourth PDF"] file4: Option<Attachment>,
+    #[description = "Fifth PDF"] file5: Option<Attachment>,
+    #[description = "Sixth PDF"] file6: Option<Attachment>,
+    #[description = "Seventh PDF"] file7: Option<Attachment>,
+    #[description = "Eighth PDF"] file8: Option<Attachment>,
+    #[description = "Ninth PDF"] file9: Option<Attachment>,
+    #[description = "Tenth PDF"] file10: Option<Attachment>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let files: Vec<Attachment> = [
+        Some(file1),
+        Some(file2),
+        file3,
+        file4,
+        file5,
+        file6,
+        file7,
+        file8,
+        file9,
+        file10,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if let Err(message) = validate_combined_attachment_size(&files) {
+        return send_error(ctx, "❌ Files Too Large", message).await;
+    }
+
+    let mut uploads = Vec::with_capacity(files.len());
+    for file in &files {
+        match download_attachment(file).await {
+            Ok(data) => uploads.push((file.filename.clone(), data)),
+            Err(e) => return send_error(ctx, "❌ Merge Failed", e.to_string()).await,
+        }
+    }
+
+    let result = run_blocking(move || {
+        let parts = uploads
+            .iter()
+            .map(|(filename, data)| {
+                let doc =
+                    load_pdf(data).map_err(|e| Error::from(format!("`{}`: {}", filename, e)))?;
+                let pages = (0..doc.get_pages().len()).collect();
+                Ok((doc, pages))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let merged = assemble(parts)?;
+        let pages = merged.get_pages().len();
+        save_pdf(merged).map(|pdf| (pdf, pages))
+    })
+    .await;
+    let (pdf, pages) = match result {
+        Ok(result) => result,
+        Err(e) => return send_error(ctx, "❌ Merge Failed", e.to_string()).await,
+    };
+
+    let description = format!("**Files merged:** {}\n**Pages:** {}", files.len(), pages);
+    let filename = output_filename(&files[0], "merged");
+    send_pdf(ctx, "✅ PDFs Merged", description, pdf, filename).await
+}
+
+/// Split a PDF into single pages or ranges, returned as a ZIP
+#[poise::command(slash_command)]
+pub async fn split(
+    ctx: Context<'_>,
+    #[description = "PDF to split"] file: Attachment,
+    #[description = "One file per comma-separated part, e.g. 1-3,4-6,7- (default: every page)"]
+    ranges: Option<String>,
+    #[description = "Pages per file when no ranges are given (default: 1)"]
+    #[min = 1]
+    #[max = 100]
+    pages_per_file: Option<u32>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => return send_error(ctx, "❌ Split Failed", e.to_string()).await,
+    };
+    let stem = sanitize_filename(&file_stem(&file.filename));
+    let entry_stem = stem.clone();
+    let result = run_blocking(move || {
+        let doc = load_pdf(&data)?;
+        let page_count = doc.get_pages().len();
+        let per_file = pages_per_file.unwrap_or(1) as usize;
+        let parts = split_parts(ranges.as_deref(), page_count, per_file).map_err(Error::from)?;
+        let entries = parts
+            .into_iter()
+            .map(|pages| {
+                let name = format!("{}_{}.pdf", entry_stem, part_label(&pages));
+                let part = assemble(vec![(doc.clone(), pages)])?;
+                save_pdf(part).map(|pdf| (name, pdf))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let archive = zip_entries(&entries)?;
+        Ok((archive, entries.len(), page_count))
+    })
+    .await;
+    let (archive, part_count, page_count) = match result {
+        Ok(result) => result,
+        Err(e) => return send_error(ctx, "❌ Split Failed", e.to_string()).await,
+    };
+    validate_output_size(archive.len(), "Split archive").map_err(Error::from)?;
+
+    let embed = CreateEmbed::new()
+        .title("✅ PDF Split")
+        .description(format!(
+            "**File:** `{}`\n**Pages:** {}\n**Files created:** {}",
+            file.filename, page_count, part_count
+        ))
+        .color(0x27ae60);
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                archive,
+                format!("{}_split.zip", stem),
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Keep only the chosen pages, in the order given; also reorders pages
+#[poise::command(slash_command)]
+pub async fn extract(
+    ctx: Context<'_>,
+    #[description = "PDF to take pages from"] file: Attachment,
+    #[description = "Pages to keep in the new order, e.g. 3,1-2,4-"] pages: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => return send_error(ctx, "❌ Extraction Failed", e.to_string()).await,
+    };
+    let result = run_blocking(move || {
+        let doc = load_pdf(&data)?;
+        let page_count = doc.get_pages().len();
+        let selection = parse_page_range(&pages, page_count).map_err(Error::from)?;
+        let kept = selection.len();
+        save_pdf(assemble(vec![(doc, selection)])?).map(|pdf| (pdf, kept, page_count))
+    })
+    .await;
+    let (pdf, kept, page_count) = match result {
+        Ok(result) => result,
+        Err(e) => return send_error(ctx, "❌ Extraction Failed", e.to_string()).await,
+    };
+
+    let description = format!(
+        "**File:** `{}`\n**Pages kept:** {} of {}",
+        file.filename, kept, page_count
+    );
+    let filename = output_filename(&file, "pages");
+    send_pdf(ctx, "✅ Pages Extracted", description, pdf, filename).await
+}
+
+/// Remove pages from a PDF
+#[poise::command(slash_command)]
+pub async fn delete(
+    ctx: Context<'_>,
+    #[description = "PDF to remove pages from"] file: Attachment,
+    #[description = "Pages to remove, e.g. 2,5-7"] pages: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => return send_error(ctx, "❌ Deletion Failed", e.to_string()).await,
+    };
+    let result = run_blocking(move || {
+        let doc = load_pdf(&data)?;
+        let page_count = doc.get_pages().len();
+        let mut removed = vec![false; page_count];
+        for page in parse_page_range(&pages, page_count).map_err(Error::from)? {
+            removed[page] = true;
+        }
+        let kept: Vec<usize> = (0..page_count).filter(|&page| !removed[page]).collect();
+        if kept.is_empty() {
+            return Err(Error::from(
+                "A PDF needs at least one page; not every page can be removed.",
+            ));
+        }
+        let remaining = kept.len();
+        save_pdf(assemble(vec![(doc, kept)])?).map(|pdf| (pdf, remaining, page_count))
+    })
+    .await;
+    let (pdf, remaining, page_count) = match result {
+        Ok(result) => result,
+        Err(e) => return send_error(ctx, "❌ Deletion Failed", e.to_string()).await,
+    };
+
+    let description = format!(
+        "**File:** `{}`\n**Pages removed:** {}\n**Pages left:** {}",
+        file.filename,
+        page_count - remaining,
+        remaining
+    );
+    let filename = output_filename(&file, "trimmed");
+    send_pdf(ctx, "✅ Pages Removed", description, pdf, filename).await
+}
+
+/// Rotate some or all pages of a PDF
+#[poise::command(slash_command)]
+pub async fn rotate(
+    ctx: Context<'_>,
+    #[description = "PDF to rotate"] file: Attachment,
+    #[description = "Rotation to apply"] angle: Rotation,
+    #[description = "Pages to rotate, e.g. 1-3,5 (defaults to all)"] pages: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => return send_error(ctx, "❌ Rotation Failed", e.to_string()).await,
+    };
+    let result = run_blocking(move || {
+        let mut doc = load_pdf(&data)?;
+        let page_count = doc.get_pages().len();
+        let selection = parse_page_range(pages.as_deref().unwrap_or_default(), page_count)
+            .map_err(Error::from)?;
+        rotate_pages(&mut doc, &selection, angle.degrees())?;
+        save_pdf(doc).map(|pdf| (pdf, selection.len()))
+    })
+    .await;
+    let (pdf, rotated) = match result {
+        Ok(result) => result,
+        Err(e) => return send_error(ctx, "❌ Rotation Failed", e.to_string()).await,
+    };
+
+    let description = format!(
+        "**File:** `{}`\n**Pages rotated:** {}\n**Rotation:** {}",
+        file.filename,
+        rotated,
+        poise::ChoiceParameter::name(&angle)
+    );
+    let filename = output_filename(&file, "rotated");
+    send_pdf(ctx, "✅ Pages Rotated", description, pdf, filename).await
+}
+
+/// Show the page count, size and document info of a PDF
+#[poise::command(slash_command)]
+pub async fn info(
+    ctx: Context<'_>,
+    #[description = "PDF to inspect"] file: Attachment,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => return send_error(ctx, "❌ PDF Info Failed", e.to_string()).await,
+    };
+    let summary = match run_blocking(move || load_pdf(&data).map(|doc| summarize(&doc))).await {
+        Ok(summary) => summary,
+        Err(e) => return send_error(ctx, "❌ PDF Info Failed", e.to_string()).await,
+    };
+
+    let mut description = format!(
+        "**File:** `{}`\n**Pages:** {}\n**PDF version:** {}\n**Objects:** {}",
+        file.filename, summary.pages, summary.version, summary.objects
+    );
+    if let Some((width, height)) = summary.page_size {
+        description.push_str(&format!(
+            "\n**Page size:** {:.0} × {:.0} pt ({:.0} × {:.0} mm)",
+            width,
+            height,
+            width / POINTS_PER_MM,
+            height / POINTS_PER_MM
+        ));
+    }
+    let mut embed = CreateEmbed::new()
+        .title("📄 PDF Info")
+        .description(description)
+        .color(0x27ae60);
+    for (label, value) in summary.fields {
+        let value: String = value.chars().take(1024).collect();
+        embed = embed.field(label, value, true);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Edit the title, author, subject and keywords of a PDF
+#[poise::command(slash_command)]
+pub async fn set_info(
+    ctx: Context<'_>,
+    #[description = "PDF to edit"] file: Attachment,
+    #[description = "New title (a single space clears it)"] title: Option<String>,
+    #[description = "New author (a single space clears it)"] author: Option<String>,
+    #[description = "New subject (a single space clears it)"] subject: Option<String>,
+    #[description = "New keywords (a single space clears them)"] keywords: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let entries: Vec<(&'static [u8], String)> = [
+        (b"Title".as_slice(), title),
+        (b"Author".as_slice(), author),
+        (b"Subject".as_slice(), subject),
+        (b"Keywords".as_slice(), keywords),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| value.map(|value| (key, value)))
+    .collect();
+    if entries.is_empty() {
+        return send_error(
+            ctx,
+            "❌ Nothing To Change",
+            "Provide at least one of `title`, `author`, `subject` or `keywords`.",
+        )
+        .await;
+    }
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => return send_error(ctx, "❌ Editing Failed", e.to_string()).await,
+    };
+    let changed = entries.len();
+    let result = run_blocking(move || {
+        let mut doc = load_pdf(&data)?;
+        set_document_info(&mut doc, &entries)?;
+        save_pdf(doc)
+    })
+    .await;
+    let pdf = match result {
+        Ok(pdf) => pdf,
+        Err(e) => return send_error(ctx, "❌ Editing Failed", e.to_string()).await,
+    };
+
+    let description = format!(
+        "**File:** `{}`\n**Fields updated:** {}",
+        file.filename, changed
+    );
+    let filename = output_filename(&file, "edited");
+    send_pdf(ctx, "✅ PDF Info Updated", description, pdf, filename).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        assemble, load_pdf, rotate_pages, save_pdf, set_document_info, split_parts, summarize,
+    };
+    use crate::conversion::image::{OutputFormat, encode_image};
+    use crate::conversion::pdf::{FitMode, PageSize, build_image_pdf, pdf_image, render_pdf_pages};
+    use image::{DynamicImage, Rgb, RgbImage};
+
+    /// A PDF with one solid 30×20 pixel page per colour.
+    fn colored_pdf(colors: &[[u8; 3]]) -> Vec<u8> {
+        let images = colors
+            .iter()
+            .map(|&color| {
+                let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(30, 20, Rgb(color)));
+                let png = encode_image(img.clone(), OutputFormat::Png).unwrap();
+                pdf_image(&png, &img).unwrap()
+            })
+            .collect();
+        build_image_pdf(images, PageSize::MatchImage, 0.0, FitMode::Fit).unwrap()
+    }
+
+    fn page_color(pdf: &[u8], page: usize) -> [u8; 4] {
+        let (_, pages) = render_pdf_pages(pdf.to_vec(), Some(&page.to_string()), 96).unwrap();
+        pages[0].1.get_pixel(10, 10).0
+    }
+
+    #[test]
+    fn merge_and_reorder_keep_page_content() {
+        let first = load_pdf(&colored_pdf(&[[255, 0, 0], [0, 255, 0]])).unwrap();
+        let second = load_pdf(&colored_pdf(&[[0, 0, 255]])).unwrap();
+        let merged =
+            save_pdf(assemble(vec![(first, vec![1, 0]), (second, vec![0])]).unwrap()).unwrap();
+
+        assert_eq!(load_pdf(&merged).unwrap().get_pages().len(), 3);
+        assert_eq!(page_color(&merged, 1), [0, 255, 0, 255]);
+        assert_eq!(page_color(&merged, 2), [255, 0, 0, 255]);
+        assert_eq!(page_color(&merged, 3), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn split_uses_ranges_or_fixed_runs() {
+        assert_eq!(
+            split_parts(None, 5, 2).unwrap(),
+            vec![vec![0, 1], vec![2, 3], vec![4]]
+        );
+        assert_eq!(
+            split_parts(Some("1-2,4-"), 5, 1).unwrap(),
+            vec![vec![0, 1], vec![3, 4]]
+        );
+        assert!(split_parts(Some("1,,2"), 5, 1).is_err());
+        assert!(split_parts(None, 101, 1).is_err());
+    }
+
+    #[test]
+    fn rotation_swaps_rendered_dimensions() {
+        let mut doc = load_pdf(&colored_pdf(&[[0, 0, 0], [0, 0, 0]])).unwrap();
+        rotate_pages(&mut doc, &[1], 90).unwrap();
+        let pdf = save_pdf(doc).unwrap();
+        let (_, pages) = render_pdf_pages(pdf, None, 96).unwrap();
+        assert_eq!(pages[0].1.dimensions(), (30, 20));
+        assert_eq!(pages[1].1.dimensions(), (20, 30));
+    }
+
+    #[test]
+    fn document_info_round_trips_unicode() {
+        let mut doc = load_pdf(&colored_pdf(&[[0, 0, 0]])).unwrap();
+        set_document_info(
+            &mut doc,
+            &[
+                (b"Title".as_slice(), "Résumé – 2024".to_string()),
+                (b"Author".as_slice(), " ".to_string()),
+            ],
+        )
+        .unwrap();
+        let doc = load_pdf(&save_pdf(doc).unwrap()).unwrap();
+        let summary = summarize(&doc);
+        assert_eq!(summary.pages, 1);
+        assert_eq!(summary.page_size, Some((22.5, 15.0)));
+        assert_eq!(summary.fields, vec![("Title", "Résumé – 2024".to_string())]);
+
+        let mut doc = load_pdf(&colored_pdf(&[[0, 0, 0]])).unwrap();
+        let too_long = [(b"Title".as_slice(), "x".repeat(501))];
+        assert!(set_document_info(&mut doc, &too_long).is_err());
+    }
+}
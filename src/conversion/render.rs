@@ -0,0 +1,982 @@
+use crate::attachments::{
+    sanitize_filename, validate_attachment_size, validate_image_dimensions, validate_output_size,
+};
+use crate::conversion::image::{OutputFormat, encode_image};
+use crate::conversion::markdown::{code_syntax, syntax_set, theme_set};
+use crate::embeds::error_embed;
+use crate::utils::{decode_text, file_stem};
+use crate::{Context, Error};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use image::{DynamicImage, Rgba, RgbaImage};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme};
+use syntect::parsing::SyntaxReference;
+use syntect::util::LinesWithEndings;
+
+/// DejaVu faces, bundled so snippets render the same on every host.
+const SANS: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const SANS_BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+const MONO: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+const MONO_BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono-Bold.ttf");
+
+const MAX_RENDER_CHARS: usize = 20_000;
+const MAX_RENDER_LINES: usize = 500;
+const DEFAULT_FONT_SIZE: u32 = 16;
+const DEFAULT_WIDTH: u32 = 800;
+/// Row height as a multiple of the font size
+const LINE_HEIGHT: f32 = 1.45;
+const TAB: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum RenderMode {
+    #[name = "Markdown"]
+    Markdown,
+    #[name = "Code"]
+    Code,
+    #[name = "Plain text"]
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum RenderTheme {
+    #[name = "Light"]
+    Light,
+    #[name = "Dark"]
+    Dark,
+    #[name = "Solarized Light"]
+    SolarizedLight,
+    #[name = "Solarized Dark"]
+    SolarizedDark,
+    #[name = "Eighties"]
+    Eighties,
+    #[name = "Mocha"]
+    Mocha,
+}
+
+impl RenderTheme {
+    /// Name of the bundled syntect theme
+    const fn syntect_name(self) -> &'static str {
+        match self {
+            Self::Light => "InspiredGitHub",
+            Self::Dark => "base16-ocean.dark",
+            Self::SolarizedLight => "Solarized (light)",
+            Self::SolarizedDark => "Solarized (dark)",
+            Self::Eighties => "base16-eighties.dark",
+            Self::Mocha => "base16-mocha.dark",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Face {
+    Sans,
+    SansBold,
+    Mono,
+    MonoBold,
+}
+
+impl Face {
+    const fn bold(self) -> Self {
+        match self {
+            Self::Sans | Self::SansBold => Self::SansBold,
+            Self::Mono | Self::MonoBold => Self::MonoBold,
+        }
+    }
+}
+
+struct Fonts {
+    faces: [FontRef<'static>; 4],
+}
+
+impl Fonts {
+    fn load() -> Result<Self, Error> {
+        let load = |data: &'static [u8]| {
+            FontRef::try_from_slice(data)
+                .map_err(|e| Error::from(format!("Failed to load the bundled font: {}", e)))
+        };
+        Ok(Self {
+            faces: [load(SANS)?, load(SANS_BOLD)?, load(MONO)?, load(MONO_BOLD)?],
+        })
+    }
+
+    /// Font that draws `c` in `face`, falling back to DejaVu Sans, which covers more scripts.
+    fn for_char(&self, face: Face, c: char) -> &FontRef<'static> {
+        let font = &self.faces[face as usize];
+        if font.glyph_id(c).0 == 0 {
+            &self.faces[Face::Sans as usize]
+        } else {
+            font
+        }
+    }
+
+    fn advance(&self, face: Face, px: f32, text: &str) -> f32 {
+        text.chars()
+            .map(|c| {
+                let font = self.for_char(face, c).as_scaled(PxScale::from(px));
+                font.h_advance(font.glyph_id(c))
+            })
+            .sum()
+    }
+}
+
+/// Colours derived from the syntect theme
+struct Palette {
+    background: [u8; 3],
+    foreground: [u8; 3],
+    muted: [u8; 3],
+    link: [u8; 3],
+    /// Background of code blocks and the line-number gutter
+    shade: [u8; 3],
+}
+
+fn mix(from: [u8; 3], to: [u8; 3], amount: f32) -> [u8; 3] {
+    std::array::from_fn(|i| {
+        (f32::from(from[i]) + (f32::from(to[i]) - f32::from(from[i])) * amount).round() as u8
+    })
+}
+
+impl Palette {
+    fn from_theme(theme: &Theme) -> Self {
+        let rgb = |color: syntect::highlighting::Color| [color.r, color.g, color.b];
+        let background = theme.settings.background.map_or([255, 255, 255], rgb);
+        let foreground = theme.settings.foreground.map_or([36, 41, 46], rgb);
+        let luma = background.iter().map(|&c| u32::from(c)).sum::<u32>() / 3;
+        let link = if luma > 128 {
+            [3, 102, 214]
+        } else {
+            [108, 182, 255]
+        };
+        Self {
+            background,
+            foreground,
+            muted: mix(foreground, background, 0.45),
+            link,
+            shade: mix(background, foreground, 0.06),
+        }
+    }
+}
+
+struct Span {
+    text: String,
+    color: [u8; 3],
+    face: Face,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Text,
+    Code,
+    Quote,
+    Rule,
+}
+
+/// One logical line before wrapping
+struct Line {
+    spans: Vec<Span>,
+    scale: f32,
+    indent: usize,
+    block: Block,
+    number: Option<usize>,
+    /// Extra space above the line, in rows
+    space_before: f32,
+}
+
+impl Line {
+    fn new(block: Block) -> Self {
+        Self {
+            spans: Vec::new(),
+            scale: 1.0,
+            indent: 0,
+            block,
+            number: None,
+            space_before: 0.0,
+        }
+    }
+}
+
+/// Highlight source code line by line with the theme's colours.
+fn code_lines(
+    code: &str,
+    syntax: &SyntaxReference,
+    theme: &Theme,
+    line_numbers: bool,
+) -> Result<Vec<Line>, Error> {
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut lines = Vec::new();
+    for (index, source) in LinesWithEndings::from(code).enumerate() {
+        let ranges = highlighter
+            .highlight_line(source, syntax_set())
+            .map_err(|e| Error::from(format!("Failed to highlight code: {}", e)))?;
+        let mut line = Line::new(Block::Code);
+        line.number = line_numbers.then_some(index + 1);
+        line.spans = ranges
+            .into_iter()
+            .map(|(style, text)| Span {
+                text: text.trim_end_matches(['\n', '\r']).replace('\t', TAB),
+                color: [style.foreground.r, style.foreground.g, style.foreground.b],
+                face: if style.font_style.contains(FontStyle::BOLD) {
+                    Face::MonoBold
+                } else {
+                    Face::Mono
+                },
+            })
+            .filter(|span| !span.text.is_empty())
+            .collect();
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+fn text_lines(text: &str, palette: &Palette) -> Vec<Line> {
+    text.lines()
+        .map(|source| {
+            let mut line = Line::new(Block::Text);
+            line.spans.push(Span {
+                text: source.replace('\t', TAB),
+                color: palette.foreground,
+                face: Face::Sans,
+            });
+            line
+        })
+        .collect()
+}
+
+const fn heading_scale(level: HeadingLevel) -> f32 {
+    match level {
+        HeadingLevel::H1 => 1.8,
+        HeadingLevel::H2 => 1.5,
+        HeadingLevel::H3 => 1.25,
+        _ => 1.1,
+    }
+}
+
+/// Turns Markdown events into styled lines.
+struct MarkdownLines<'a> {
+    palette: &'a Palette,
+    theme: &'a Theme,
+    lines: Vec<Line>,
+    current: Option<Line>,
+    bold: u32,
+    link: bool,
+    heading: Option<f32>,
+    quote: usize,
+    /// Next number of each open list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    code: Option<(&'static SyntaxReference, String)>,
+    table_cell: usize,
+    pending_space: f32,
+}
+
+impl<'a> MarkdownLines<'a> {
+    fn new(palette: &'a Palette, theme: &'a Theme) -> Self {
+        Self {
+            palette,
+            theme,
+            lines: Vec::new(),
+            current: None,
+            bold: 0,
+            link: false,
+            heading: None,
+            quote: 0,
+            lists: Vec::new(),
+            code: None,
+            table_cell: 0,
+            pending_space: 0.0,
+        }
+    }
+
+    fn indent(&self) -> usize {
+        self.lists.len() + self.quote
+    }
+
+    fn gap(&mut self, rows: f32) {
+        if !self.lines.is_empty() {
+            self.pending_space = self.pending_space.max(rows);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(line) = self.current.take() {
+            self.lines.push(line);
+        }
+    }
+
+    fn push(&mut self, text: &str, face: Face, color: [u8; 3]) {
+        if self.current.is_none() {
+            let block = if self.quote > 0 {
+                Block::Quote
+            } else {
+                Block::Text
+            };
+            let mut line = Line::new(block);
+            line.scale = self.heading.unwrap_or(1.0);
+            line.indent = self.indent();
+            line.space_before = std::mem::take(&mut self.pending_space);
+            self.current = Some(line);
+        }
+        let face = if self.bold > 0 || self.heading.is_some() {
+            face.bold()
+        } else {
+            face
+        };
+        if let Some(line) = &mut self.current {
+            line.spans.push(Span {
+                text: text.replace('\t', TAB),
+                color,
+                face,
+            });
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let color = if self.link {
+            self.palette.link
+        } else {
+            self.palette.foreground
+        };
+        self.push(text, Face::Sans, color);
+    }
+
+    fn event(&mut self, event: Event) -> Result<(), Error> {
+        let muted = self.palette.muted;
+        match event {
+            Event::Text(text) => match &mut self.code {
+                Some((_, code)) => code.push_str(&text),
+                None => self.text(&text),
+            },
+            Event::Code(code) => self.push(&code, Face::Mono, self.palette.foreground),
+            Event::SoftBreak => self.text(" "),
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush();
+                self.gap(0.5);
+                let mut rule = Line::new(Block::Rule);
+                rule.space_before = std::mem::take(&mut self.pending_space);
+                self.lines.push(rule);
+                self.gap(0.5);
+            }
+            Event::TaskListMarker(checked) => {
+                self.push(if checked { "☑ " } else { "☐ " }, Face::Sans, muted)
+            }
+            Event::FootnoteReference(label) => {
+                self.push(&format!("[{}]", label), Face::Sans, muted)
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                for (index, source) in html.lines().enumerate() {
+                    if index > 0 {
+                        self.flush();
+                    }
+                    self.push(source, Face::Mono, muted);
+                }
+            }
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn start(&mut self, tag: Tag) {
+        let muted = self.palette.muted;
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush();
+                self.gap(0.6);
+                self.heading = Some(heading_scale(level));
+            }
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.quote += 1;
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.push(&marker, Face::Sans, muted);
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                self.gap(0.5);
+                let syntax = match kind {
+                    CodeBlockKind::Fenced(info) => code_syntax(&info),
+                    CodeBlockKind::Indented => None,
+                };
+                let syntax = syntax.unwrap_or_else(|| syntax_set().find_syntax_plain_text());
+                self.code = Some((syntax, String::new()));
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.flush();
+                self.push(&format!("[{}] ", label), Face::Sans, muted);
+            }
+            Tag::Table(_) => {
+                self.flush();
+                self.gap(0.5);
+            }
+            Tag::TableHead => self.bold += 1,
+            Tag::TableCell if self.table_cell > 0 => self.push(" │ ", Face::Sans, muted),
+            Tag::Strong => self.bold += 1,
+            Tag::Link { .. } => self.link = true,
+            Tag::Image { .. } => self.push("[image: ", Face::Sans, muted),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) -> Result<(), Error> {
+        match tag {
+            TagEnd::Paragraph => {
+                self.flush();
+                self.gap(0.5);
+            }
+            TagEnd::Heading(_) => {
+                self.flush();
+                self.heading = None;
+                self.gap(0.3);
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quote = self.quote.saturating_sub(1);
+                self.gap(0.5);
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.gap(0.5);
+                }
+            }
+            TagEnd::Item | TagEnd::FootnoteDefinition => self.flush(),
+            TagEnd::CodeBlock => {
+                if let Some((syntax, code)) = self.code.take() {
+                    let indent = self.indent();
+                    let mut block = code_lines(&code, syntax, self.theme, false)?;
+                    if let Some(first) = block.first_mut() {
+                        first.space_before = std::mem::take(&mut self.pending_space);
+                    }
+                    for line in &mut block {
+                        line.indent = indent;
+                    }
+                    self.lines.extend(block);
+                    self.gap(0.5);
+                }
+            }
+            TagEnd::TableHead => {
+                self.bold = self.bold.saturating_sub(1);
+                self.table_cell = 0;
+                self.flush();
+            }
+            TagEnd::TableRow => {
+                self.table_cell = 0;
+                self.flush();
+            }
+            TagEnd::TableCell => self.table_cell += 1,
+            TagEnd::Table => {
+                self.flush();
+                self.gap(0.5);
+            }
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Link => self.link = false,
+            TagEnd::Image => self.push("]", Face::Sans, self.palette.muted),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn markdown_lines(markdown: &str, palette: &Palette, theme: &Theme) -> Result<Vec<Line>, Error> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES;
+    let mut builder = MarkdownLines::new(palette, theme);
+    for event in Parser::new_ext(markdown, options) {
+        builder.event(event)?;
+    }
+    builder.flush();
+    Ok(builder.lines)
+}
+
+struct Placed {
+    x: f32,
+    text: String,
+    color: [u8; 3],
+    face: Face,
+}
+
+/// A wrapped line as drawn on the canvas
+struct Row {
+    y: f32,
+    height: f32,
+    px: f32,
+    left: f32,
+    block: Block,
+    number: Option<usize>,
+    spans: Vec<Placed>,
+}
+
+/// Wrap lines to the canvas width and assign each row its position.
+fn layout(lines: &[Line], fonts: &Fonts, size: f32, width: u32) -> (Vec<Row>, f32, f32) {
+    let padding = size * 1.5;
+    let widest_number = lines.iter().filter_map(|line| line.number).max();
+    let gutter = widest_number.map_or(0.0, |number| {
+        fonts.advance(Face::Mono, size, &number.to_string()) + size
+    });
+    let right_edge = width as f32 - padding;
+
+    let mut rows = Vec::new();
+    let mut y = padding;
+    for line in lines {
+        let px = size * line.scale;
+        let height = px * LINE_HEIGHT;
+        y += line.space_before * size * LINE_HEIGHT;
+        let inset = match line.block {
+            Block::Code => size * 0.5,
+            Block::Quote => size * 0.75,
+            _ => 0.0,
+        };
+        let left = padding + gutter + line.indent as f32 * size * 1.5 + inset;
+        let right = right_edge
+            - if line.block == Block::Code {
+                inset
+            } else {
+                0.0
+            };
+        let new_row = |y: f32, number| Row {
+            y,
+            height,
+            px,
+            left,
+            block: line.block,
+            number,
+            spans: Vec::new(),
+        };
+
+        let mut row = new_row(y, line.number);
+        let mut x = left;
+        for span in &line.spans {
+            for piece in span.text.split_inclusive(' ') {
+                let piece_width = fonts.advance(span.face, px, piece);
+                if x + piece_width > right && x > left {
+                    y += height;
+                    rows.push(std::mem::replace(&mut row, new_row(y, None)));
+                    x = left;
+                }
+                // Words wider than a whole row are broken between characters
+                let chunks: Vec<String> = if piece_width > right - left {
+                    piece.chars().map(String::from).collect()
+                } else {
+                    vec![piece.to_string()]
+                };
+                for chunk in chunks {
+                    let chunk_width = fonts.advance(span.face, px, &chunk);
+                    if x + chunk_width > right && x > left {
+                        y += height;
+                        rows.push(std::mem::replace(&mut row, new_row(y, None)));
+                        x = left;
+                    }
+                    match row.spans.last_mut() {
+                        Some(last) if last.face == span.face && last.color == span.color => {
+                            last.text.push_str(&chunk)
+                        }
+                        _ => row.spans.push(Placed {
+                            x,
+                            text: chunk,
+                            color: span.color,
+                            face: span.face,
+                        }),
+                    }
+                    x += chunk_width;
+                }
+            }
+        }
+        rows.push(row);
+        y += height;
+    }
+    (rows, y + padding, padding + gutter)
+}
+
+fn fill_rect(canvas: &mut RgbaImage, x: f32, y: f32, width: f32, height: f32, color: [u8; 3]) {
+    let (x0, y0) = (x.max(0.0) as u32, y.max(0.0) as u32);
+    let x1 = ((x + width).round() as u32).min(canvas.width());
+    let y1 = ((y + height).round() as u32).min(canvas.height());
+    for py in y0..y1 {
+        for px in x0..x1 {
+            canvas.put_pixel(px, py, Rgba([color[0], color[1], color[2], 255]));
+        }
+    }
+}
+
+fn draw_text(
+    canvas: &mut RgbaImage,
+    fonts: &Fonts,
+    text: &str,
+    face: Face,
+    (x, baseline): (f32, f32),
+    px: f32,
+    color: [u8; 3],
+) {
+    let (width, height) = (i64::from(canvas.width()), i64::from(canvas.height()));
+    let mut caret = x;
+    for c in text.chars() {
+        let font = fonts.for_char(face, c);
+        let scaled = font.as_scaled(PxScale::from(px));
+        let mut glyph = scaled.scaled_glyph(c);
+        glyph.position = point(caret, baseline);
+        caret += scaled.h_advance(glyph.id);
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let x = bounds.min.x as i64 + i64::from(gx);
+            let y = bounds.min.y as i64 + i64::from(gy);
+            if x < 0 || y < 0 || x >= width || y >= height {
+                return;
+            }
+            let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+            let blended = mix(
+                [pixel[0], pixel[1], pixel[2]],
+                color,
+                coverage.clamp(0.0, 1.0),
+            );
+            *pixel = Rgba([blended[0], blended[1], blended[2], 255]);
+        });
+    }
+}
+
+/// Rasterize styled lines onto a canvas of the given width.
+fn draw(lines: &[Line], palette: &Palette, size: f32, width: u32) -> Result<RgbaImage, Error> {
+    let fonts = Fonts::load()?;
+    let (rows, height, content_left) = layout(lines, &fonts, size, width);
+    let height = height.ceil() as u32;
+    validate_image_dimensions(width, height).map_err(Error::from)?;
+
+    let [r, g, b] = palette.background;
+    let mut canvas = RgbaImage::from_pixel(width, height, Rgba([r, g, b, 255]));
+    let padding = size * 1.5;
+    let has_gutter = rows.iter().any(|row| row.number.is_some());
+    if has_gutter {
+        fill_rect(
+            &mut canvas,
+            0.0,
+            0.0,
+            content_left - size * 0.5,
+            height as f32,
+            palette.shade,
+        );
+    }
+
+    for row in &rows {
+        match row.block {
+            Block::Code if !has_gutter => {
+                let x = row.left - size * 0.5;
+                let block_width = width as f32 - padding - x;
+                fill_rect(
+                    &mut canvas,
+                    x,
+                    row.y,
+                    block_width,
+                    row.height,
+                    palette.shade,
+                );
+            }
+            Block::Quote => {
+                let x = row.left - size * 0.75;
+                fill_rect(&mut canvas, x, row.y, 3.0, row.height, palette.muted);
+            }
+            Block::Rule => {
+                let y = row.y + row.height / 2.0;
+                let rule_width = width as f32 - padding - row.left;
+                fill_rect(&mut canvas, row.left, y, rule_width, 1.0, palette.muted);
+            }
+            _ => {}
+        }
+
+        let scaled = fonts.faces[Face::Sans as usize].as_scaled(PxScale::from(row.px));
+        let baseline =
+            row.y + (row.height - (scaled.ascent() - scaled.descent())) / 2.0 + scaled.ascent();
+        if let Some(number) = row.number {
+            let label = number.to_string();
+            let x = content_left - size * 0.5 - fonts.advance(Face::Mono, row.px, &label);
+            let position = (x - size * 0.5, baseline);
+            draw_text(
+                &mut canvas,
+                &fonts,
+                &label,
+                Face::Mono,
+                position,
+                row.px,
+                palette.muted,
+            );
+        }
+        for span in &row.spans {
+            let position = (span.x, baseline);
+            draw_text(
+                &mut canvas,
+                &fonts,
+                &span.text,
+                span.face,
+                position,
+                row.px,
+                span.color,
+            );
+        }
+    }
+    Ok(canvas)
+}
+
+/// Guess how to read the input from its filename; pasted text is Markdown.
+fn detect_mode(filename: Option<&str>) -> RenderMode {
+    let extension = filename
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        None if filename.is_none() => RenderMode::Markdown,
+        Some("md" | "markdown" | "mkd") => RenderMode::Markdown,
+        Some("txt" | "text" | "log") => RenderMode::Text,
+        Some(extension) if syntax_set().find_syntax_by_extension(extension).is_some() => {
+            RenderMode::Code
+        }
+        _ => RenderMode::Text,
+    }
+}
+
+/// Pick the syntax for code from the language option, the extension or a shebang line.
+fn detect_syntax(
+    source: &str,
+    filename: Option<&str>,
+    language: Option<&str>,
+) -> Result<&'static SyntaxReference, String> {
+    let syntaxes = syntax_set();
+    if let Some(language) = language {
+        return code_syntax(language.trim()).ok_or_else(|| {
+            format!(
+                "Unknown language `{}`. Try a name or extension like `rust` or `py`.",
+                language.trim()
+            )
+        });
+    }
+    let by_extension = filename
+        .and_then(|name| name.rsplit_once('.'))
+        .and_then(|(_, extension)| syntaxes.find_syntax_by_extension(extension));
+    Ok(by_extension
+        .or_else(|| syntaxes.find_syntax_by_first_line(source))
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text()))
+}
+
+/// Settings for `render_source`
+pub struct RenderOptions<'a> {
+    pub mode: Option<RenderMode>,
+    pub filename: Option<&'a str>,
+    pub language: Option<&'a str>,
+    pub theme: RenderTheme,
+    pub font_size: u32,
+    pub width: u32,
+    pub line_numbers: Option<bool>,
+}
+
+/// Render Markdown, code or plain text to an image.
+pub fn render_source(source: &str, options: &RenderOptions) -> Result<RgbaImage, Error> {
+    if source.chars().count() > MAX_RENDER_CHARS || source.lines().count() > MAX_RENDER_LINES {
+        return Err(Error::from(format!(
+            "The input is too long to render (max {} characters and {} lines).",
+            MAX_RENDER_CHARS, MAX_RENDER_LINES
+        )));
+    }
+    let theme = theme_set()
+        .themes
+        .get(options.theme.syntect_name())
+        .ok_or_else(|| Error::from("The selected theme is not available."))?;
+    let palette = Palette::from_theme(theme);
+
+    // A language only makes sense for code, so choosing one implies code mode
+    let mode = options.mode.unwrap_or(match options.language {
+        Some(_) => RenderMode::Code,
+        None => detect_mode(options.filename),
+    });
+    let lines = match mode {
+        RenderMode::Markdown => markdown_lines(source, &palette, theme)?,
+        RenderMode::Code => {
+            let syntax =
+                detect_syntax(source, options.filename, options.language).map_err(Error::from)?;
+            code_lines(source, syntax, theme, options.line_numbers.unwrap_or(true))?
+        }
+        RenderMode::Text => text_lines(source, &palette),
+    };
+    let blank = |line: &Line| line.spans.iter().all(|span| span.text.trim().is_empty());
+    if lines.iter().all(blank) {
+        return Err(Error::from("There is nothing to render."));
+    }
+    draw(&lines, &palette, options.font_size as f32, options.width)
+}
+
+/// Render Markdown, code or text to a PNG image
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn render(
+    ctx: Context<'_>,
+    #[description = "Markdown, code or text file to render"] file: Option<Attachment>,
+    #[description = "Text to render instead of a file (read as Markdown)"] text: Option<String>,
+    #[description = "How to read the input (default: from the file extension)"] mode: Option<
+        RenderMode,
+    >,
+    #[description = "Language for highlighting, e.g. rust or py (default: detected)"]
+    language: Option<String>,
+    #[description = "Colour theme (default: Light)"] theme: Option<RenderTheme>,
+    #[description = "Font size in pixels (default: 16)"]
+    #[min = 10]
+    #[max = 32]
+    font_size: Option<u32>,
+    #[description = "Image width in pixels (default: 800)"]
+    #[min = 300]
+    #[max = 2000]
+    width: Option<u32>,
+    #[description = "Show line numbers for code (default: on)"] line_numbers: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let source = match (&file, text) {
+        (Some(file), None) => {
+            if let Err(message) = validate_attachment_size(file) {
+                let embed = error_embed("❌ Render Failed", message);
+                ctx.send(poise::CreateReply::default().embed(embed)).await?;
+                return Ok(());
+            }
+            let data = file
+                .download()
+                .await
+                .map_err(|e| Error::from(format!("Failed to download file: {}", e)))?;
+            // Code saved on Windows is often UTF-16 or in a legacy code page
+            tokio::task::spawn_blocking(move || {
+                decode_text(&data)
+                    .map(|(text, _)| text)
+                    .unwrap_or_else(|| String::from_utf8_lossy(&data).into_owned())
+            })
+            .await
+            .map_err(|e| format!("Decoding task failed: {}", e))
+        }
+        (None, Some(text)) => Ok(text),
+        _ => Err("Provide either a `file` or some `text` to render.".to_string()),
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(message) => {
+            let embed = error_embed("❌ Render Failed", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let filename = file.as_ref().map(|file| file.filename.clone());
+    let rendered = tokio::task::spawn_blocking(move || {
+        let options = RenderOptions {
+            mode,
+            filename: filename.as_deref(),
+            language: language.as_deref(),
+            theme: theme.unwrap_or(RenderTheme::Light),
+            font_size: font_size.unwrap_or(DEFAULT_FONT_SIZE),
+            width: width.unwrap_or(DEFAULT_WIDTH),
+            line_numbers,
+        };
+        let image = render_source(&source, &options)?;
+        let size = (image.width(), image.height());
+        encode_image(DynamicImage::ImageRgba8(image), OutputFormat::Png).map(|bytes| (bytes, size))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Render task failed: {}", e)))
+    .and_then(|r| r);
+    let (output_bytes, (image_width, image_height)) = match rendered {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            let embed = error_embed("❌ Render Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(output_bytes.len(), "Rendered image").map_err(Error::from)?;
+
+    let output_filename = match &file {
+        Some(file) => format!(
+            "{}_render.png",
+            sanitize_filename(&file_stem(&file.filename))
+        ),
+        None => "render.png".to_string(),
+    };
+    let embed = CreateEmbed::new()
+        .title("✅ Rendered")
+        .description(format!("**Size:** {}×{}", image_width, image_height))
+        .image(format!("attachment://{}", output_filename))
+        .color(0x27ae60);
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(output_bytes, output_filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RenderMode, RenderOptions, RenderTheme, detect_mode, detect_syntax, render_source,
+    };
+
+    fn options(mode: Option<RenderMode>) -> RenderOptions<'static> {
+        RenderOptions {
+            mode,
+            filename: None,
+            language: None,
+            theme: RenderTheme::Dark,
+            font_size: 16,
+            width: 400,
+            line_numbers: None,
+        }
+    }
+
+    #[test]
+    fn detects_mode_and_language() {
+        assert_eq!(detect_mode(None), RenderMode::Markdown);
+        assert_eq!(detect_mode(Some("README.md")), RenderMode::Markdown);
+        assert_eq!(detect_mode(Some("main.rs")), RenderMode::Code);
+        assert_eq!(detect_mode(Some("notes")), RenderMode::Text);
+
+        let syntax = detect_syntax("#!/usr/bin/env python\n", Some("script"), None).unwrap();
+        assert_eq!(syntax.name, "Python");
+        assert!(detect_syntax("", None, Some("no-such-language")).is_err());
+    }
+
+    #[test]
+    fn long_lines_wrap_to_the_requested_width() {
+        let short = render_source("one line", &options(Some(RenderMode::Text))).unwrap();
+        let long = render_source(&"word ".repeat(80), &options(Some(RenderMode::Text))).unwrap();
+        assert_eq!(long.width(), 400);
+        assert!(long.height() > short.height() * 3);
+    }
+
+    #[test]
+    fn renders_markdown_and_code_on_the_theme_background() {
+        let markdown = "# Title\n\n- [x] item\n\n> quote\n\n```rust\nfn main() {}\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |";
+        let image = render_source(markdown, &options(None)).unwrap();
+        let corner = image.get_pixel(0, 0);
+        assert!(corner[0] < 100, "dark theme background expected");
+        assert!(image.pixels().any(|pixel| pixel[0] > 150));
+
+        let mut code = options(Some(RenderMode::Code));
+        code.language = Some("rs");
+        assert!(render_source("fn main() {}\n", &code).is_ok());
+        assert!(render_source("   \n", &options(Some(RenderMode::Text))).is_err());
+    }
+}
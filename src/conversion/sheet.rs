@@ -0,0 +1,951 @@
+use crate::attachments::{
+    download_attachment, sanitize_filename, validate_output_size, validate_sheet_dimensions,
+};
+use crate::conversion::data::csv_field;
+use crate::embeds::{EMBED_SUCCESS_COLOR, INLINE_PREVIEW_LIMIT, error_embed};
+use crate::utils::file_stem;
+use crate::{Context, Error};
+use calamine::{Cell, Data, DataRef, Range, Reader, Sheets, XlsxError, open_workbook_auto_from_rs};
+use poise::ChoiceParameter;
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed};
+use quick_xml::Reader as XmlReader;
+use quick_xml::events::{BytesStart, Event};
+use rust_xlsxwriter::{Format, Workbook};
+use serde_json::{Map, Value};
+use std::io::{BufReader, Cursor};
+use zip::ZipArchive;
+
+/// Longest worksheet name Excel accepts
+const MAX_SHEET_NAME_LEN: usize = 31;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum SheetFormat {
+    #[name = "CSV"]
+    Csv,
+    #[name = "TSV"]
+    Tsv,
+    #[name = "JSON"]
+    Json,
+    #[name = "Markdown table"]
+    Markdown,
+    #[name = "HTML table"]
+    Html,
+    #[name = "Excel (xlsx)"]
+    Xlsx,
+}
+
+impl SheetFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+            Self::Json => "json",
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// One worksheet as rows of cells. Cells are strings, numbers, booleans or null.
+#[derive(Debug)]
+pub struct Sheet {
+    pub name: String,
+    pub rows: Vec<Vec<Value>>,
+    /// Every sheet in the workbook, to list alongside the one converted
+    pub sheet_names: Vec<String>,
+}
+
+impl Sheet {
+    fn column_count(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+}
+
+fn is_delimited_text(filename: &str) -> bool {
+    let name = filename.to_ascii_lowercase();
+    [".csv", ".tsv", ".tab", ".txt"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+}
+
+/// Use tabs when the first line has more of them than commas.
+fn detect_delimiter(filename: &str, text: &str) -> u8 {
+    let name = filename.to_ascii_lowercase();
+    if name.ends_with(".tsv") || name.ends_with(".tab") {
+        return b'\t';
+    }
+    let first_line = text.lines().next().unwrap_or_default();
+    if first_line.matches('\t').count() > first_line.matches(',').count() {
+        b'\t'
+    } else {
+        b','
+    }
+}
+
+fn read_delimited(data: &[u8], filename: &str) -> Result<Sheet, String> {
+    let text = std::str::from_utf8(data)
+        .map_err(|_| "CSV and TSV files must be UTF-8 text.".to_string())?
+        .trim_start_matches('\u{feff}');
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(detect_delimiter(filename, text))
+        .from_reader(text.as_bytes());
+
+    let mut rows = Vec::new();
+    let mut columns = 0;
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        columns = columns.max(record.len());
+        validate_sheet_dimensions(rows.len() + 1, columns)?;
+        rows.push(record.iter().map(csv_field).collect());
+    }
+    let name = file_stem(filename);
+    Ok(Sheet {
+        sheet_names: vec![name.clone()],
+        name,
+        rows,
+    })
+}
+
+/// Dates as ISO 8601 and durations as `h:mm:ss`, since spreadsheets store both as numbers.
+fn excel_datetime(value: &calamine::ExcelDateTime) -> String {
+    if value.is_duration() {
+        let seconds = (value.as_f64() * 86_400.0).round() as i64;
+        let sign = if seconds < 0 { "-" } else { "" };
+        let seconds = seconds.abs();
+        return format!(
+            "{}{}:{:02}:{:02}",
+            sign,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        );
+    }
+    let (year, month, day, hour, minute, second, _) = value.to_ymd_hms_milli();
+    let time = format!("{:02}:{:02}:{:02}", hour, minute, second);
+    if value.as_f64().trunc() == 0.0 {
+        time
+    } else if (hour, minute, second) == (0, 0, 0) {
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    } else {
+        format!("{:04}-{:02}-{:02}T{}", year, month, day, time)
+    }
+}
+
+fn cell_value(cell: &Data) -> Value {
+    match cell {
+        Data::Empty => Value::Null,
+        Data::Int(number) => Value::from(*number),
+        Data::Float(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+            Value::from(*number as i64)
+        }
+        Data::Float(number) => serde_json::Number::from_f64(*number)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        Data::Bool(value) => Value::Bool(*value),
+        Data::String(text) => Value::String(text.clone()),
+        Data::DateTime(datetime) => Value::String(excel_datetime(datetime)),
+        Data::DateTimeIso(text) | Data::DurationIso(text) => Value::String(text.clone()),
+        Data::Error(error) => Value::String(error.to_string()),
+    }
+}
+
+/// Pick a sheet by name (case-insensitive) or by its 1-based position.
+fn select_sheet<'a>(names: &'a [String], wanted: Option<&str>) -> Result<&'a String, String> {
+    let Some(wanted) = wanted.map(str::trim) else {
+        return names
+            .first()
+            .ok_or_else(|| "The workbook has no sheets.".to_string());
+    };
+    wanted
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| names.get(number.checked_sub(1)?))
+        .or_else(|| names.iter().find(|name| name.eq_ignore_ascii_case(wanted)))
+        .ok_or_else(|| {
+            format!(
+                "No sheet named `{}`. Available sheets: {}",
+                wanted,
+                names
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+/// Bounding box of the populated cells seen so far, checked against the sheet
+/// limits as each cell arrives.
+#[derive(Default)]
+struct CellBounds {
+    first: Option<(u32, u32)>,
+    last: (u32, u32),
+}
+
+impl CellBounds {
+    fn add(&mut self, row: u32, column: u32) -> Result<(), String> {
+        let first = self.first.unwrap_or((row, column));
+        let first = (first.0.min(row), first.1.min(column));
+        self.first = Some(first);
+        self.last = (self.last.0.max(row), self.last.1.max(column));
+        validate_sheet_dimensions(
+            (self.last.0 - first.0) as usize + 1,
+            (self.last.1 - first.1) as usize + 1,
+        )
+    }
+}
+
+fn push_cell(
+    cells: &mut Vec<Cell<Data>>,
+    bounds: &mut CellBounds,
+    cell: Cell<DataRef<'_>>,
+) -> Result<(), String> {
+    if matches!(cell.get_value(), DataRef::Empty) {
+        return Ok(());
+    }
+    let (row, column) = cell.get_position();
+    bounds.add(row, column)?;
+    cells.push(Cell::new(
+        (row, column),
+        Data::from(cell.get_value().clone()),
+    ));
+    Ok(())
+}
+
+/// Little-endian `u16` and `u32` readers for the binary formats below.
+fn read_u16_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 2)?;
+    Some(u32::from(u16::from_le_bytes([bytes[0], bytes[1]])))
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// The `Workbook` (or BIFF5 `Book`) stream of a legacy `.xls` file, read out
+/// of its compound file container.
+fn xls_workbook_stream(data: &[u8]) -> Option<Vec<u8>> {
+    const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+    if !data.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+        return None;
+    }
+    let sector_shift = read_u16_le(data, 0x1E)?;
+    let mini_sector_shift = read_u16_le(data, 0x20)?;
+    if !(7..=16).contains(&sector_shift) || !(1..=sector_shift).contains(&mini_sector_shift) {
+        return None;
+    }
+    let sector_size = 1usize << sector_shift;
+    let mini_sector_size = 1usize << mini_sector_shift;
+    // No chain can be longer than the number of sectors in the file.
+    let sector_count = data.len() / sector_size;
+    let sector = |id: u32| {
+        let start = (id as usize + 1).checked_mul(sector_size)?;
+        data.get(start..data.len().min(start + sector_size))
+            .filter(|bytes| !bytes.is_empty())
+    };
+    let chain = |mut id: u32, table: &[u32], limit: usize| {
+        let mut ids = Vec::new();
+        while id != END_OF_CHAIN && ids.len() < limit {
+            ids.push(id);
+            match table.get(id as usize) {
+                Some(&next) => id = next,
+                None => break,
+            }
+        }
+        ids
+    };
+    let as_u32s = |bytes: &[u8]| -> Vec<u32> {
+        bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    };
+
+    let mut fat_sectors: Vec<u32> = (0..109)
+        .filter_map(|index| read_u32_le(data, 0x4C + 4 * index))
+        .collect();
+    let mut difat_sector = read_u32_le(data, 0x44)?;
+    for _ in 0..read_u32_le(data, 0x48)?.min(sector_count as u32) {
+        let Some(bytes) = sector(difat_sector) else {
+            break;
+        };
+        let mut entries = as_u32s(bytes);
+        difat_sector = entries.pop()?;
+        fat_sectors.extend(entries);
+    }
+    fat_sectors.retain(|&id| (id as usize) < sector_count);
+    fat_sectors.truncate(sector_count);
+    let fat: Vec<u32> = fat_sectors
+        .into_iter()
+        .filter_map(sector)
+        .flat_map(as_u32s)
+        .collect();
+    let read_chain = |start: u32| -> Vec<u8> {
+        chain(start, &fat, sector_count)
+            .into_iter()
+            .filter_map(sector)
+            .flatten()
+            .copied()
+            .collect()
+    };
+
+    let directory = read_chain(read_u32_le(data, 0x30)?);
+    let root = directory.get(..128)?;
+    let entry = directory.chunks_exact(128).find(|entry| {
+        let name_len = (read_u16_le(entry, 0x40).unwrap_or(0) as usize).clamp(2, 64) - 2;
+        let name: Vec<u16> = entry[..name_len]
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        entry[0x42] == 2
+            && matches!(
+                String::from_utf16_lossy(&name).as_str(),
+                "Workbook" | "Book"
+            )
+    })?;
+    let start = read_u32_le(entry, 0x74)?;
+    let size = read_u32_le(entry, 0x78)? as usize;
+
+    let mut stream = if size < read_u32_le(data, 0x38)? as usize {
+        let mini_stream = read_chain(read_u32_le(root, 0x74)?);
+        let mini_fat = as_u32s(&read_chain(read_u32_le(data, 0x3C)?));
+        chain(start, &mini_fat, mini_stream.len() / mini_sector_size)
+            .into_iter()
+            .filter_map(|id| {
+                let offset = (id as usize).checked_mul(mini_sector_size)?;
+                mini_stream.get(offset..offset + mini_sector_size)
+            })
+            .flatten()
+            .copied()
+            .collect()
+    } else {
+        read_chain(start)
+    };
+    stream.truncate(size);
+    Some(stream)
+}
+
+/// Check every sheet of a BIFF workbook stream the way calamine will load it:
+/// the declared dimensions are reserved up front, then every cell is placed.
+fn check_biff_dimensions(stream: &[u8]) -> Result<(), String> {
+    let corrupted = || "The spreadsheet is corrupted.".to_string();
+    let mut bounds = CellBounds::default();
+    let mut offset = 0;
+    while let (Some(kind), Some(len)) =
+        (read_u16_le(stream, offset), read_u16_le(stream, offset + 2))
+    {
+        let Some(record) = stream.get(offset + 4..offset + 4 + len as usize) else {
+            break;
+        };
+        offset += 4 + len as usize;
+        let position = || Some((read_u16_le(record, 0)?, read_u16_le(record, 2)?));
+        match kind {
+            // BOF starts the next sheet
+            0x0809 => bounds = CellBounds::default(),
+            // FILEPASS: the rest is encrypted, which calamine refuses anyway
+            0x002F => break,
+            // DIMENSIONS
+            0x0200 => {
+                let (first_row, last_row, first_column, last_column) = match record.len() {
+                    10 => (
+                        read_u16_le(record, 0),
+                        read_u16_le(record, 2),
+                        read_u16_le(record, 4),
+                        read_u16_le(record, 6),
+                    ),
+                    14 => (
+                        read_u32_le(record, 0),
+                        read_u32_le(record, 4),
+                        read_u16_le(record, 8),
+                        read_u16_le(record, 10),
+                    ),
+                    _ => return Err(corrupted()),
+                };
+                let (first_row, last_row) = (first_row.unwrap_or(0), last_row.unwrap_or(0));
+                let (mut first_column, last_column) =
+                    (first_column.unwrap_or(0), last_column.unwrap_or(0));
+                if first_column > 0xFF || last_column < first_column {
+                    first_column = 0;
+                }
+                if last_row >= 1 && last_column >= 1 {
+                    if last_row <= first_row || last_column <= first_column {
+                        return Err(corrupted());
+                    }
+                    validate_sheet_dimensions(
+                        (last_row - first_row) as usize,
+                        (last_column - first_column) as usize,
+                    )?;
+                }
+            }
+            // Formula, Number, Label, BoolErr, RK and LabelSst
+            0x0006 | 0x0203 | 0x0204 | 0x0205 | 0x027E | 0x00FD => {
+                if let Some((row, column)) = position() {
+                    bounds.add(row, column)?;
+                }
+            }
+            // MulRk: a run of cells in one row
+            0x00BD => {
+                if let Some((row, column)) = position()
+                    && record.len() >= 12
+                {
+                    bounds.add(row, column)?;
+                    bounds.add(row, column + (record.len() as u32 - 6) / 6 - 1)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check the sheets of an ODS file, whose repeated rows and cells calamine
+/// expands while opening it.
+fn check_ods_dimensions(data: &[u8]) -> Result<(), String> {
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(data)) else {
+        return Ok(());
+    };
+    let Ok(content) = archive.by_name("content.xml") else {
+        return Ok(());
+    };
+    let repeats = |element: &BytesStart, name: &[u8]| -> u64 {
+        element
+            .try_get_attribute(name)
+            .ok()
+            .flatten()
+            .and_then(|value| std::str::from_utf8(&value.value).ok()?.parse().ok())
+            .unwrap_or(1)
+    };
+
+    let mut reader = XmlReader::from_reader(BufReader::new(content));
+    let mut buf = Vec::new();
+    // Empty rows only take up space once a later row has a value.
+    let (mut rows, mut pending_rows, mut columns) = (0u64, 0u64, 0u64);
+    let (mut row_repeats, mut row_width, mut pending_cells, mut row_has_value) =
+        (1u64, 0u64, 0u64, false);
+    loop {
+        let (element, is_empty) = match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => (element, false),
+            Ok(Event::Empty(element)) => (element, true),
+            Ok(Event::End(element)) => {
+                if element.name().as_ref() == b"table:table-row" {
+                    if row_has_value {
+                        rows += pending_rows + row_repeats;
+                        pending_rows = 0;
+                        columns = columns.max(row_width);
+                        validate_sheet_dimensions(
+                            rows.min(usize::MAX as u64) as usize,
+                            columns.min(usize::MAX as u64) as usize,
+                        )?;
+                    } else if rows > 0 {
+                        pending_rows += row_repeats;
+                    }
+                }
+                buf.clear();
+                continue;
+            }
+            Ok(Event::Eof) => return Ok(()),
+            // Calamine reports the broken XML itself
+            Err(_) => return Ok(()),
+            Ok(_) => {
+                buf.clear();
+                continue;
+            }
+        };
+        match element.name().as_ref() {
+            b"table:table" => (rows, pending_rows, columns) = (0, 0, 0),
+            b"table:table-row" if is_empty && rows > 0 => {
+                pending_rows += repeats(&element, b"table:number-rows-repeated");
+            }
+            b"table:table-row" if is_empty => {}
+            b"table:table-row" => {
+                row_repeats = repeats(&element, b"table:number-rows-repeated");
+                (row_width, pending_cells, row_has_value) = (0, 0, false);
+            }
+            b"table:table-cell" | b"table:covered-table-cell" => {
+                let cells = repeats(&element, b"table:number-columns-repeated");
+                let has_value = !is_empty
+                    || element
+                        .try_get_attribute(b"office:value-type")
+                        .is_ok_and(|value| value.is_some())
+                    || element
+                        .try_get_attribute(b"table:formula")
+                        .is_ok_and(|value| value.is_some());
+                if has_value {
+                    row_width += pending_cells + cells;
+                    pending_cells = 0;
+                    row_has_value = true;
+                } else {
+                    pending_cells += cells;
+                }
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+fn read_workbook(data: &[u8], sheet: Option<&str>) -> Result<Sheet, String> {
+    // XLS and ODS sheets are all loaded while the workbook is opened.
+    if let Some(stream) = xls_workbook_stream(data) {
+        check_biff_dimensions(&stream)?;
+    }
+    check_ods_dimensions(data)?;
+
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(data))
+        .map_err(|_| "The file is not an XLSX, XLS, XLSB or ODS spreadsheet.".to_string())?;
+    let sheet_names = workbook.sheet_names();
+    let name = select_sheet(&sheet_names, sheet)?.clone();
+    let read_error = |e: &dyn std::fmt::Display| format!("Failed to read sheet `{}`: {}", name, e);
+
+    // XLSX and XLSB cells are read one at a time so the bounding box is
+    // checked before a dense range is allocated for it.
+    let mut bounds = CellBounds::default();
+    let mut cells = Vec::new();
+    let range = match &mut workbook {
+        Sheets::Xlsx(xlsx) => match xlsx.worksheet_cells_reader(&name) {
+            Ok(mut reader) => {
+                while let Some(cell) = reader.next_cell().map_err(|e| read_error(&e))? {
+                    push_cell(&mut cells, &mut bounds, cell)?;
+                }
+                Range::from_sparse(cells)
+            }
+            Err(XlsxError::NotAWorksheet(_)) => Range::default(),
+            Err(e) => return Err(read_error(&e)),
+        },
+        Sheets::Xlsb(xlsb) => {
+            let mut reader = xlsb
+                .worksheet_cells_reader(&name)
+                .map_err(|e| read_error(&e))?;
+            while let Some(cell) = reader.next_cell().map_err(|e| read_error(&e))? {
+                push_cell(&mut cells, &mut bounds, cell)?;
+            }
+            Range::from_sparse(cells)
+        }
+        workbook => workbook
+            .worksheet_range(&name)
+            .map_err(|e| read_error(&e))?,
+    };
+    let (height, width) = range.get_size();
+    validate_sheet_dimensions(height, width)?;
+
+    let rows = range
+        .rows()
+        .map(|row| row.iter().map(cell_value).collect())
+        .collect();
+    Ok(Sheet {
+        name,
+        rows,
+        sheet_names,
+    })
+}
+
+/// Read a spreadsheet or a CSV/TSV file into one sheet.
+pub fn read_sheet(data: &[u8], filename: &str, sheet: Option<&str>) -> Result<Sheet, String> {
+    if is_delimited_text(filename) {
+        read_delimited(data, filename)
+    } else {
+        read_workbook(data, sheet)
+    }
+}
+
+fn cell_text(cell: &Value) -> String {
+    match cell {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Column names from the header row, made unique and never empty.
+fn column_names(header: &[Value], columns: usize) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(columns);
+    for index in 0..columns {
+        let name = header.get(index).map(cell_text).unwrap_or_default();
+        let name = name.trim();
+        let mut name = if name.is_empty() {
+            format!("Column {}", index + 1)
+        } else {
+            name.to_string()
+        };
+        if names.contains(&name) {
+            name = format!("{} ({})", name, index + 1);
+        }
+        names.push(name);
+    }
+    names
+}
+
+fn write_delimited(sheet: &Sheet, delimiter: u8) -> Result<Vec<u8>, String> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(Vec::new());
+    for row in &sheet.rows {
+        writer
+            .write_record(row.iter().map(cell_text))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+fn write_json(sheet: &Sheet, header: bool) -> Result<Vec<u8>, String> {
+    let value = if header && !sheet.rows.is_empty() {
+        let names = column_names(&sheet.rows[0], sheet.column_count());
+        Value::Array(
+            sheet.rows[1..]
+                .iter()
+                .map(|row| {
+                    let record: Map<String, Value> = names
+                        .iter()
+                        .enumerate()
+                        .map(|(index, name)| {
+                            (name.clone(), row.get(index).cloned().unwrap_or(Value::Null))
+                        })
+                        .collect();
+                    Value::Object(record)
+                })
+                .collect(),
+        )
+    } else {
+        Value::Array(sheet.rows.iter().cloned().map(Value::Array).collect())
+    };
+    serde_json::to_vec_pretty(&value).map_err(|e| e.to_string())
+}
+
+fn markdown_cell(cell: &Value) -> String {
+    cell_text(cell)
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn write_markdown(sheet: &Sheet, header: bool) -> Vec<u8> {
+    let columns = sheet.column_count().max(1);
+    let (head, body) = match sheet.rows.split_first() {
+        Some((first, rest)) if header => (column_names(first, columns), rest),
+        _ => (column_names(&[], columns), sheet.rows.as_slice()),
+    };
+    // Right-align columns that hold only numbers
+    let numeric: Vec<bool> = (0..columns)
+        .map(|index| {
+            let mut cells = body.iter().filter_map(|row| row.get(index));
+            cells.clone().any(Value::is_number)
+                && cells.all(|cell| cell.is_number() || cell.is_null())
+        })
+        .collect();
+
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut output = line(
+        head.iter()
+            .map(|name| markdown_cell(&Value::String(name.clone())))
+            .collect(),
+    );
+    output.push_str(&line(
+        numeric
+            .iter()
+            .map(|&numeric| if numeric { "---:" } else { "---" }.to_string())
+            .collect(),
+    ));
+    for row in body {
+        output.push_str(&line(
+            (0..columns)
+                .map(|index| row.get(index).map(markdown_cell).unwrap_or_default())
+                .collect(),
+        ));
+    }
+    output.into_bytes()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_html(sheet: &Sheet, header: bool) -> Vec<u8> {
+    let columns = sheet.column_count();
+    let row_html = |row: &[Value], tag: &str| {
+        let cells: String = (0..columns)
+            .map(|index| {
+                let text = row.get(index).map(cell_text).unwrap_or_default();
+                format!("<{tag}>{}</{tag}>", escape_html(&text))
+            })
+            .collect();
+        format!("    <tr>{}</tr>\n", cells)
+    };
+
+    let mut output = String::from("<table>\n");
+    let body = match sheet.rows.split_first() {
+        Some((first, rest)) if header => {
+            output.push_str("  <thead>\n");
+            output.push_str(&row_html(first, "th"));
+            output.push_str("  </thead>\n");
+            rest
+        }
+        _ => sheet.rows.as_slice(),
+    };
+    output.push_str("  <tbody>\n");
+    for row in body {
+        output.push_str(&row_html(row, "td"));
+    }
+    output.push_str("  </tbody>\n</table>\n");
+    output.into_bytes()
+}
+
+/// Make a name Excel accepts for a worksheet.
+fn worksheet_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(MAX_SHEET_NAME_LEN)
+        .collect();
+    let name = name.trim_matches('\'').trim();
+    if name.is_empty() {
+        "Sheet1".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn write_xlsx(sheet: &Sheet, header: bool) -> Result<Vec<u8>, String> {
+    let xlsx_error = |e: rust_xlsxwriter::XlsxError| format!("Failed to write the workbook: {}", e);
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet
+        .set_name(worksheet_name(&sheet.name))
+        .map_err(xlsx_error)?;
+    let bold = Format::new().set_bold();
+
+    for (row_index, row) in sheet.rows.iter().enumerate() {
+        let row_number = row_index as u32;
+        for (column_index, cell) in row.iter().enumerate() {
+            let column = column_index as u16;
+            match cell {
+                Value::Null => {}
+                Value::Number(number) => {
+                    worksheet
+                        .write_number(row_number, column, number.as_f64().unwrap_or_default())
+                        .map_err(xlsx_error)?;
+                }
+                Value::Bool(value) => {
+                    worksheet
+                        .write_boolean(row_number, column, *value)
+                        .map_err(xlsx_error)?;
+                }
+                other if header && row_index == 0 => {
+                    worksheet
+                        .write_string_with_format(row_number, column, cell_text(other), &bold)
+                        .map_err(xlsx_error)?;
+                }
+                other => {
+                    worksheet
+                        .write_string(row_number, column, cell_text(other))
+                        .map_err(xlsx_error)?;
+                }
+            }
+        }
+    }
+    if header && !sheet.rows.is_empty() {
+        worksheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+    }
+    worksheet.autofit();
+    workbook.save_to_buffer().map_err(xlsx_error)
+}
+
+/// Write a sheet in `format`. With `header`, the first row names the columns.
+pub fn write_sheet(sheet: &Sheet, format: SheetFormat, header: bool) -> Result<Vec<u8>, String> {
+    match format {
+        SheetFormat::Csv => write_delimited(sheet, b','),
+        SheetFormat::Tsv => write_delimited(sheet, b'\t'),
+        SheetFormat::Json => write_json(sheet, header),
+        SheetFormat::Markdown => Ok(write_markdown(sheet, header)),
+        SheetFormat::Html => Ok(write_html(sheet, header)),
+        SheetFormat::Xlsx => write_xlsx(sheet, header),
+    }
+}
+
+/// Convert spreadsheets between XLSX, ODS, CSV, TSV, JSON, Markdown and HTML
+#[poise::command(slash_command)]
+pub async fn convert_sheet(
+    ctx: Context<'_>,
+    #[description = "Spreadsheet (XLSX, XLS, ODS) or CSV/TSV file"] file: Attachment,
+    #[description = "Format to convert to"] output_format: SheetFormat,
+    #[description = "Sheet name or number to convert (default: the first)"] sheet: Option<String>,
+    #[description = "Treat the first row as column names (default: true)"] header: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_attachment(&file).await {
+        Ok(data) => data,
+        Err(e) => {
+            let embed = error_embed("❌ Conversion Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let filename = file.filename.clone();
+    let header = header.unwrap_or(true);
+
+    let result = tokio::task::spawn_blocking(move || {
+        let sheet = read_sheet(&data, &filename, sheet.as_deref())?;
+        let output = write_sheet(&sheet, output_format, header)?;
+        validate_output_size(output.len(), "Converted sheet")?;
+        Ok::<_, String>((sheet, output))
+    })
+    .await
+    .map_err(|e| format!("Conversion task failed: {}", e))
+    .and_then(|result| result);
+    let (sheet, output) = match result {
+        Ok(converted) => converted,
+        Err(message) => {
+            let embed = error_embed("❌ Conversion Failed", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let mut description = format!(
+        "**Sheet:** `{}`\n**Size:** {} rows × {} columns\n**Output:** {}",
+        sheet.name,
+        sheet.rows.len(),
+        sheet.column_count(),
+        output_format.name()
+    );
+    if sheet.sheet_names.len() > 1 {
+        description.push_str(&format!(
+            "\n**Other sheets:** {}",
+            sheet
+                .sheet_names
+                .iter()
+                .filter(|name| **name != sheet.name)
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if output_format != SheetFormat::Xlsx
+        && let Ok(preview) = std::str::from_utf8(&output)
+        && preview.len() <= INLINE_PREVIEW_LIMIT
+    {
+        description.push_str(&format!(
+            "\n\n```{}\n{}\n```",
+            output_format.extension(),
+            preview.trim_end().replace("```", "`\u{200b}``")
+        ));
+    }
+
+    let output_name = format!(
+        "{}.{}",
+        sanitize_filename(&file_stem(&file.filename)),
+        output_format.extension()
+    );
+    let embed = CreateEmbed::new()
+        .title("✅ Sheet Converted")
+        .description(description)
+        .color(EMBED_SUCCESS_COLOR);
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(output, output_name)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SheetFormat, check_biff_dimensions, read_sheet, write_sheet};
+
+    const CSV: &[u8] = b"name,qty,note\nApple,3,\"red | green\"\nPear,10,\n";
+
+    #[test]
+    fn csv_becomes_markdown_and_json() {
+        let sheet = read_sheet(CSV, "fruit.csv", None).unwrap();
+        assert_eq!(sheet.rows.len(), 3);
+
+        let markdown = write_sheet(&sheet, SheetFormat::Markdown, true).unwrap();
+        assert_eq!(
+            String::from_utf8(markdown).unwrap(),
+            "| name | qty | note |\n| --- | ---: | --- |\n\
+             | Apple | 3 | red \\| green |\n| Pear | 10 |  |\n"
+        );
+
+        let json = write_sheet(&sheet, SheetFormat::Json, true).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            json[1],
+            serde_json::json!({"name": "Pear", "qty": 10, "note": ""})
+        );
+    }
+
+    #[test]
+    fn xlsx_round_trips_through_the_reader() {
+        let sheet = read_sheet(CSV, "fruit.csv", None).unwrap();
+        let xlsx = write_sheet(&sheet, SheetFormat::Xlsx, true).unwrap();
+
+        let workbook = read_sheet(&xlsx, "fruit.xlsx", Some("1")).unwrap();
+        assert_eq!(workbook.name, "fruit");
+        let tsv = write_sheet(&workbook, SheetFormat::Tsv, true).unwrap();
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            "name\tqty\tnote\nApple\t3\tred | green\nPear\t10\t\n"
+        );
+
+        let error = read_sheet(&xlsx, "fruit.xlsx", Some("Prices")).unwrap_err();
+        assert!(error.contains("`fruit`"), "{}", error);
+    }
+
+    #[test]
+    fn html_escapes_cells_and_detects_tabs() {
+        let sheet = read_sheet(b"a\tb\n<x>\t&\n", "table.txt", None).unwrap();
+        let html =
+            String::from_utf8(write_sheet(&sheet, SheetFormat::Html, true).unwrap()).unwrap();
+        assert!(html.contains("<th>a</th><th>b</th>"));
+        assert!(html.contains("<td>&lt;x&gt;</td><td>&amp;</td>"));
+    }
+
+    #[test]
+    fn far_apart_xlsx_cells_are_rejected_before_loading() {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.write_string(0, 0, "first").unwrap();
+        worksheet.write_string(1_048_575, 16_383, "last").unwrap();
+        let xlsx = workbook.save_to_buffer().unwrap();
+
+        let error = read_sheet(&xlsx, "corners.xlsx", None).unwrap_err();
+        assert!(error.contains("rejected"), "{}", error);
+    }
+
+    #[test]
+    fn biff_records_are_checked_against_the_limits() {
+        let record = |kind: u16, body: &[u8]| {
+            let mut record = kind.to_le_bytes().to_vec();
+            record.extend((body.len() as u16).to_le_bytes());
+            record.extend(body);
+            record
+        };
+        let number = |row: u16, column: u16| {
+            let mut body = [row.to_le_bytes(), column.to_le_bytes(), [0, 0]].concat();
+            body.extend(1.0f64.to_le_bytes());
+            record(0x0203, &body)
+        };
+
+        let small = [number(0, 0), number(9, 4)].concat();
+        assert!(check_biff_dimensions(&small).is_ok());
+
+        let wide = [number(0, 0), number(0, 5_000)].concat();
+        assert!(check_biff_dimensions(&wide).is_err());
+
+        let mut dimensions = 0u32.to_le_bytes().to_vec();
+        dimensions.extend(1_000_000u32.to_le_bytes());
+        dimensions.extend([0, 0, 200, 0, 0, 0]);
+        assert!(check_biff_dimensions(&record(0x0200, &dimensions)).is_err());
+    }
+}
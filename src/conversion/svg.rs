@@ -0,0 +1,103 @@
+use crate::Error;
+use crate::attachments::validate_image_dimensions;
+use image::{DynamicImage, RgbaImage};
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{self, ImageHrefResolver, Options};
+use std::sync::{Arc, OnceLock};
+
+/// How many leading bytes are inspected when sniffing for SVG markup
+const SVG_SNIFF_LEN: usize = 4096;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// System fonts are scanned once and shared by every rasterization.
+fn font_database() -> Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = usvg::fontdb::Database::new();
+            fonts.load_system_fonts();
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+/// Determine whether bytes look like SVG (or gzip-compressed SVGZ) rather than a raster image.
+pub fn is_svg(data: &[u8]) -> bool {
+    if image::guess_format(data).is_ok() {
+        return false;
+    }
+    if data.starts_with(&GZIP_MAGIC) {
+        return true;
+    }
+
+    let head = String::from_utf8_lossy(&data[..data.len().min(SVG_SNIFF_LEN)]);
+    head.contains("<svg")
+}
+
+/// Rasterize an SVG document `width` pixels wide, preserving its aspect ratio.
+///
+/// External `href`s are never resolved so uploaded SVGs cannot read files
+/// from the bot host; embedded `data:` images still render.
+pub fn rasterize_svg(data: &[u8], width: u32) -> Result<DynamicImage, Error> {
+    let options = Options {
+        image_href_resolver: ImageHrefResolver {
+            resolve_data: ImageHrefResolver::default_data_resolver(),
+            resolve_string: Box::new(|_, _| None),
+        },
+        fontdb: font_database(),
+        ..Options::default()
+    };
+
+    let tree = usvg::Tree::from_data(data, &options)
+        .map_err(|e| Error::from(format!("Failed to parse SVG: {}", e)))?;
+
+    let size = tree.size();
+    let scale = width as f32 / size.width();
+    let height = (size.height() * scale).round().max(1.0) as u32;
+    validate_image_dimensions(width, height).map_err(Error::from)?;
+
+    let mut pixmap =
+        Pixmap::new(width, height).ok_or_else(|| Error::from("Invalid SVG rasterization size."))?;
+    resvg::render(
+        &tree,
+        Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // tiny-skia stores premultiplied alpha, while `image` expects straight alpha.
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    RgbaImage::from_raw(width, height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| Error::from("Failed to rebuild the rasterized SVG."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_svg, rasterize_svg};
+
+    const SQUARE: &[u8] = br##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="5">
+        <rect width="10" height="5" fill="#ff0000"/>
+    </svg>"##;
+
+    #[test]
+    fn is_svg_detects_markup_but_not_raster_images() {
+        assert!(is_svg(SQUARE));
+        assert!(!is_svg(b"\x89PNG\r\n\x1a\n"));
+        assert!(!is_svg(b"plain text"));
+    }
+
+    #[test]
+    fn rasterize_svg_scales_to_requested_width() {
+        let img = rasterize_svg(SQUARE, 40).unwrap().to_rgba8();
+        assert_eq!(img.dimensions(), (40, 20));
+        assert_eq!(img.get_pixel(20, 10).0, [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,17 @@
+use poise::serenity_prelude as serenity;
+use serenity::builder::CreateEmbed;
+
+pub const EMBED_ERROR_COLOR: u32 = 0xff4444;
+pub const EMBED_SUCCESS_COLOR: u32 = 0x27ae60;
+pub const EMBED_WARNING_COLOR: u32 = 0xf39c12;
+
+/// Longest text shown inside an embed before falling back to an attachment
+pub const INLINE_PREVIEW_LIMIT: usize = 1900;
+
+/// Build the red embed used to report a failed command
+pub fn error_embed(title: &str, message: impl Into<String>) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(title)
+        .description(message.into())
+        .color(EMBED_ERROR_COLOR)
+}
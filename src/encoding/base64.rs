@@ -1,5 +1,6 @@
 use crate::attachments::{sanitize_filename, validate_attachment_size, validate_output_size};
-use crate::utils::{detect_file_type, file_stem, format_file_size, is_previewable_text};
+use crate::embeds::{EMBED_SUCCESS_COLOR, INLINE_PREVIEW_LIMIT, error_embed};
+use crate::utils::{detect_file_type, file_stem, format_file_size, preview_text};
 use ::serenity::all::CreateEmbedFooter;
 use poise::serenity_prelude as serenity;
 use poise::serenity_prelude::Attachment;
@@ -7,19 +8,10 @@ use serenity::builder::CreateEmbed;
 
 use crate::{Context, Error};
 use base64::{Engine, engine::general_purpose};
+use encoding_rs::Encoding;
 
-const EMBED_ERROR_COLOR: u32 = 0xff4444;
-const EMBED_SUCCESS_COLOR: u32 = 0x27ae60;
-const INLINE_PREVIEW_LIMIT: usize = 1900;
 const INLINE_ENCODE_LIMIT: usize = 1024;
 
-fn error_embed(title: &str, message: impl Into<String>) -> CreateEmbed {
-    CreateEmbed::new()
-        .title(title)
-        .description(message.into())
-        .color(EMBED_ERROR_COLOR)
-}
-
 fn encoded_summary_embed(filename: &str, original_len: usize, encoded_len: usize) -> CreateEmbed {
     CreateEmbed::new()
         .title("✅ Base64 Encoded")
@@ -42,21 +34,27 @@ fn decoded_summary_embed(decoded_len: usize) -> CreateEmbed {
         .color(EMBED_SUCCESS_COLOR)
 }
 
-async fn send_decoded_response(ctx: Context<'_>, decoded: Vec<u8>) -> Result<(), Error> {
-    if is_previewable_text(&decoded) {
-        let decoded_string = String::from_utf8(decoded.clone())
-            .map_err(|e| Error::from(format!("Failed to prepare decoded text: {}", e)))?;
-
-        if decoded_string.len() <= INLINE_PREVIEW_LIMIT {
-            let embed = decoded_summary_embed(decoded.len()).field(
-                "Decoded Data",
-                format!("```\n{}\n```", decoded_string),
-                false,
-            );
+async fn send_decoded_response(
+    ctx: Context<'_>,
+    decoded: Vec<u8>,
+    preview: Option<(String, &'static Encoding)>,
+) -> Result<(), Error> {
+    if let Some((decoded_string, encoding)) = preview
+        && decoded_string.len() <= INLINE_PREVIEW_LIMIT
+    {
+        let field_name = if encoding == encoding_rs::UTF_8 {
+            "Decoded Data".to_string()
+        } else {
+            format!("Decoded Data ({})", encoding.name())
+        };
+        let embed = decoded_summary_embed(decoded.len()).field(
+            field_name,
+            format!("```\n{}\n```", decoded_string),
+            false,
+        );
 
-            ctx.send(poise::CreateReply::default().embed(embed)).await?;
-            return Ok(());
-        }
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
     }
 
     validate_output_size(decoded.len(), "Decoded data").map_err(Error::from)?;
@@ -182,12 +180,18 @@ pub async fn base64_decode(
         return Ok(());
     };
 
-    let decoded_result =
-        tokio::task::spawn_blocking(move || general_purpose::STANDARD.decode(&data_to_decode))
-            .await?;
+    let decoded_result = tokio::task::spawn_blocking(move || {
+        general_purpose::STANDARD
+            .decode(&data_to_decode)
+            .map(|decoded| {
+                let preview = preview_text(&decoded);
+                (decoded, preview)
+            })
+    })
+    .await?;
 
     match decoded_result {
-        Ok(decoded) => send_decoded_response(ctx, decoded).await?,
+        Ok((decoded, preview)) => send_decoded_response(ctx, decoded, preview).await?,
         Err(e) => {
             let embed = error_embed(
                 "❌ Decode Failed",
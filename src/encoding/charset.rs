@@ -0,0 +1,431 @@
+use crate::attachments::{sanitize_filename, validate_attachment_size, validate_output_size};
+use crate::embeds::{EMBED_SUCCESS_COLOR, error_embed};
+use crate::utils::{DetectedEncoding, detect_encoding, format_file_size};
+use crate::{Context, Error};
+use encoding_rs::{EncoderResult, Encoding};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed};
+use unicode_normalization::{UnicodeNormalization, is_nfc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum TargetEncoding {
+    #[name = "UTF-8"]
+    Utf8,
+    #[name = "UTF-16 LE"]
+    Utf16Le,
+    #[name = "UTF-16 BE"]
+    Utf16Be,
+    #[name = "Windows-1252 (Western European, Latin-1)"]
+    Windows1252,
+    #[name = "Windows-1250 (Central European)"]
+    Windows1250,
+    #[name = "Windows-1251 (Cyrillic)"]
+    Windows1251,
+    #[name = "KOI8-R (Cyrillic)"]
+    Koi8R,
+    #[name = "Shift_JIS (Japanese)"]
+    ShiftJis,
+    #[name = "EUC-JP (Japanese)"]
+    EucJp,
+    #[name = "GB18030 (Chinese)"]
+    Gb18030,
+    #[name = "Big5 (Traditional Chinese)"]
+    Big5,
+    #[name = "EUC-KR (Korean)"]
+    EucKr,
+}
+
+impl TargetEncoding {
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            Self::Utf8 => encoding_rs::UTF_8,
+            Self::Utf16Le => encoding_rs::UTF_16LE,
+            Self::Utf16Be => encoding_rs::UTF_16BE,
+            Self::Windows1252 => encoding_rs::WINDOWS_1252,
+            Self::Windows1250 => encoding_rs::WINDOWS_1250,
+            Self::Windows1251 => encoding_rs::WINDOWS_1251,
+            Self::Koi8R => encoding_rs::KOI8_R,
+            Self::ShiftJis => encoding_rs::SHIFT_JIS,
+            Self::EucJp => encoding_rs::EUC_JP,
+            Self::Gb18030 => encoding_rs::GB18030,
+            Self::Big5 => encoding_rs::BIG5,
+            Self::EucKr => encoding_rs::EUC_KR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum LineEnding {
+    #[name = "Keep"]
+    Keep,
+    #[name = "LF (Unix, macOS)"]
+    Lf,
+    #[name = "CRLF (Windows)"]
+    Crlf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum BomMode {
+    #[name = "Keep"]
+    Keep,
+    #[name = "Add"]
+    Add,
+    #[name = "Strip"]
+    Strip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Normalization {
+    #[name = "NFC (composed)"]
+    Nfc,
+    #[name = "NFD (decomposed)"]
+    Nfd,
+    #[name = "NFKC (compatibility composed)"]
+    Nfkc,
+    #[name = "NFKD (compatibility decomposed)"]
+    Nfkd,
+}
+
+/// Encode text, replacing characters the encoding cannot represent with `?`.
+///
+/// Returns the bytes and the number of characters replaced.
+pub fn encode_text(text: &str, encoding: &'static Encoding) -> (Vec<u8>, usize) {
+    // encoding_rs only decodes UTF-16, so it is written here
+    if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE {
+        let big_endian = encoding == encoding_rs::UTF_16BE;
+        let bytes = text
+            .encode_utf16()
+            .flat_map(|unit| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            })
+            .collect();
+        return (bytes, 0);
+    }
+
+    let mut encoder = encoding.new_encoder();
+    let mut output = Vec::new();
+    let mut remaining = text;
+    let mut unmappable = 0;
+    loop {
+        let needed = encoder
+            .max_buffer_length_from_utf8_without_replacement(remaining.len())
+            .unwrap_or(remaining.len() * 4);
+        output.reserve(needed.max(1));
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(remaining, &mut output, true);
+        remaining = &remaining[read..];
+        match result {
+            EncoderResult::InputEmpty => break,
+            EncoderResult::OutputFull => {}
+            EncoderResult::Unmappable(_) => {
+                unmappable += 1;
+                output.push(b'?');
+            }
+        }
+    }
+    (output, unmappable)
+}
+
+/// The BOM written for an encoding, if it has one.
+fn bom_bytes(encoding: &'static Encoding) -> &'static [u8] {
+    if encoding == encoding_rs::UTF_8 {
+        b"\xEF\xBB\xBF"
+    } else if encoding == encoding_rs::UTF_16LE {
+        b"\xFF\xFE"
+    } else if encoding == encoding_rs::UTF_16BE {
+        b"\xFE\xFF"
+    } else {
+        b""
+    }
+}
+
+/// Counts of CRLF, LF-only and CR-only line endings.
+fn count_line_endings(text: &str) -> (usize, usize, usize) {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    let cr = text.matches('\r').count() - crlf;
+    (crlf, lf, cr)
+}
+
+fn describe_line_endings(text: &str) -> String {
+    let (crlf, lf, cr) = count_line_endings(text);
+    let kinds: Vec<String> = [("CRLF", crlf), ("LF", lf), ("CR", cr)]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect();
+    match kinds.len() {
+        0 => "None".to_string(),
+        1 => kinds[0].clone(),
+        _ => format!("Mixed: {}", kinds.join(", ")),
+    }
+}
+
+pub fn convert_line_endings(text: &str, ending: LineEnding) -> String {
+    let unix = || text.replace("\r\n", "\n").replace('\r', "\n");
+    match ending {
+        LineEnding::Keep => text.to_string(),
+        LineEnding::Lf => unix(),
+        LineEnding::Crlf => unix().replace('\n', "\r\n"),
+    }
+}
+
+pub fn normalize(text: &str, form: Normalization) -> String {
+    match form {
+        Normalization::Nfc => text.nfc().collect(),
+        Normalization::Nfd => text.nfd().collect(),
+        Normalization::Nfkc => text.nfkc().collect(),
+        Normalization::Nfkd => text.nfkd().collect(),
+    }
+}
+
+/// The result of decoding and re-encoding a text file.
+struct ConvertedText {
+    detected: DetectedEncoding,
+    had_errors: bool,
+    line_endings: String,
+    nfc: bool,
+    unchanged: bool,
+    output: Vec<u8>,
+    output_line_endings: String,
+    write_bom: bool,
+    unmappable: usize,
+}
+
+/// Detect a text file's encoding and convert its encoding, line endings, BOM or Unicode form
+#[poise::command(slash_command)]
+pub async fn text_encoding(
+    ctx: Context<'_>,
+    #[description = "Text file to inspect or convert"] file: Attachment,
+    #[description = "Encoding to convert to (default: UTF-8)"] to: Option<TargetEncoding>,
+    #[description = "Encoding of the file, e.g. shift_jis or windows-1252 (default: detected)"]
+    from: Option<String>,
+    #[description = "Line endings to use (default: keep)"] line_endings: Option<LineEnding>,
+    #[description = "Byte order mark for UTF-8 and UTF-16 (default: keep)"] bom: Option<BomMode>,
+    #[description = "Unicode normalization form to apply"] normalization: Option<Normalization>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    if let Err(message) = validate_attachment_size(&file) {
+        let embed = error_embed("❌ File Too Large", message);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+    let data = match file.download().await {
+        Ok(data) => data,
+        Err(e) => {
+            let embed = error_embed(
+                "❌ Download Failed",
+                format!("Failed to download file: {}", e),
+            );
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let detected = match from.as_deref().map(str::trim) {
+        Some(label) => {
+            let Some(encoding) = Encoding::for_label(label.as_bytes()) else {
+                let embed = error_embed(
+                    "❌ Unknown Encoding",
+                    format!(
+                        "`{}` is not an encoding name, try e.g. `utf-16le`, `windows-1252` or `shift_jis`.",
+                        label
+                    ),
+                );
+                ctx.send(poise::CreateReply::default().embed(embed)).await?;
+                return Ok(());
+            };
+            let bom_len = Encoding::for_bom(&data)
+                .filter(|(bom_encoding, _)| *bom_encoding == encoding)
+                .map_or(0, |(_, len)| len);
+            Some(DetectedEncoding {
+                encoding,
+                bom_len,
+                confident: true,
+            })
+        }
+        None => None,
+    };
+
+    let target = to.unwrap_or(TargetEncoding::Utf8).encoding();
+    let encoding_given = detected.is_some();
+    let result = tokio::task::spawn_blocking(move || {
+        let detected = detected.unwrap_or_else(|| detect_encoding(&data));
+        let (text, had_errors) = detected
+            .encoding
+            .decode_without_bom_handling(&data[detected.bom_len..]);
+        if text.contains('\0') {
+            return Err((
+                "❌ Not a Text File",
+                "The file contains binary data, so it cannot be converted as text.".to_string(),
+            ));
+        }
+        if had_errors && !encoding_given {
+            return Err((
+                "❌ Unknown Encoding",
+                format!(
+                    "The file looks like {} but is not valid in it. Set `from` to its encoding.",
+                    detected.encoding.name()
+                ),
+            ));
+        }
+
+        let mut converted = text.to_string();
+        if let Some(form) = normalization {
+            converted = normalize(&converted, form);
+        }
+        converted = convert_line_endings(&converted, line_endings.unwrap_or(LineEnding::Keep));
+        let write_bom = match bom.unwrap_or(BomMode::Keep) {
+            BomMode::Keep => detected.bom_len > 0,
+            BomMode::Add => true,
+            BomMode::Strip => false,
+        };
+        let (encoded, unmappable) = encode_text(&converted, target);
+        let mut output = Vec::with_capacity(encoded.len() + 3);
+        if write_bom {
+            output.extend_from_slice(bom_bytes(target));
+        }
+        output.extend_from_slice(&encoded);
+
+        Ok(ConvertedText {
+            detected,
+            had_errors,
+            line_endings: describe_line_endings(&text),
+            nfc: is_nfc(&text),
+            unchanged: output == data,
+            output,
+            output_line_endings: describe_line_endings(&converted),
+            write_bom,
+            unmappable,
+        })
+    })
+    .await
+    .map_err(|e| {
+        (
+            "❌ Conversion Failed",
+            format!("Conversion task failed: {}", e),
+        )
+    })
+    .and_then(|result| result);
+    let ConvertedText {
+        detected,
+        had_errors,
+        line_endings,
+        nfc,
+        unchanged,
+        output,
+        output_line_endings,
+        write_bom,
+        unmappable,
+    } = match result {
+        Ok(converted) => converted,
+        Err((title, message)) => {
+            let embed = error_embed(title, message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let mut description = format!(
+        "**File:** `{}`\n**Encoding:** {}{}\n**BOM:** {}\n**Line endings:** {}\n**Unicode:** {}",
+        file.filename,
+        detected.encoding.name(),
+        match (&from, detected.confident) {
+            (Some(_), _) => " (as given)",
+            (None, true) => "",
+            (None, false) => " (best guess)",
+        },
+        if detected.bom_len > 0 { "Yes" } else { "No" },
+        line_endings,
+        if nfc { "NFC" } else { "Not NFC" }
+    );
+    if had_errors {
+        description.push_str("\n⚠️ Some bytes were invalid and were replaced with `�`.");
+    }
+    if unmappable > 0 {
+        description.push_str(&format!(
+            "\n⚠️ {} characters have no equivalent in {} and were replaced with `?`.",
+            unmappable,
+            target.name()
+        ));
+    }
+    if write_bom && bom_bytes(target).is_empty() {
+        description.push_str(&format!("\n{} has no byte order mark.", target.name()));
+    }
+
+    if unchanged {
+        let embed = CreateEmbed::new()
+            .title("✅ No Changes Needed")
+            .description(description)
+            .color(EMBED_SUCCESS_COLOR);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    if let Err(message) = validate_output_size(output.len(), "Converted text") {
+        let embed = error_embed("❌ Conversion Failed", message);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+    description.push_str(&format!(
+        "\n\n**Output:** {}{}, {}\n**Output line endings:** {}",
+        target.name(),
+        if write_bom && !bom_bytes(target).is_empty() {
+            " with BOM"
+        } else {
+            ""
+        },
+        format_file_size(output.len() as u64),
+        output_line_endings
+    ));
+    let embed = CreateEmbed::new()
+        .title("✅ Text Converted")
+        .description(description)
+        .color(EMBED_SUCCESS_COLOR);
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                output,
+                sanitize_filename(&file.filename),
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineEnding, Normalization, convert_line_endings, encode_text, normalize};
+
+    #[test]
+    fn encoding_replaces_unmappable_characters() {
+        let (shift_jis, unmappable) = encode_text("日本語テキスト", encoding_rs::SHIFT_JIS);
+        assert_eq!(unmappable, 0);
+        let (decoded, _, _) = encoding_rs::SHIFT_JIS.decode(&shift_jis);
+        assert_eq!(decoded, "日本語テキスト");
+
+        let (latin, unmappable) = encode_text("naïve → 😀", encoding_rs::WINDOWS_1252);
+        assert_eq!(latin, b"na\xefve ? ?");
+        assert_eq!(unmappable, 2);
+    }
+
+    #[test]
+    fn converts_line_endings_and_normalizes() {
+        assert_eq!(
+            convert_line_endings("a\r\nb\rc\n", LineEnding::Lf),
+            "a\nb\nc\n"
+        );
+        assert_eq!(
+            convert_line_endings("a\nb\r\n", LineEnding::Crlf),
+            "a\r\nb\r\n"
+        );
+        assert_eq!(normalize("e\u{301}", Normalization::Nfc), "é");
+        assert_eq!(normalize("ﬁ", Normalization::Nfkc), "fi");
+    }
+}
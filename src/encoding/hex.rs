@@ -1,25 +1,17 @@
 use crate::attachments::{sanitize_filename, validate_attachment_size, validate_output_size};
+use crate::embeds::{EMBED_SUCCESS_COLOR, INLINE_PREVIEW_LIMIT, error_embed};
 use poise::serenity_prelude as serenity;
 use poise::serenity_prelude::Attachment;
 use serenity::all::CreateEmbedFooter;
 use serenity::builder::CreateEmbed;
 
-use crate::utils::{detect_file_type, file_stem, format_file_size, is_previewable_text};
+use crate::utils::{decode_text, detect_file_type, file_stem, format_file_size, preview_text};
 use crate::{Context, Error};
+use encoding_rs::Encoding;
 use hex;
 
-const EMBED_ERROR_COLOR: u32 = 0xff4444;
-const EMBED_SUCCESS_COLOR: u32 = 0x27ae60;
-const INLINE_PREVIEW_LIMIT: usize = 1900;
 const INLINE_ENCODE_LIMIT: usize = 1024;
 
-fn error_embed(title: &str, message: impl Into<String>) -> CreateEmbed {
-    CreateEmbed::new()
-        .title(title)
-        .description(message.into())
-        .color(EMBED_ERROR_COLOR)
-}
-
 fn encoded_summary_embed(filename: &str, original_size: &str, encoded_size: &str) -> CreateEmbed {
     CreateEmbed::new()
         .title("✅ Hex Encoded")
@@ -51,22 +43,21 @@ async fn send_decoded_response(
     original_filename: Option<&str>,
     encoded_len: usize,
     decoded_data: Vec<u8>,
+    preview: Option<(String, &'static Encoding)>,
 ) -> Result<(), Error> {
-    if is_previewable_text(&decoded_data) {
-        let decoded_string = String::from_utf8(decoded_data.clone())
-            .map_err(|e| Error::from(format!("Failed to prepare decoded text: {}", e)))?;
-
-        if decoded_string.len() <= INLINE_PREVIEW_LIMIT {
-            let embed = decoded_summary_embed(original_filename, encoded_len, decoded_data.len())
-                .field(
-                    "Decoded Data",
-                    format!("```\n{}\n```", decoded_string),
-                    false,
-                );
+    if let Some((decoded_string, encoding)) = preview
+        && decoded_string.len() <= INLINE_PREVIEW_LIMIT
+    {
+        let field_name = if encoding == encoding_rs::UTF_8 {
+            "Decoded Data".to_string()
+        } else {
+            format!("Decoded Data ({})", encoding.name())
+        };
+        let embed = decoded_summary_embed(original_filename, encoded_len, decoded_data.len())
+            .field(field_name, format!("```\n{}\n```", decoded_string), false);
 
-            ctx.send(poise::CreateReply::default().embed(embed)).await?;
-            return Ok(());
-        }
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
     }
 
     validate_output_size(decoded_data.len(), "Decoded data").map_err(Error::from)?;
@@ -170,7 +161,13 @@ pub async fn hex_decode(
         let filename = file.filename.clone();
         match file.download().await {
             Ok(file_data) => {
-                let string_data = String::from_utf8_lossy(&file_data).to_string();
+                // Hex saved by Windows editors is often UTF-16
+                let string_data = tokio::task::spawn_blocking(move || {
+                    decode_text(&file_data)
+                        .map(|(text, _)| text)
+                        .unwrap_or_else(|| String::from_utf8_lossy(&file_data).to_string())
+                })
+                .await?;
                 (string_data, Some(filename))
             }
             Err(e) => {
@@ -196,10 +193,15 @@ pub async fn hex_decode(
     };
 
     let hex_input_clone = hex_input.clone();
-    let decoded_data = match tokio::task::spawn_blocking(move || hex::decode(hex_input_clone))
-        .await?
-    {
-        Ok(data) => data,
+    let decoded = tokio::task::spawn_blocking(move || {
+        hex::decode(hex_input_clone).map(|decoded| {
+            let preview = preview_text(&decoded);
+            (decoded, preview)
+        })
+    })
+    .await?;
+    let (decoded_data, preview) = match decoded {
+        Ok(decoded) => decoded,
         Err(e) => {
             let embed = error_embed("❌ Decoding Failed", format!("Failed to decode hex: {}", e));
             ctx.send(poise::CreateReply::default().embed(embed)).await?;
@@ -212,6 +214,7 @@ pub async fn hex_decode(
         original_filename.as_deref(),
         hex_input.len(),
         decoded_data,
+        preview,
     )
     .await?;
 
@@ -1,2 +1,4 @@
 pub mod base64;
+pub mod charset;
 pub mod hex;
+pub mod qr;
@@ -0,0 +1,343 @@
+use crate::attachments::{validate_image_dimensions, validate_output_size};
+use crate::conversion::image::{OutputFormat, download_image, encode_image};
+use crate::embeds::{EMBED_SUCCESS_COLOR, INLINE_PREVIEW_LIMIT, error_embed};
+use crate::utils::parse_hex_color;
+use crate::{Context, Error};
+use image::{DynamicImage, GrayImage, Rgba, RgbaImage};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use qrcode::render::svg;
+use qrcode::{Color, EcLevel, QrCode};
+
+/// Blank modules around the code, as required by the QR specification
+const QUIET_ZONE: u32 = 4;
+const DEFAULT_SIZE: u32 = 512;
+const DEFAULT_FOREGROUND: &str = "#000000";
+const DEFAULT_BACKGROUND: &str = "#ffffff";
+/// Minimum luma difference between the two colours before scanners struggle
+const MIN_CONTRAST: f32 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ErrorCorrection {
+    #[name = "Low (7% recovery)"]
+    Low,
+    #[name = "Medium (15% recovery)"]
+    Medium,
+    #[name = "Quartile (25% recovery)"]
+    Quartile,
+    #[name = "High (30% recovery)"]
+    High,
+}
+
+impl ErrorCorrection {
+    const fn level(self) -> EcLevel {
+        match self {
+            Self::Low => EcLevel::L,
+            Self::Medium => EcLevel::M,
+            Self::Quartile => EcLevel::Q,
+            Self::High => EcLevel::H,
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Medium => "Medium",
+            Self::Quartile => "Quartile",
+            Self::High => "High",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum QrFormat {
+    #[name = "PNG"]
+    Png,
+    #[name = "SVG"]
+    Svg,
+}
+
+fn luma([r, g, b, _]: [u8; 4]) -> f32 {
+    0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b)
+}
+
+fn hex_string([r, g, b, a]: [u8; 4]) -> String {
+    if a == u8::MAX {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+/// Rasterize a QR code at the largest whole-pixel module size that fits `size`.
+fn render_png(code: &QrCode, size: u32, dark: [u8; 4], light: [u8; 4]) -> RgbaImage {
+    let modules = code.width() as u32;
+    let total = modules + QUIET_ZONE * 2;
+    let module_px = (size / total).max(1);
+    let colors = code.to_colors();
+
+    RgbaImage::from_fn(total * module_px, total * module_px, |x, y| {
+        let (mx, my) = (x / module_px, y / module_px);
+        let inside = (QUIET_ZONE..QUIET_ZONE + modules).contains(&mx)
+            && (QUIET_ZONE..QUIET_ZONE + modules).contains(&my);
+        let is_dark = inside
+            && colors[((my - QUIET_ZONE) * modules + (mx - QUIET_ZONE)) as usize] == Color::Dark;
+        Rgba(if is_dark { dark } else { light })
+    })
+}
+
+fn render_svg(code: &QrCode, size: u32, dark: [u8; 4], light: [u8; 4]) -> String {
+    let (dark, light) = (hex_string(dark), hex_string(light));
+    code.render::<svg::Color>()
+        .min_dimensions(size, size)
+        .dark_color(svg::Color(&dark))
+        .light_color(svg::Color(&light))
+        .build()
+}
+
+/// Find and decode every QR code in an image.
+///
+/// Grids that are detected but cannot be decoded are skipped.
+pub fn decode_qr_codes(img: &GrayImage) -> Vec<String> {
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        img.width() as usize,
+        img.height() as usize,
+        |x, y| img.get_pixel(x as u32, y as u32)[0],
+    );
+
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok())
+        .map(|(_, content)| content)
+        .collect()
+}
+
+/// Generate a QR code from text
+#[poise::command(slash_command)]
+pub async fn qr_encode(
+    ctx: Context<'_>,
+    #[description = "Text or link to encode"] text: String,
+    #[description = "Error correction level (defaults to Medium)"] error_correction: Option<
+        ErrorCorrection,
+    >,
+    #[description = "Approximate image size in pixels (defaults to 512)"]
+    #[min = 64]
+    #[max = 4096]
+    size: Option<u32>,
+    #[description = "Module colour as hex (defaults to #000000)"] foreground: Option<String>,
+    #[description = "Background colour as hex (defaults to #ffffff)"] background: Option<String>,
+    #[description = "Output format (defaults to PNG)"] output_format: Option<QrFormat>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let error_correction = error_correction.unwrap_or(ErrorCorrection::Medium);
+    let size = size.unwrap_or(DEFAULT_SIZE);
+    let output_format = output_format.unwrap_or(QrFormat::Png);
+    let colors = (
+        parse_hex_color(foreground.as_deref().unwrap_or(DEFAULT_FOREGROUND)),
+        parse_hex_color(background.as_deref().unwrap_or(DEFAULT_BACKGROUND)),
+    );
+    let (Some(dark), Some(light)) = colors else {
+        let embed = error_embed(
+            "❌ Invalid Colour",
+            "Colours must be hex values such as `#000000` or `#ffffff`.",
+        );
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let code = match QrCode::with_error_correction_level(text.as_bytes(), error_correction.level())
+    {
+        Ok(code) => code,
+        Err(e) => {
+            let embed = error_embed(
+                "❌ QR Encode Failed",
+                format!(
+                    "Failed to encode text: {}. Try shorter text or a lower error correction level.",
+                    e
+                ),
+            );
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let modules = code.width();
+
+    let result = tokio::task::spawn_blocking(move || match output_format {
+        QrFormat::Png => {
+            let img = render_png(&code, size, dark, light);
+            validate_image_dimensions(img.width(), img.height()).map_err(Error::from)?;
+            encode_image(DynamicImage::ImageRgba8(img), OutputFormat::Png)
+        }
+        QrFormat::Svg => Ok(render_svg(&code, size, dark, light).into_bytes()),
+    })
+    .await
+    .map_err(|e| Error::from(format!("QR encode task failed: {}", e)))
+    .and_then(|result| result);
+
+    let output_bytes = match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let embed = error_embed("❌ QR Encode Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(output_bytes.len(), "QR code").map_err(Error::from)?;
+
+    let mut embed = CreateEmbed::new()
+        .title("✅ QR Code Generated")
+        .description(format!(
+            "**Characters:** {}\n**Modules:** {}×{}\n**Error correction:** {}",
+            text.chars().count(),
+            modules,
+            modules,
+            error_correction.label()
+        ))
+        .color(EMBED_SUCCESS_COLOR);
+    if (luma(dark) - luma(light)).abs() < MIN_CONTRAST {
+        embed = embed.footer(CreateEmbedFooter::new(
+            "⚠️ The colours have low contrast, so some scanners may not read this code.",
+        ));
+    } else if luma(dark) > luma(light) {
+        embed = embed.footer(CreateEmbedFooter::new(
+            "⚠️ Light-on-dark codes are not supported by every scanner.",
+        ));
+    }
+
+    let extension = match output_format {
+        QrFormat::Png => "png",
+        QrFormat::Svg => "svg",
+    };
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                output_bytes,
+                format!("qr_code.{}", extension),
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Read QR codes from an image
+#[poise::command(slash_command)]
+pub async fn qr_decode(
+    ctx: Context<'_>,
+    #[description = "Image or screenshot containing QR codes"] file: Attachment,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let img = match download_image(&file, None).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ QR Decode Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let payloads = tokio::task::spawn_blocking(move || decode_qr_codes(&img.to_luma8()))
+        .await
+        .map_err(|e| Error::from(format!("QR decode task failed: {}", e)))?;
+
+    if payloads.is_empty() {
+        let embed = error_embed(
+            "❌ No QR Code Found",
+            "No readable QR code was found. Try a sharper or tighter crop of the code.",
+        );
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let summary = format!(
+        "**File:** `{}`\n**Codes found:** {}",
+        file.filename,
+        payloads.len()
+    );
+    let preview = payloads
+        .iter()
+        .enumerate()
+        .map(|(index, payload)| format!("**Code {}**\n```\n{}\n```", index + 1, payload))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if preview.len() <= INLINE_PREVIEW_LIMIT {
+        let embed = CreateEmbed::new()
+            .title("✅ QR Code Decoded")
+            .description(format!("{}\n\n{}", summary, preview))
+            .color(EMBED_SUCCESS_COLOR);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let contents = payloads.join("\n\n");
+    validate_output_size(contents.len(), "Decoded data").map_err(Error::from)?;
+    let embed = CreateEmbed::new()
+        .title("✅ QR Code Decoded")
+        .description(summary)
+        .color(EMBED_SUCCESS_COLOR)
+        .footer(CreateEmbedFooter::new(
+            "Decoded data is attached as a file.",
+        ));
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                contents.into_bytes(),
+                "qr_contents.txt",
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorCorrection, decode_qr_codes, render_png, render_svg};
+    use image::{DynamicImage, GrayImage, imageops};
+    use qrcode::QrCode;
+
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    #[test]
+    fn rendered_png_decodes_back_to_the_text() {
+        let code = QrCode::with_error_correction_level(
+            "https://example.com/qr",
+            ErrorCorrection::High.level(),
+        )
+        .unwrap();
+        let img = render_png(&code, 256, BLACK, WHITE);
+        assert_eq!(img.width() % (code.width() as u32 + 8), 0);
+
+        let luma = DynamicImage::ImageRgba8(img).to_luma8();
+        assert_eq!(decode_qr_codes(&luma), vec!["https://example.com/qr"]);
+    }
+
+    #[test]
+    fn every_code_in_an_image_is_decoded() {
+        let mut canvas = GrayImage::from_pixel(600, 300, [255].into());
+        for (index, text) in ["first", "second"].iter().enumerate() {
+            let code = QrCode::new(text.as_bytes()).unwrap();
+            let img = DynamicImage::ImageRgba8(render_png(&code, 250, BLACK, WHITE)).to_luma8();
+            imageops::overlay(&mut canvas, &img, 20 + index as i64 * 300, 20);
+        }
+
+        let mut payloads = decode_qr_codes(&canvas);
+        payloads.sort();
+        assert_eq!(payloads, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn svg_uses_requested_colours() {
+        let code = QrCode::new(b"hello").unwrap();
+        let svg = render_svg(&code, 200, [255, 0, 0, 255], [0, 0, 255, 128]);
+        assert!(svg.contains("fill=\"#ff0000\""));
+        assert!(svg.contains("fill=\"#0000ff80\""));
+    }
+}
@@ -0,0 +1,504 @@
+use crate::attachments::{sanitize_filename, validate_image_dimensions, validate_output_size};
+use crate::commands::resize::{fit_within, resize_rgba};
+use crate::conversion::image::{OutputFormat, download_image, encode_image};
+use crate::embeds::error_embed;
+use crate::utils::{file_stem, parse_hex_color};
+use crate::{Context, Error};
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum AlphaFormat {
+    #[name = "PNG"]
+    Png,
+    #[name = "WebP (lossless)"]
+    Webp,
+}
+
+impl AlphaFormat {
+    const fn output_format(self) -> OutputFormat {
+        match self {
+            Self::Png => OutputFormat::Png,
+            Self::Webp => OutputFormat::Webp,
+        }
+    }
+}
+
+const DEFAULT_TOLERANCE: u8 = 32;
+const DEFAULT_FEATHER: u32 = 1;
+const MAX_FEATHER: u32 = 20;
+
+fn color_distance(pixel: &Rgba<u8>, key: [u8; 3]) -> f64 {
+    (0..3)
+        .map(|channel| (f64::from(pixel[channel]) - f64::from(key[channel])).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// The most common colour among the four corners, preferring the top-left on ties.
+pub fn corner_color(img: &RgbaImage) -> [u8; 3] {
+    let (right, bottom) = (img.width() - 1, img.height() - 1);
+    let corners = [(0, 0), (right, 0), (0, bottom), (right, bottom)].map(|(x, y)| {
+        let pixel = img.get_pixel(x, y);
+        [pixel[0], pixel[1], pixel[2]]
+    });
+    corners
+        .iter()
+        .max_by_key(|&&color| {
+            let votes = corners.iter().filter(|&&other| other == color).count();
+            // Ties go to the earliest corner; `max_by_key` alone would pick the last
+            let position = corners
+                .iter()
+                .position(|&other| other == color)
+                .unwrap_or(0);
+            (votes, std::cmp::Reverse(position))
+        })
+        .copied()
+        .unwrap_or([255, 255, 255])
+}
+
+/// Mark the pixels within `tolerance` of `key`.
+///
+/// With `flood_fill` only matching pixels connected to a matching corner are
+/// marked, so the same colour inside the subject is kept.
+fn background_mask(img: &RgbaImage, key: [u8; 3], tolerance: u8, flood_fill: bool) -> Vec<bool> {
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    let matches = |x: usize, y: usize| {
+        let pixel = img.get_pixel(x as u32, y as u32);
+        pixel[3] == 0 || color_distance(pixel, key) <= f64::from(tolerance)
+    };
+
+    if !flood_fill {
+        return (0..width * height)
+            .map(|index| matches(index % width, index / width))
+            .collect();
+    }
+
+    let mut mask = vec![false; width * height];
+    let mut queue = VecDeque::new();
+    for (x, y) in [
+        (0, 0),
+        (width - 1, 0),
+        (0, height - 1),
+        (width - 1, height - 1),
+    ] {
+        if !mask[y * width + x] && matches(x, y) {
+            mask[y * width + x] = true;
+            queue.push_back((x, y));
+        }
+    }
+
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbours {
+            if nx < width && ny < height && !mask[ny * width + nx] && matches(nx, ny) {
+                mask[ny * width + nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    mask
+}
+
+/// Box-blur a single channel horizontally and vertically with the given radius.
+fn box_blur(values: &[f32], width: usize, height: usize, radius: usize) -> Vec<f32> {
+    let pass = |input: &[f32], len: usize, lines: usize, index: &dyn Fn(usize, usize) -> usize| {
+        let mut output = vec![0.0; input.len()];
+        for line in 0..lines {
+            for position in 0..len {
+                let start = position.saturating_sub(radius);
+                let end = (position + radius).min(len - 1);
+                let sum: f32 = (start..=end).map(|p| input[index(line, p)]).sum();
+                output[index(line, position)] = sum / (end - start + 1) as f32;
+            }
+        }
+        output
+    };
+
+    let horizontal = pass(values, width, height, &|row, column| row * width + column);
+    pass(&horizontal, height, width, &|column, row| {
+        row * width + column
+    })
+}
+
+/// Make the background transparent, softening the cut edge over `feather` pixels.
+pub fn cut_out_background(
+    img: &RgbaImage,
+    key: [u8; 3],
+    tolerance: u8,
+    feather: u32,
+    flood_fill: bool,
+) -> (RgbaImage, usize) {
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    let mask = background_mask(img, key, tolerance, flood_fill);
+    let removed = mask.iter().filter(|&&removed| removed).count();
+
+    let coverage: Vec<f32> = mask
+        .iter()
+        .map(|&removed| if removed { 0.0 } else { 1.0 })
+        .collect();
+    // Blurring the mask fades kept pixels next to the cut; removed pixels stay clear
+    let coverage = if feather > 0 {
+        let blurred = box_blur(&coverage, width, height, feather as usize);
+        coverage
+            .iter()
+            .zip(blurred)
+            .map(|(&kept, blurred)| kept.min(blurred))
+            .collect()
+    } else {
+        coverage
+    };
+
+    let mut output = img.clone();
+    for (pixel, coverage) in output.pixels_mut().zip(coverage) {
+        pixel[3] = (f32::from(pixel[3]) * coverage).round() as u8;
+    }
+    (output, removed)
+}
+
+/// Parse an aspect ratio written as `16:9`, `16/9`, `16x9` or `1.5`.
+pub fn parse_aspect(input: &str) -> Option<f64> {
+    let input = input.trim();
+    let ratio = match input.split_once([':', '/', 'x']) {
+        Some((width, height)) => {
+            width.trim().parse::<f64>().ok()? / height.trim().parse::<f64>().ok()?
+        }
+        None => input.parse().ok()?,
+    };
+    (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+}
+
+/// Smallest canvas with the given aspect ratio that contains `width`×`height`.
+pub fn aspect_canvas(width: u32, height: u32, ratio: f64) -> (u32, u32) {
+    if f64::from(width) / f64::from(height) < ratio {
+        ((f64::from(height) * ratio).round() as u32, height)
+    } else {
+        (width, (f64::from(width) / ratio).round() as u32)
+    }
+}
+
+/// Centre an image on a `width`×`height` canvas filled with `fill`.
+///
+/// Images larger than the canvas are scaled down to fit first.
+pub fn pad_image_to(
+    img: &RgbaImage,
+    width: u32,
+    height: u32,
+    fill: [u8; 4],
+) -> Result<RgbaImage, Error> {
+    let (fitted_width, fitted_height) = fit_within(img.width(), img.height(), width, height);
+    let fitted = if (fitted_width, fitted_height) == img.dimensions() {
+        img.clone()
+    } else {
+        resize_rgba(img, fitted_width, fitted_height)?
+    };
+
+    let mut canvas = RgbaImage::from_pixel(width, height, Rgba(fill));
+    let x = i64::from((width - fitted_width) / 2);
+    let y = i64::from((height - fitted_height) / 2);
+    imageops::overlay(&mut canvas, &fitted, x, y);
+    Ok(canvas)
+}
+
+fn output_filename(file: &Attachment, suffix: &str, format: AlphaFormat) -> String {
+    format!(
+        "{}_{}.{}",
+        sanitize_filename(&file_stem(&file.filename)),
+        suffix,
+        format.output_format().extension()
+    )
+}
+
+/// Make a solid background colour transparent
+#[poise::command(slash_command)]
+pub async fn remove_background(
+    ctx: Context<'_>,
+    #[description = "Image to cut out"] file: Attachment,
+    #[description = "Background colour to remove, e.g. #ffffff (default: detected from the corners)"]
+    color: Option<String>,
+    #[description = "How far a colour may differ from the background and still be removed (default 32)"]
+    #[min = 0]
+    #[max = 255]
+    tolerance: Option<u8>,
+    #[description = "Width in pixels of the softened edge (default 1)"]
+    #[min = 0]
+    #[max = 20]
+    feather: Option<u32>,
+    #[description = "Only remove background connected to the corners (default true)"]
+    flood_fill: Option<bool>,
+    #[description = "Output format (default PNG)"] output_format: Option<AlphaFormat>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let key = match color.as_deref().map(parse_hex_color) {
+        Some(None) => {
+            let embed = error_embed(
+                "❌ Invalid Colour",
+                "Colour must be a hex value such as `#ffffff`.",
+            );
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        Some(Some([r, g, b, _])) => Some([r, g, b]),
+        None => None,
+    };
+
+    let img = match download_image(&file, None).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ Background Removal Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let tolerance = tolerance.unwrap_or(DEFAULT_TOLERANCE);
+    let feather = feather.unwrap_or(DEFAULT_FEATHER).min(MAX_FEATHER);
+    let flood_fill = flood_fill.unwrap_or(true);
+    let output_format = output_format.unwrap_or(AlphaFormat::Png);
+    let result = tokio::task::spawn_blocking(move || {
+        let rgba = img.to_rgba8();
+        let key = key.unwrap_or_else(|| corner_color(&rgba));
+        let (output, removed) = cut_out_background(&rgba, key, tolerance, feather, flood_fill);
+        let share = removed as f64 * 100.0 / (rgba.width() as f64 * rgba.height() as f64);
+        let bytes = encode_image(
+            DynamicImage::ImageRgba8(output),
+            output_format.output_format(),
+        )?;
+        Ok::<_, Error>((bytes, key, share))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Background removal task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (output_bytes, key, share) = match result {
+        Ok(output) => output,
+        Err(e) => {
+            let embed = error_embed("❌ Background Removal Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(output_bytes.len(), "Cut-out image").map_err(Error::from)?;
+
+    let embed = CreateEmbed::new()
+        .title("✅ Background Removed")
+        .description(format!(
+            "**File:** `{}`\n**Removed colour:** `#{:02x}{:02x}{:02x}`\n**Transparent area:** {:.1}%",
+            file.filename, key[0], key[1], key[2], share
+        ))
+        .color(0x27ae60)
+        .footer(CreateEmbedFooter::new(format!(
+            "Tolerance: {} • Feather: {}px • {}",
+            tolerance,
+            feather,
+            if flood_fill {
+                "Connected to corners only"
+            } else {
+                "Every matching pixel"
+            }
+        )));
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                output_bytes,
+                output_filename(&file, "cutout", output_format),
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Pad an image to an aspect ratio or exact size
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn pad_image(
+    ctx: Context<'_>,
+    #[description = "Image to pad"] file: Attachment,
+    #[description = "Target aspect ratio such as 1:1, 16:9 or 4:5 (default 1:1)"] aspect: Option<
+        String,
+    >,
+    #[description = "Exact output width (use with height instead of aspect)"]
+    #[min = 1]
+    #[max = 16384]
+    width: Option<u32>,
+    #[description = "Exact output height (use with width instead of aspect)"]
+    #[min = 1]
+    #[max = 16384]
+    height: Option<u32>,
+    #[description = "Fill colour such as #000000, or `transparent` (default transparent)"]
+    color: Option<String>,
+    #[description = "Output format (default PNG)"] output_format: Option<AlphaFormat>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let fill = match color.as_deref().map(str::trim) {
+        None => Some([0, 0, 0, 0]),
+        Some(color) if color.eq_ignore_ascii_case("transparent") => Some([0, 0, 0, 0]),
+        Some(color) => parse_hex_color(color),
+    };
+    let target = match (aspect.as_deref(), width, height) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            Err("Use either `aspect` or `width` and `height`, not both.")
+        }
+        (None, Some(width), Some(height)) => Ok((None, Some((width, height)))),
+        (None, Some(_), None) | (None, None, Some(_)) => {
+            Err("Set both `width` and `height` to pad to an exact size.")
+        }
+        (Some(aspect), None, None) => parse_aspect(aspect)
+            .map(|ratio| (Some(ratio), None))
+            .ok_or("Aspect ratio must look like `16:9`, `4/5` or `1.5`."),
+        (None, None, None) => Ok((Some(1.0), None)),
+    };
+    let (ratio, size, fill) = match (target, fill) {
+        (Ok((ratio, size)), Some(fill)) => (ratio, size, fill),
+        (Err(message), _) => {
+            let embed = error_embed("❌ Invalid Padding", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+        (_, None) => {
+            let embed = error_embed(
+                "❌ Invalid Colour",
+                "Colour must be a hex value such as `#000000` or `transparent`.",
+            );
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let img = match download_image(&file, None).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ Padding Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let output_format = output_format.unwrap_or(AlphaFormat::Png);
+    let result = tokio::task::spawn_blocking(move || {
+        let rgba = img.to_rgba8();
+        let (canvas_width, canvas_height) = match (size, ratio) {
+            (Some(size), _) => size,
+            (None, Some(ratio)) => aspect_canvas(rgba.width(), rgba.height(), ratio),
+            (None, None) => rgba.dimensions(),
+        };
+        validate_image_dimensions(canvas_width, canvas_height).map_err(Error::from)?;
+        let padded = pad_image_to(&rgba, canvas_width, canvas_height, fill)?;
+        let bytes = encode_image(
+            DynamicImage::ImageRgba8(padded),
+            output_format.output_format(),
+        )?;
+        Ok::<_, Error>((bytes, rgba.dimensions(), (canvas_width, canvas_height)))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Padding task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (output_bytes, (original_width, original_height), (canvas_width, canvas_height)) =
+        match result {
+            Ok(output) => output,
+            Err(e) => {
+                let embed = error_embed("❌ Padding Failed", e.to_string());
+                ctx.send(poise::CreateReply::default().embed(embed)).await?;
+                return Ok(());
+            }
+        };
+    validate_output_size(output_bytes.len(), "Padded image").map_err(Error::from)?;
+
+    let embed = CreateEmbed::new()
+        .title("✅ Image Padded")
+        .description(format!(
+            "**File:** `{}`\n**Original:** {}×{}\n**Padded:** {}×{}",
+            file.filename, original_width, original_height, canvas_width, canvas_height
+        ))
+        .color(0x27ae60);
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                output_bytes,
+                output_filename(&file, "padded", output_format),
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{aspect_canvas, corner_color, cut_out_background, pad_image_to, parse_aspect};
+    use image::{Rgba, RgbaImage};
+
+    /// A white image with a black ring around a white centre.
+    fn ring() -> RgbaImage {
+        RgbaImage::from_fn(9, 9, |x, y| {
+            let edge = x.abs_diff(4).max(y.abs_diff(4));
+            if edge == 2 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn flood_fill_keeps_enclosed_background_colour() {
+        let img = ring();
+        assert_eq!(corner_color(&img), [255, 255, 255]);
+
+        let (filled, removed) = cut_out_background(&img, [255, 255, 255], 10, 0, true);
+        assert_eq!(removed, 81 - 25);
+        assert_eq!(filled.get_pixel(0, 0)[3], 0);
+        assert_eq!(filled.get_pixel(4, 4)[3], 255);
+
+        let (keyed, _) = cut_out_background(&img, [255, 255, 255], 10, 0, false);
+        assert_eq!(keyed.get_pixel(4, 4)[3], 0);
+        assert_eq!(keyed.get_pixel(2, 2)[3], 255);
+    }
+
+    #[test]
+    fn feathering_softens_only_kept_edges() {
+        let (feathered, _) = cut_out_background(&ring(), [255, 255, 255], 10, 1, true);
+        assert_eq!(feathered.get_pixel(0, 0)[3], 0);
+        let edge = feathered.get_pixel(2, 4)[3];
+        assert!(edge > 0 && edge < 255, "{}", edge);
+        assert_eq!(feathered.get_pixel(4, 4)[3], 255);
+    }
+
+    #[test]
+    fn aspect_ratios_parse_and_grow_the_short_side() {
+        assert_eq!(parse_aspect("16:9"), Some(16.0 / 9.0));
+        assert_eq!(parse_aspect("4/5"), Some(0.8));
+        assert_eq!(parse_aspect("1.5"), Some(1.5));
+        assert_eq!(parse_aspect("0:1"), None);
+        assert_eq!(parse_aspect("wide"), None);
+
+        assert_eq!(aspect_canvas(300, 200, 1.0), (300, 300));
+        assert_eq!(aspect_canvas(100, 100, 16.0 / 9.0), (178, 100));
+    }
+
+    #[test]
+    fn padding_centres_and_fills() {
+        let img = RgbaImage::from_pixel(4, 2, Rgba([255, 0, 0, 255]));
+        let padded = pad_image_to(&img, 4, 4, [0, 0, 255, 255]).unwrap();
+        assert_eq!(padded.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert_eq!(padded.get_pixel(0, 1).0, [255, 0, 0, 255]);
+
+        let shrunk = pad_image_to(&RgbaImage::new(40, 20), 10, 10, [0; 4]).unwrap();
+        assert_eq!(shrunk.dimensions(), (10, 10));
+    }
+}
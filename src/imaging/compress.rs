@@ -0,0 +1,245 @@
+use crate::attachments::{MAX_TRANSFORMED_BYTES, sanitize_filename, validate_output_size};
+use crate::commands::resize::resize_rgba;
+use crate::conversion::image::{OutputFormat, download_image, encode_image_with_quality};
+use crate::embeds::error_embed;
+use crate::utils::{file_stem, format_file_size, parse_file_size};
+use crate::{Context, Error};
+use image::DynamicImage;
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+const MIN_TARGET_BYTES: u64 = 1024;
+const MIN_QUALITY: u8 = 10;
+const MAX_QUALITY: u8 = 95;
+const MIN_DIMENSION: u32 = 16;
+const MAX_DOWNSCALE_STEPS: usize = 12;
+
+/// The smallest encoding found that fits under the requested size.
+pub struct CompressedImage {
+    pub bytes: Vec<u8>,
+    pub quality: Option<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Parse and bound-check the user supplied target size.
+fn parse_target_size(input: &str) -> Result<usize, Error> {
+    let target = parse_file_size(input).ok_or_else(|| {
+        Error::from("Invalid target size. Use a value such as `200KB`, `1.5MB` or `50000`.")
+    })?;
+
+    if target < MIN_TARGET_BYTES {
+        return Err(Error::from(format!(
+            "The target size must be at least {}.",
+            format_file_size(MIN_TARGET_BYTES)
+        )));
+    }
+
+    Ok(target.min(MAX_TRANSFORMED_BYTES as u64) as usize)
+}
+
+/// Binary-search the highest quality whose encoding fits in `target` bytes.
+fn best_quality_under(
+    img: &DynamicImage,
+    format: OutputFormat,
+    target: usize,
+) -> Result<Option<(u8, Vec<u8>)>, Error> {
+    let (mut low, mut high) = (MIN_QUALITY, MAX_QUALITY);
+    let mut best = None;
+
+    while low <= high {
+        let quality = low + (high - low) / 2;
+        let encoded = encode_image_with_quality(img.clone(), format, quality)?;
+
+        if encoded.len() <= target {
+            best = Some((quality, encoded));
+            low = quality + 1;
+        } else if quality == MIN_QUALITY {
+            break;
+        } else {
+            high = quality - 1;
+        }
+    }
+
+    Ok(best)
+}
+
+/// Shrink an image until its encoding fits in `target` bytes.
+///
+/// Quality is searched first for formats that expose it; dimensions are only
+/// reduced when even the lowest quality is too large.
+pub fn compress_to_target(
+    img: &DynamicImage,
+    format: OutputFormat,
+    target: usize,
+) -> Result<CompressedImage, Error> {
+    let mut current = img.clone();
+
+    for _ in 0..=MAX_DOWNSCALE_STEPS {
+        let (width, height) = (current.width(), current.height());
+
+        let smallest_len = if format.supports_quality() {
+            if let Some((quality, bytes)) = best_quality_under(&current, format, target)? {
+                return Ok(CompressedImage {
+                    bytes,
+                    quality: Some(quality),
+                    width,
+                    height,
+                });
+            }
+            encode_image_with_quality(current.clone(), format, MIN_QUALITY)?.len()
+        } else {
+            let bytes = encode_image_with_quality(current.clone(), format, MAX_QUALITY)?;
+            if bytes.len() <= target {
+                return Ok(CompressedImage {
+                    bytes,
+                    quality: None,
+                    width,
+                    height,
+                });
+            }
+            bytes.len()
+        };
+
+        // Encoded size scales roughly with pixel count, so shrink each side by
+        // the square root of the overshoot, and always by at least 10%.
+        let scale = ((target as f64 / smallest_len as f64).sqrt() * 0.95).min(0.9);
+        let next_width = (f64::from(width) * scale).round() as u32;
+        let next_height = (f64::from(height) * scale).round() as u32;
+        if next_width < MIN_DIMENSION || next_height < MIN_DIMENSION {
+            break;
+        }
+
+        let resized = resize_rgba(&current.to_rgba8(), next_width, next_height)?;
+        current = DynamicImage::ImageRgba8(resized);
+    }
+
+    Err(Error::from(format!(
+        "Unable to compress this image below {} as {}. Try a larger target or a lossy format such as JPEG.",
+        format_file_size(target as u64),
+        format.extension()
+    )))
+}
+
+/// Compress an image to fit a target file size
+#[poise::command(slash_command)]
+pub async fn compress_image(
+    ctx: Context<'_>,
+    #[description = "Image to compress"] file: Attachment,
+    #[description = "Maximum output size, e.g. 200KB or 1.5MB"] target_size: String,
+    #[description = "Output format (defaults to JPEG)"] output_format: Option<OutputFormat>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let target = match parse_target_size(&target_size) {
+        Ok(target) => target,
+        Err(e) => {
+            let embed = error_embed("❌ Invalid Target Size", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let output_format = output_format.unwrap_or(OutputFormat::Jpg);
+
+    let img = match download_image(&file, None).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ Compression Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let (original_width, original_height) = (img.width(), img.height());
+
+    let result =
+        tokio::task::spawn_blocking(move || compress_to_target(&img, output_format, target))
+            .await
+            .map_err(|e| Error::from(format!("Compression task failed: {}", e)))
+            .and_then(|result| result);
+
+    let compressed = match result {
+        Ok(compressed) => compressed,
+        Err(e) => {
+            let embed = error_embed("❌ Compression Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(compressed.bytes.len(), "Compressed image").map_err(Error::from)?;
+
+    let quality = compressed
+        .quality
+        .map_or_else(|| "lossless".to_string(), |quality| quality.to_string());
+    let embed = CreateEmbed::new()
+        .title("✅ Image Compressed")
+        .description(format!(
+            "**Original:** `{}` ({}, {}×{})\n**Compressed:** {} (target {})\n**Dimensions:** {}×{}\n**Quality:** {}",
+            file.filename,
+            format_file_size(u64::from(file.size)),
+            original_width,
+            original_height,
+            format_file_size(compressed.bytes.len() as u64),
+            format_file_size(target as u64),
+            compressed.width,
+            compressed.height,
+            quality
+        ))
+        .color(0x27ae60)
+        .footer(CreateEmbedFooter::new(format!(
+            "Format: {}",
+            output_format.extension()
+        )));
+
+    let filename = format!(
+        "{}_compressed.{}",
+        sanitize_filename(&file_stem(&file.filename)),
+        output_format.extension()
+    );
+    let attachment = CreateAttachment::bytes(compressed.bytes, filename);
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(attachment),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress_to_target, parse_target_size};
+    use crate::conversion::image::OutputFormat;
+    use image::{DynamicImage, Rgb, RgbImage};
+
+    fn noisy_image(size: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(size, size, |x, y| {
+            let seed = x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663);
+            Rgb([seed as u8, (seed >> 8) as u8, (seed >> 16) as u8])
+        }))
+    }
+
+    #[test]
+    fn target_size_rejects_tiny_targets() {
+        assert_eq!(parse_target_size("200KB").unwrap(), 200 * 1024);
+        assert!(parse_target_size("10").is_err());
+        assert!(parse_target_size("big").is_err());
+    }
+
+    #[test]
+    fn jpeg_output_fits_under_target() {
+        let target = 8 * 1024;
+        let result = compress_to_target(&noisy_image(256), OutputFormat::Jpg, target).unwrap();
+        assert!(result.bytes.len() <= target);
+        assert!(result.quality.is_some());
+    }
+
+    #[test]
+    fn lossless_output_is_downscaled_to_fit() {
+        let target = 16 * 1024;
+        let result = compress_to_target(&noisy_image(256), OutputFormat::Png, target).unwrap();
+        assert!(result.bytes.len() <= target);
+        assert!(result.width < 256);
+        assert_eq!(result.quality, None);
+    }
+}
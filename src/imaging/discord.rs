@@ -0,0 +1,513 @@
+use crate::attachments::{
+    sanitize_filename, validate_attachment_size, validate_image_dimensions, validate_output_size,
+};
+use crate::commands::resize::resize_rgba;
+use crate::conversion::image::{OutputFormat, decode_image, encode_image};
+use crate::embeds::error_embed;
+use crate::imaging::metadata::gif_frame_count;
+use crate::imaging::palette::{Dither, quantize};
+use crate::utils::{file_stem, format_file_size};
+use crate::{Context, Error};
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::{AnimationDecoder, Delay, DynamicImage, Frame, ImageDecoder, RgbaImage, imageops};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use std::io::Cursor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Framing {
+    #[name = "Crop to fill"]
+    Crop,
+    #[name = "Pad with transparency"]
+    Pad,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum BannerKind {
+    #[name = "Server banner (960×540)"]
+    Server,
+    #[name = "Profile banner (680×240)"]
+    Profile,
+    #[name = "Invite splash (1920×1080, static)"]
+    InviteSplash,
+}
+
+/// Container used when an asset keeps its animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnimatedFormat {
+    Gif,
+    Apng,
+}
+
+/// Exact size, byte limit and animation support of one kind of Discord asset.
+#[derive(Debug, Clone, Copy)]
+struct AssetPreset {
+    label: &'static str,
+    suffix: &'static str,
+    width: u32,
+    height: u32,
+    max_bytes: usize,
+    animated: Option<AnimatedFormat>,
+}
+
+const EMOJI: AssetPreset = AssetPreset {
+    label: "Emoji",
+    suffix: "emoji",
+    width: 128,
+    height: 128,
+    max_bytes: 256 * 1024,
+    animated: Some(AnimatedFormat::Gif),
+};
+
+const STICKER: AssetPreset = AssetPreset {
+    label: "Sticker",
+    suffix: "sticker",
+    width: 320,
+    height: 320,
+    max_bytes: 512 * 1024,
+    animated: Some(AnimatedFormat::Apng),
+};
+
+impl BannerKind {
+    const fn preset(self) -> AssetPreset {
+        match self {
+            Self::Server => AssetPreset {
+                label: "Server banner",
+                suffix: "banner",
+                width: 960,
+                height: 540,
+                max_bytes: 10 * 1024 * 1024,
+                animated: Some(AnimatedFormat::Gif),
+            },
+            Self::Profile => AssetPreset {
+                label: "Profile banner",
+                suffix: "profile_banner",
+                width: 680,
+                height: 240,
+                max_bytes: 10 * 1024 * 1024,
+                animated: Some(AnimatedFormat::Gif),
+            },
+            Self::InviteSplash => AssetPreset {
+                label: "Invite splash",
+                suffix: "splash",
+                width: 1920,
+                height: 1080,
+                max_bytes: 10 * 1024 * 1024,
+                animated: None,
+            },
+        }
+    }
+}
+
+/// Frames decoded from an animation beyond this are dropped
+const MAX_FRAMES: usize = 200;
+/// Palette sizes tried, largest first, when an asset is over its byte limit
+const COLOR_STEPS: [usize; 5] = [256, 128, 64, 32, 16];
+/// Keep every Nth frame, tried in order, when reducing colours is not enough
+const FRAME_STEPS: [usize; 4] = [1, 2, 3, 4];
+
+/// A fitted frame and its delay in milliseconds
+type TimedFrame = (RgbaImage, u32);
+
+/// A finished asset and how it was reduced to fit.
+struct PreparedAsset {
+    bytes: Vec<u8>,
+    extension: &'static str,
+    frames: Option<(usize, usize)>,
+    colors: Option<usize>,
+}
+
+/// Scale an image onto a `width`×`height` canvas, cropping or padding to keep its aspect.
+pub fn fit_to_canvas(
+    img: &RgbaImage,
+    width: u32,
+    height: u32,
+    framing: Framing,
+) -> Result<RgbaImage, Error> {
+    let (source_width, source_height) = (f64::from(img.width()), f64::from(img.height()));
+    let (scale_x, scale_y) = (
+        f64::from(width) / source_width,
+        f64::from(height) / source_height,
+    );
+    let scale = match framing {
+        Framing::Crop => scale_x.max(scale_y),
+        Framing::Pad => scale_x.min(scale_y),
+    };
+    let scaled_width = ((source_width * scale).round() as u32).max(1);
+    let scaled_height = ((source_height * scale).round() as u32).max(1);
+
+    let resized = if (scaled_width, scaled_height) == img.dimensions() {
+        img.clone()
+    } else {
+        resize_rgba(img, scaled_width, scaled_height)?
+    };
+
+    let mut canvas = RgbaImage::new(width, height);
+    let x = (i64::from(width) - i64::from(scaled_width)) / 2;
+    let y = (i64::from(height) - i64::from(scaled_height)) / 2;
+    imageops::replace(&mut canvas, &resized, x, y);
+    Ok(canvas)
+}
+
+/// Decode every frame of an animated GIF, fitted to the preset as it is read.
+///
+/// Returns `None` for GIFs with a single frame.
+fn decode_animation(
+    data: &[u8],
+    preset: AssetPreset,
+    framing: Framing,
+) -> Result<Option<(Vec<TimedFrame>, usize)>, Error> {
+    let decoder = GifDecoder::new(Cursor::new(data))
+        .map_err(|e| Error::from(format!("Failed to read GIF: {}", e)))?;
+    let (width, height) = decoder.dimensions();
+    validate_image_dimensions(width, height).map_err(Error::from)?;
+
+    let mut frames = Vec::new();
+    for frame in decoder.into_frames().take(MAX_FRAMES) {
+        let frame = frame.map_err(|e| Error::from(format!("Failed to read GIF frame: {}", e)))?;
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        let delay_ms = numerator / denominator.max(1);
+        let fitted = fit_to_canvas(frame.buffer(), preset.width, preset.height, framing)?;
+        frames.push((fitted, delay_ms));
+    }
+
+    // Frames past the limit are counted from the block structure, not decoded.
+    let total = gif_frame_count(data).unwrap_or(0).max(frames.len());
+    Ok((frames.len() > 1).then_some((frames, total)))
+}
+
+/// Keep every `step`th frame, folding the delays of dropped frames into the kept ones.
+fn drop_frames(frames: &[TimedFrame], step: usize) -> Vec<TimedFrame> {
+    frames
+        .chunks(step)
+        .map(|chunk| {
+            let delay = chunk.iter().map(|(_, delay)| delay).sum();
+            (chunk[0].0.clone(), delay)
+        })
+        .collect()
+}
+
+fn encode_gif(frames: &[TimedFrame]) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut buf);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .and_then(|()| {
+                encoder.encode_frames(frames.iter().map(|(image, delay)| {
+                    Frame::from_parts(image.clone(), 0, 0, Delay::from_numer_denom_ms(*delay, 1))
+                }))
+            })
+            .map_err(|e| Error::from(format!("Failed to encode GIF: {}", e)))?;
+    }
+    Ok(buf)
+}
+
+fn encode_apng(frames: &[TimedFrame]) -> Result<Vec<u8>, Error> {
+    fn png_error(e: png::EncodingError) -> Error {
+        Error::from(format!("Failed to encode APNG: {}", e))
+    }
+
+    let (width, height) = frames[0].0.dimensions();
+    let mut buf = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buf, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Best);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(png_error)?;
+
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    for (image, delay) in frames {
+        writer
+            .set_frame_delay((*delay).min(u32::from(u16::MAX)) as u16, 1000)
+            .map_err(png_error)?;
+        writer.write_image_data(image.as_raw()).map_err(png_error)?;
+    }
+    writer.finish().map_err(png_error)?;
+    Ok(buf)
+}
+
+/// Encode frames, dropping colours and then frames until they fit the preset.
+fn fit_animation(
+    frames: &[TimedFrame],
+    preset: AssetPreset,
+    format: AnimatedFormat,
+) -> Result<(Vec<u8>, usize, usize), Error> {
+    for step in FRAME_STEPS {
+        let kept = drop_frames(frames, step);
+        for colors in COLOR_STEPS {
+            // Ordered dithering is stable between frames, so it does not shimmer
+            let quantized: Vec<TimedFrame> = kept
+                .iter()
+                .map(|(image, delay)| (quantize(image, colors, Dither::Ordered), *delay))
+                .collect();
+            let bytes = match format {
+                AnimatedFormat::Gif => encode_gif(&quantized)?,
+                AnimatedFormat::Apng => encode_apng(&quantized)?,
+            };
+            if bytes.len() <= preset.max_bytes {
+                return Ok((bytes, kept.len(), colors));
+            }
+        }
+    }
+
+    Err(Error::from(format!(
+        "The animation does not fit under {} even with fewer colours and frames. Try a shorter clip or set `animated:False`.",
+        format_file_size(preset.max_bytes as u64)
+    )))
+}
+
+/// Encode a still image as PNG, reducing colours if it is over the byte limit.
+fn fit_still(image: RgbaImage, preset: AssetPreset) -> Result<(Vec<u8>, Option<usize>), Error> {
+    let bytes = encode_image(DynamicImage::ImageRgba8(image.clone()), OutputFormat::Png)?;
+    if bytes.len() <= preset.max_bytes {
+        return Ok((bytes, None));
+    }
+
+    for colors in COLOR_STEPS {
+        let quantized = quantize(&image, colors, Dither::FloydSteinberg);
+        let bytes = encode_image(DynamicImage::ImageRgba8(quantized), OutputFormat::Png)?;
+        if bytes.len() <= preset.max_bytes {
+            return Ok((bytes, Some(colors)));
+        }
+    }
+
+    Err(Error::from(format!(
+        "The image does not fit under {} even with reduced colours.",
+        format_file_size(preset.max_bytes as u64)
+    )))
+}
+
+/// Turn uploaded bytes into an asset that meets the preset's constraints.
+fn prepare_asset(
+    data: &[u8],
+    preset: AssetPreset,
+    framing: Framing,
+    keep_animation: bool,
+) -> Result<PreparedAsset, Error> {
+    if let Some(format) = preset
+        .animated
+        .filter(|_| keep_animation && data.starts_with(b"GIF8"))
+        && let Some((frames, total)) = decode_animation(data, preset, framing)?
+    {
+        let (bytes, kept, colors) = fit_animation(&frames, preset, format)?;
+        return Ok(PreparedAsset {
+            bytes,
+            extension: match format {
+                AnimatedFormat::Gif => "gif",
+                AnimatedFormat::Apng => "png",
+            },
+            frames: Some((kept, total)),
+            colors: Some(colors),
+        });
+    }
+
+    let img = decode_image(data)?;
+    let fitted = fit_to_canvas(&img.to_rgba8(), preset.width, preset.height, framing)?;
+    let (bytes, colors) = fit_still(fitted, preset)?;
+    Ok(PreparedAsset {
+        bytes,
+        extension: "png",
+        frames: None,
+        colors,
+    })
+}
+
+/// Download, fit and send one Discord asset.
+async fn send_asset(
+    ctx: Context<'_>,
+    file: &Attachment,
+    preset: AssetPreset,
+    framing: Framing,
+    keep_animation: bool,
+) -> Result<(), Error> {
+    let failed_title = format!("❌ {} Failed", preset.label);
+    if let Err(message) = validate_attachment_size(file) {
+        let embed = error_embed(&failed_title, message);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let data = match file.download().await {
+        Ok(data) => data,
+        Err(e) => {
+            let embed = error_embed(&failed_title, format!("Failed to download image: {}", e));
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let result =
+        tokio::task::spawn_blocking(move || prepare_asset(&data, preset, framing, keep_animation))
+            .await
+            .map_err(|e| Error::from(format!("{} task failed: {}", preset.label, e)))
+            .and_then(|result| result);
+
+    let asset = match result {
+        Ok(asset) => asset,
+        Err(e) => {
+            let embed = error_embed(&failed_title, e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(asset.bytes.len(), preset.label).map_err(Error::from)?;
+
+    let mut description = format!(
+        "**File:** `{}`\n**Size:** {}×{}\n**File size:** {} of {}",
+        file.filename,
+        preset.width,
+        preset.height,
+        format_file_size(asset.bytes.len() as u64),
+        format_file_size(preset.max_bytes as u64)
+    );
+    if let Some((kept, total)) = asset.frames {
+        description.push_str(&format!("\n**Frames:** {} of {}", kept, total));
+    }
+    if let Some(colors) = asset.colors {
+        description.push_str(&format!("\n**Colours:** {}", colors));
+    }
+
+    let filename = format!(
+        "{}_{}.{}",
+        sanitize_filename(&file_stem(&file.filename)),
+        preset.suffix,
+        asset.extension
+    );
+    let embed = CreateEmbed::new()
+        .title(format!("✅ {} Ready", preset.label))
+        .description(description)
+        .color(0x27ae60)
+        .footer(CreateEmbedFooter::new(match framing {
+            Framing::Crop => "Cropped to fill",
+            Framing::Pad => "Padded with transparency",
+        }));
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(asset.bytes, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Prepare an image as a 128×128 Discord emoji under 256 KB
+#[poise::command(slash_command)]
+pub async fn make_emoji(
+    ctx: Context<'_>,
+    #[description = "Image or animated GIF to turn into an emoji"] file: Attachment,
+    #[description = "Crop to fill the square or pad with transparency (default pad)"]
+    framing: Option<Framing>,
+    #[description = "Keep GIF animation (default true)"] animated: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    send_asset(
+        ctx,
+        &file,
+        EMOJI,
+        framing.unwrap_or(Framing::Pad),
+        animated.unwrap_or(true),
+    )
+    .await
+}
+
+/// Prepare an image as a 320×320 Discord sticker under 512 KB
+#[poise::command(slash_command)]
+pub async fn make_sticker(
+    ctx: Context<'_>,
+    #[description = "Image or animated GIF to turn into a sticker"] file: Attachment,
+    #[description = "Crop to fill the square or pad with transparency (default pad)"]
+    framing: Option<Framing>,
+    #[description = "Convert GIF animation to APNG (default true)"] animated: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    send_asset(
+        ctx,
+        &file,
+        STICKER,
+        framing.unwrap_or(Framing::Pad),
+        animated.unwrap_or(true),
+    )
+    .await
+}
+
+/// Prepare an image as a Discord server or profile banner
+#[poise::command(slash_command)]
+pub async fn make_banner(
+    ctx: Context<'_>,
+    #[description = "Image or animated GIF to turn into a banner"] file: Attachment,
+    #[description = "Banner type (default server banner)"] kind: Option<BannerKind>,
+    #[description = "Crop to fill the banner or pad with transparency (default crop)"]
+    framing: Option<Framing>,
+    #[description = "Keep GIF animation where Discord allows it (default true)"] animated: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+    send_asset(
+        ctx,
+        &file,
+        kind.unwrap_or(BannerKind::Server).preset(),
+        framing.unwrap_or(Framing::Crop),
+        animated.unwrap_or(true),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EMOJI, Framing, STICKER, TimedFrame, encode_gif, fit_to_canvas, prepare_asset};
+    use image::{Rgba, RgbaImage};
+
+    fn animation(frames: usize) -> Vec<u8> {
+        let frames: Vec<TimedFrame> = (0..frames)
+            .map(|index| {
+                let shade = (index * 40) as u8;
+                (
+                    RgbaImage::from_pixel(200, 100, Rgba([shade, 0, 255 - shade, 255])),
+                    50,
+                )
+            })
+            .collect();
+        encode_gif(&frames).unwrap()
+    }
+
+    #[test]
+    fn crop_fills_and_pad_letterboxes() {
+        let wide = RgbaImage::from_pixel(200, 100, Rgba([255, 0, 0, 255]));
+
+        let cropped = fit_to_canvas(&wide, 64, 64, Framing::Crop).unwrap();
+        assert_eq!(cropped.dimensions(), (64, 64));
+        assert_eq!(cropped.get_pixel(0, 0)[3], 255);
+
+        let padded = fit_to_canvas(&wide, 64, 64, Framing::Pad).unwrap();
+        assert_eq!(padded.dimensions(), (64, 64));
+        assert_eq!(padded.get_pixel(0, 0)[3], 0);
+        assert_eq!(padded.get_pixel(32, 32)[3], 255);
+    }
+
+    #[test]
+    fn animated_emoji_stays_a_gif_under_the_limit() {
+        let asset = prepare_asset(&animation(4), EMOJI, Framing::Pad, true).unwrap();
+        assert_eq!(asset.extension, "gif");
+        assert_eq!(asset.frames, Some((4, 4)));
+        assert!(asset.bytes.len() <= EMOJI.max_bytes);
+
+        let decoded = image::load_from_memory(&asset.bytes).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (128, 128));
+    }
+
+    #[test]
+    fn animated_sticker_becomes_apng() {
+        let asset = prepare_asset(&animation(3), STICKER, Framing::Crop, true).unwrap();
+        assert_eq!(asset.extension, "png");
+        assert!(asset.bytes.windows(4).any(|chunk| chunk == b"acTL"));
+
+        let still = prepare_asset(&animation(3), STICKER, Framing::Crop, false).unwrap();
+        assert!(still.frames.is_none());
+        assert!(!still.bytes.windows(4).any(|chunk| chunk == b"acTL"));
+    }
+}
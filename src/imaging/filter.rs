@@ -0,0 +1,284 @@
+use crate::attachments::{sanitize_filename, validate_output_size};
+use crate::commands::resize::output_format_for_filename;
+use crate::conversion::image::{download_image, encode_image};
+use crate::embeds::error_embed;
+use crate::utils::file_stem;
+use crate::{Context, Error};
+use image::{DynamicImage, Rgba};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum FilterOperation {
+    #[name = "Grayscale"]
+    Grayscale,
+    #[name = "Gaussian blur (strength = sigma)"]
+    Blur,
+    #[name = "Sharpen (unsharp mask, strength = sigma)"]
+    Sharpen,
+    #[name = "Brightness (-255 to 255)"]
+    Brightness,
+    #[name = "Contrast (-100 to 100)"]
+    Contrast,
+    #[name = "Hue rotate (degrees)"]
+    HueRotate,
+    #[name = "Saturation (percent, 100 = unchanged)"]
+    Saturation,
+    #[name = "Invert"]
+    Invert,
+    #[name = "Sepia (intensity 0 to 100)"]
+    Sepia,
+    #[name = "Threshold (level 0 to 255)"]
+    Threshold,
+}
+
+impl FilterOperation {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Grayscale => "Grayscale",
+            Self::Blur => "Gaussian blur",
+            Self::Sharpen => "Sharpen",
+            Self::Brightness => "Brightness",
+            Self::Contrast => "Contrast",
+            Self::HueRotate => "Hue rotate",
+            Self::Saturation => "Saturation",
+            Self::Invert => "Invert",
+            Self::Sepia => "Sepia",
+            Self::Threshold => "Threshold",
+        }
+    }
+
+    /// Default strength and the inclusive range users may pick from.
+    ///
+    /// Returns `None` for operations that take no strength.
+    const fn strength_range(self) -> Option<(f32, f32, f32)> {
+        match self {
+            Self::Grayscale | Self::Invert => None,
+            Self::Blur => Some((2.0, 0.1, 50.0)),
+            Self::Sharpen => Some((1.5, 0.1, 20.0)),
+            Self::Brightness => Some((30.0, -255.0, 255.0)),
+            Self::Contrast => Some((20.0, -100.0, 100.0)),
+            Self::HueRotate => Some((90.0, -360.0, 360.0)),
+            Self::Saturation => Some((150.0, 0.0, 400.0)),
+            Self::Sepia => Some((100.0, 0.0, 100.0)),
+            Self::Threshold => Some((128.0, 0.0, 255.0)),
+        }
+    }
+}
+
+/// Intensity below which the unsharp mask leaves pixels untouched
+const SHARPEN_THRESHOLD: i32 = 1;
+
+/// Resolve the strength for an operation, applying its default and range.
+fn filter_strength(operation: FilterOperation, strength: Option<f64>) -> Result<f32, String> {
+    let Some((default, min, max)) = operation.strength_range() else {
+        return Ok(0.0);
+    };
+
+    let value = strength.map_or(default, |value| value as f32);
+    if !value.is_finite() || value < min || value > max {
+        return Err(format!(
+            "{} strength must be between {} and {}.",
+            operation.label(),
+            min,
+            max
+        ));
+    }
+
+    Ok(value)
+}
+
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+fn map_rgba(img: &DynamicImage, mut f: impl FnMut(f32, f32, f32) -> [f32; 3]) -> DynamicImage {
+    let mut rgba = img.to_rgba8();
+    for pixel in rgba.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let [r, g, b] = f(f32::from(r), f32::from(g), f32::from(b));
+        *pixel = Rgba([
+            r.round().clamp(0.0, 255.0) as u8,
+            g.round().clamp(0.0, 255.0) as u8,
+            b.round().clamp(0.0, 255.0) as u8,
+            a,
+        ]);
+    }
+    DynamicImage::ImageRgba8(rgba)
+}
+
+/// Scale each pixel's distance from its luma; 0 is grayscale and 1 unchanged.
+fn saturate(img: &DynamicImage, factor: f32) -> DynamicImage {
+    map_rgba(img, |r, g, b| {
+        let y = luma(r, g, b);
+        [
+            y + (r - y) * factor,
+            y + (g - y) * factor,
+            y + (b - y) * factor,
+        ]
+    })
+}
+
+/// Blend the classic sepia tone matrix with the original by `intensity` (0–1).
+fn sepia(img: &DynamicImage, intensity: f32) -> DynamicImage {
+    map_rgba(img, |r, g, b| {
+        let toned = [
+            0.393 * r + 0.769 * g + 0.189 * b,
+            0.349 * r + 0.686 * g + 0.168 * b,
+            0.272 * r + 0.534 * g + 0.131 * b,
+        ];
+        [
+            r + (toned[0] - r) * intensity,
+            g + (toned[1] - g) * intensity,
+            b + (toned[2] - b) * intensity,
+        ]
+    })
+}
+
+/// Turn pixels black or white depending on whether their luma reaches `level`.
+fn threshold(img: &DynamicImage, level: f32) -> DynamicImage {
+    map_rgba(img, |r, g, b| {
+        let value = if luma(r, g, b) >= level { 255.0 } else { 0.0 };
+        [value; 3]
+    })
+}
+
+/// Apply a filter operation.
+fn apply_filter(img: DynamicImage, operation: FilterOperation, strength: f32) -> DynamicImage {
+    match operation {
+        FilterOperation::Grayscale => img.grayscale(),
+        FilterOperation::Blur => img.blur(strength),
+        FilterOperation::Sharpen => img.unsharpen(strength, SHARPEN_THRESHOLD),
+        FilterOperation::Brightness => img.brighten(strength.round() as i32),
+        FilterOperation::Contrast => img.adjust_contrast(strength),
+        FilterOperation::HueRotate => img.huerotate(strength.round() as i32),
+        FilterOperation::Saturation => saturate(&img, strength / 100.0),
+        FilterOperation::Invert => {
+            let mut img = img;
+            img.invert();
+            img
+        }
+        FilterOperation::Sepia => sepia(&img, strength / 100.0),
+        FilterOperation::Threshold => threshold(&img, strength),
+    }
+}
+
+/// Apply an adjustment filter to an image
+#[poise::command(slash_command)]
+pub async fn image_filter(
+    ctx: Context<'_>,
+    #[description = "Image to edit"] file: Attachment,
+    #[description = "Filter to apply"] operation: FilterOperation,
+    #[description = "Filter strength (see the operation name for its range)"] strength: Option<f64>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let strength = match filter_strength(operation, strength) {
+        Ok(strength) => strength,
+        Err(message) => {
+            let embed = error_embed("❌ Invalid Strength", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let img = match download_image(&file, None).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ Filter Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let output_format = output_format_for_filename(&file.filename);
+    let result = tokio::task::spawn_blocking(move || {
+        encode_image(apply_filter(img, operation, strength), output_format)
+    })
+    .await
+    .map_err(|e| Error::from(format!("Filter task failed: {}", e)))
+    .and_then(|result| result);
+
+    let output_bytes = match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let embed = error_embed("❌ Filter Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(output_bytes.len(), "Filtered image").map_err(Error::from)?;
+
+    let mut embed = CreateEmbed::new()
+        .title("✅ Filter Applied")
+        .description(format!(
+            "**File:** `{}`\n**Filter:** {}",
+            file.filename,
+            operation.label()
+        ))
+        .color(0x27ae60);
+    if operation.strength_range().is_some() {
+        embed = embed.footer(CreateEmbedFooter::new(format!("Strength: {}", strength)));
+    }
+
+    let filename = format!(
+        "{}_{}.{}",
+        sanitize_filename(&file_stem(&file.filename)),
+        operation.label().to_ascii_lowercase().replace(' ', "_"),
+        output_format.extension()
+    );
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(output_bytes, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FilterOperation, apply_filter, filter_strength};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    fn pixel_image(rgba: [u8; 4]) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba(rgba)))
+    }
+
+    #[test]
+    fn filter_strength_applies_defaults_and_ranges() {
+        assert_eq!(filter_strength(FilterOperation::Blur, None), Ok(2.0));
+        assert_eq!(filter_strength(FilterOperation::Invert, Some(5.0)), Ok(0.0));
+        assert!(filter_strength(FilterOperation::Brightness, Some(300.0)).is_err());
+        assert!(filter_strength(FilterOperation::Blur, Some(f64::NAN)).is_err());
+    }
+
+    #[test]
+    fn zero_saturation_produces_gray_and_keeps_alpha() {
+        let img = apply_filter(
+            pixel_image([200, 40, 40, 90]),
+            FilterOperation::Saturation,
+            0.0,
+        );
+        let [r, g, b, a] = img.to_rgba8().get_pixel(0, 0).0;
+        assert_eq!((r, r), (g, b));
+        assert_eq!(a, 90);
+    }
+
+    #[test]
+    fn threshold_outputs_black_or_white() {
+        let bright = apply_filter(
+            pixel_image([220, 220, 220, 255]),
+            FilterOperation::Threshold,
+            128.0,
+        );
+        let dark = apply_filter(
+            pixel_image([20, 20, 20, 255]),
+            FilterOperation::Threshold,
+            128.0,
+        );
+        assert_eq!(bright.to_rgba8().get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(dark.to_rgba8().get_pixel(0, 0).0, [0, 0, 0, 255]);
+    }
+}
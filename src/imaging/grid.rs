@@ -0,0 +1,596 @@
+use crate::attachments::{
+    sanitize_filename, validate_combined_attachment_size, validate_image_dimensions,
+    validate_output_size,
+};
+use crate::commands::resize::resize_rgba;
+use crate::compression::compress::zip_entries;
+use crate::conversion::image::{OutputFormat, download_image, encode_image};
+use crate::embeds::error_embed;
+use crate::utils::{file_stem, format_file_size, parse_hex_color};
+use crate::{Context, Error};
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+const DEFAULT_SPACING: u32 = 8;
+const DEFAULT_BACKGROUND: &str = "#ffffff";
+/// Upper bound for the automatically chosen collage cell size
+const MAX_DEFAULT_CELL: u32 = 1024;
+const MAX_TILES: usize = 1024;
+
+/// Where each frame lands in a packed sheet, plus the sheet size.
+struct Packing {
+    positions: Vec<(u32, u32)>,
+    width: u32,
+    height: u32,
+}
+
+/// Collect the optional attachment parameters of a multi-image command.
+fn uploaded_files(first: Attachment, rest: [Option<Attachment>; 9]) -> Vec<Attachment> {
+    std::iter::once(first)
+        .chain(rest.into_iter().flatten())
+        .collect()
+}
+
+async fn download_images(files: &[Attachment]) -> Result<Vec<DynamicImage>, Error> {
+    validate_combined_attachment_size(files).map_err(Error::from)?;
+
+    let mut images = Vec::with_capacity(files.len());
+    for file in files {
+        let img = download_image(file, None)
+            .await
+            .map_err(|e| Error::from(format!("`{}`: {}", file.filename, e)))?;
+        images.push(img);
+    }
+    Ok(images)
+}
+
+/// Default column count that keeps a grid roughly square.
+fn default_columns(count: usize) -> u32 {
+    (count as f64).sqrt().ceil().max(1.0) as u32
+}
+
+/// Median of the images' longest sides, so one huge upload does not blow up
+/// every cell.
+fn default_cell_size(images: &[DynamicImage]) -> u32 {
+    let mut sides: Vec<u32> = images
+        .iter()
+        .map(|img| img.width().max(img.height()))
+        .collect();
+    sides.sort_unstable();
+    sides
+        .get(sides.len() / 2)
+        .copied()
+        .unwrap_or(1)
+        .clamp(1, MAX_DEFAULT_CELL)
+}
+
+/// Scale an image to a square cell, either letterboxed or cropped to fill it.
+fn scale_to_cell(img: &DynamicImage, cell: u32, crop: bool) -> Result<RgbaImage, Error> {
+    let (width, height) = (f64::from(img.width()), f64::from(img.height()));
+    let (scale_x, scale_y) = (f64::from(cell) / width, f64::from(cell) / height);
+    let scale = if crop {
+        scale_x.max(scale_y)
+    } else {
+        scale_x.min(scale_y)
+    };
+    let scaled_width = ((width * scale).round() as u32).max(1);
+    let scaled_height = ((height * scale).round() as u32).max(1);
+    validate_image_dimensions(scaled_width, scaled_height).map_err(Error::from)?;
+
+    let resized = resize_rgba(&img.to_rgba8(), scaled_width, scaled_height)?;
+    if !crop {
+        return Ok(resized);
+    }
+
+    let x = scaled_width.saturating_sub(cell) / 2;
+    let y = scaled_height.saturating_sub(cell) / 2;
+    Ok(imageops::crop_imm(
+        &resized,
+        x,
+        y,
+        cell.min(scaled_width),
+        cell.min(scaled_height),
+    )
+    .to_image())
+}
+
+/// Arrange images into a grid of equal square cells.
+fn build_collage(
+    images: &[DynamicImage],
+    columns: u32,
+    cell: u32,
+    spacing: u32,
+    background: [u8; 4],
+    crop: bool,
+) -> Result<RgbaImage, Error> {
+    let columns = columns.clamp(1, images.len().max(1) as u32);
+    let rows = (images.len() as u32).div_ceil(columns);
+    let span = |count: u32| -> Result<u32, Error> {
+        count
+            .checked_mul(cell)
+            .and_then(|cells| cells.checked_add((count + 1).checked_mul(spacing)?))
+            .ok_or_else(|| Error::from("The collage would be too large."))
+    };
+    let (width, height) = (span(columns)?, span(rows)?);
+    validate_image_dimensions(width, height).map_err(Error::from)?;
+
+    let mut canvas = RgbaImage::from_pixel(width, height, Rgba(background));
+    for (index, img) in images.iter().enumerate() {
+        let (column, row) = (index as u32 % columns, index as u32 / columns);
+        let tile = scale_to_cell(img, cell, crop)?;
+        let x = spacing + column * (cell + spacing) + (cell - tile.width()) / 2;
+        let y = spacing + row * (cell + spacing) + (cell - tile.height()) / 2;
+        imageops::overlay(&mut canvas, &tile, i64::from(x), i64::from(y));
+    }
+
+    Ok(canvas)
+}
+
+/// Shelf-pack frames row by row, `columns` frames per row, keeping their
+/// original sizes.
+fn pack_frames(sizes: &[(u32, u32)], columns: u32, padding: u32) -> Packing {
+    let columns = columns.max(1) as usize;
+    let mut positions = Vec::with_capacity(sizes.len());
+    let (mut width, mut y) = (0, padding);
+
+    for row in sizes.chunks(columns) {
+        let mut x = padding;
+        for &(frame_width, _) in row {
+            positions.push((x, y));
+            x += frame_width + padding;
+        }
+        width = width.max(x);
+        y += row.iter().map(|&(_, height)| height).max().unwrap_or(0) + padding;
+    }
+
+    Packing {
+        positions,
+        width: width.max(1),
+        height: y.max(1),
+    }
+}
+
+/// Frame map in the widely supported TexturePacker JSON hash format.
+fn frame_map(names: &[String], sizes: &[(u32, u32)], packing: &Packing, image: &str) -> String {
+    let frames: serde_json::Map<String, serde_json::Value> = names
+        .iter()
+        .zip(sizes)
+        .zip(&packing.positions)
+        .map(|((name, &(w, h)), &(x, y))| {
+            (
+                name.clone(),
+                serde_json::json!({
+                    "frame": { "x": x, "y": y, "w": w, "h": h },
+                    "rotated": false,
+                    "trimmed": false,
+                    "spriteSourceSize": { "x": 0, "y": 0, "w": w, "h": h },
+                    "sourceSize": { "w": w, "h": h }
+                }),
+            )
+        })
+        .collect();
+
+    let map = serde_json::json!({
+        "frames": frames,
+        "meta": {
+            "image": image,
+            "format": "RGBA8888",
+            "size": { "w": packing.width, "h": packing.height },
+            "scale": "1"
+        }
+    });
+    serde_json::to_string_pretty(&map).unwrap_or_default()
+}
+
+/// Unique frame names derived from the uploaded filenames.
+fn frame_names(files: &[Attachment]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(files.len());
+    for (index, file) in files.iter().enumerate() {
+        let name = sanitize_filename(&file.filename);
+        if names.contains(&name) {
+            names.push(format!("{}_{}", index + 1, name));
+        } else {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Number of tile columns and rows that fit in a sheet.
+fn tile_grid(sheet: (u32, u32), cell: (u32, u32), margin: u32, spacing: u32) -> (u32, u32) {
+    let axis = |length: u32, cell: u32| -> u32 {
+        let (length, cell, margin) = (u64::from(length), u64::from(cell), u64::from(margin));
+        if margin + cell > length {
+            return 0;
+        }
+        (1 + (length - margin - cell) / (cell + u64::from(spacing))) as u32
+    };
+
+    (axis(sheet.0, cell.0), axis(sheet.1, cell.1))
+}
+
+/// Top-left corners of every `cell_width`×`cell_height` tile in a sheet.
+fn tile_origins(sheet: (u32, u32), cell: (u32, u32), margin: u32, spacing: u32) -> Vec<(u32, u32)> {
+    let (columns, rows) = tile_grid(sheet, cell, margin, spacing);
+    (0..rows)
+        .flat_map(|row| {
+            (0..columns).map(move |column| {
+                (
+                    margin + column * (cell.0 + spacing),
+                    margin + row * (cell.1 + spacing),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Arrange images into a grid collage
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn collage(
+    ctx: Context<'_>,
+    #[description = "First image"] image1: Attachment,
+    #[description = "Second image"] image2: Option<Attachment>,
+    #[description = "Third image"] image3: Option<Attachment>,
+    #[description = "Fourth image"] image4: Option<Attachment>,
+    #[description = "Fifth image"] image5: Option<Attachment>,
+    #[description = "Sixth image"] image6: Option<Attachment>,
+    #[description = "Seventh image"] image7: Option<Attachment>,
+    #[description = "Eighth image"] image8: Option<Attachment>,
+    #[description = "Ninth image"] image9: Option<Attachment>,
+    #[description = "Tenth image"] image10: Option<Attachment>,
+    #[description = "Number of columns (defaults to a square grid)"]
+    #[min = 1]
+    #[max = 10]
+    columns: Option<u32>,
+    #[description = "Cell size in pixels (defaults to the median image size)"]
+    #[min = 16]
+    #[max = 2048]
+    cell_size: Option<u32>,
+    #[description = "Gap between cells in pixels (defaults to 8)"]
+    #[min = 0]
+    #[max = 200]
+    spacing: Option<u32>,
+    #[description = "Background colour as hex, e.g. #ffffff or #00000000"] background: Option<
+        String,
+    >,
+    #[description = "Crop images to fill their cells instead of letterboxing"] crop: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let files = uploaded_files(
+        image1,
+        [
+            image2, image3, image4, image5, image6, image7, image8, image9, image10,
+        ],
+    );
+    let Some(background) = parse_hex_color(background.as_deref().unwrap_or(DEFAULT_BACKGROUND))
+    else {
+        let embed = error_embed(
+            "❌ Invalid Colour",
+            "Background must be a hex value such as `#ffffff`.",
+        );
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let images = match download_images(&files).await {
+        Ok(images) => images,
+        Err(e) => {
+            let embed = error_embed("❌ Collage Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let columns = columns.unwrap_or_else(|| default_columns(images.len()));
+    let cell = cell_size.unwrap_or_else(|| default_cell_size(&images));
+    let spacing = spacing.unwrap_or(DEFAULT_SPACING);
+    let crop = crop.unwrap_or(false);
+
+    let result = tokio::task::spawn_blocking(move || {
+        let canvas = build_collage(&images, columns, cell, spacing, background, crop)?;
+        let dimensions = canvas.dimensions();
+        encode_image(DynamicImage::ImageRgba8(canvas), OutputFormat::Png)
+            .map(|bytes| (dimensions, bytes))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Collage task failed: {}", e)))
+    .and_then(|result| result);
+
+    let ((width, height), output_bytes) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let embed = error_embed("❌ Collage Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(output_bytes.len(), "Collage").map_err(Error::from)?;
+
+    let embed = CreateEmbed::new()
+        .title("✅ Collage Created")
+        .description(format!(
+            "**Images:** {}\n**Cell size:** {}×{}\n**Dimensions:** {}×{}\n**Size:** {}",
+            files.len(),
+            cell,
+            cell,
+            width,
+            height,
+            format_file_size(output_bytes.len() as u64)
+        ))
+        .color(0x27ae60);
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(output_bytes, "collage.png")),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Pack frames into a sprite sheet with a JSON frame map
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn sprite_sheet(
+    ctx: Context<'_>,
+    #[description = "First frame"] frame1: Attachment,
+    #[description = "Second frame"] frame2: Option<Attachment>,
+    #[description = "Third frame"] frame3: Option<Attachment>,
+    #[description = "Fourth frame"] frame4: Option<Attachment>,
+    #[description = "Fifth frame"] frame5: Option<Attachment>,
+    #[description = "Sixth frame"] frame6: Option<Attachment>,
+    #[description = "Seventh frame"] frame7: Option<Attachment>,
+    #[description = "Eighth frame"] frame8: Option<Attachment>,
+    #[description = "Ninth frame"] frame9: Option<Attachment>,
+    #[description = "Tenth frame"] frame10: Option<Attachment>,
+    #[description = "Frames per row (defaults to a square sheet)"]
+    #[min = 1]
+    #[max = 10]
+    columns: Option<u32>,
+    #[description = "Transparent padding between frames in pixels (defaults to 0)"]
+    #[min = 0]
+    #[max = 64]
+    padding: Option<u32>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let files = uploaded_files(
+        frame1,
+        [
+            frame2, frame3, frame4, frame5, frame6, frame7, frame8, frame9, frame10,
+        ],
+    );
+    let images = match download_images(&files).await {
+        Ok(images) => images,
+        Err(e) => {
+            let embed = error_embed("❌ Sprite Sheet Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let columns = columns.unwrap_or_else(|| default_columns(images.len()));
+    let padding = padding.unwrap_or(0);
+    let names = frame_names(&files);
+
+    let result = tokio::task::spawn_blocking(move || {
+        let sizes: Vec<(u32, u32)> = images
+            .iter()
+            .map(|img| (img.width(), img.height()))
+            .collect();
+        let packing = pack_frames(&sizes, columns, padding);
+        validate_image_dimensions(packing.width, packing.height).map_err(Error::from)?;
+
+        let mut sheet = RgbaImage::new(packing.width, packing.height);
+        for (img, &(x, y)) in images.iter().zip(&packing.positions) {
+            imageops::replace(&mut sheet, &img.to_rgba8(), i64::from(x), i64::from(y));
+        }
+        let png = encode_image(DynamicImage::ImageRgba8(sheet), OutputFormat::Png)?;
+        let map = frame_map(&names, &sizes, &packing, "sprite_sheet.png");
+        Ok::<_, Error>((packing.width, packing.height, png, map))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Sprite sheet task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (width, height, png, map) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let embed = error_embed("❌ Sprite Sheet Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(png.len() + map.len(), "Sprite sheet").map_err(Error::from)?;
+
+    let embed = CreateEmbed::new()
+        .title("✅ Sprite Sheet Created")
+        .description(format!(
+            "**Frames:** {}\n**Sheet size:** {}×{}",
+            files.len(),
+            width,
+            height
+        ))
+        .color(0x27ae60)
+        .footer(CreateEmbedFooter::new(
+            "The frame map uses the TexturePacker JSON hash format.",
+        ));
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(png, "sprite_sheet.png"))
+            .attachment(CreateAttachment::bytes(
+                map.into_bytes(),
+                "sprite_sheet.json",
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Cut a sprite sheet into tiles
+#[poise::command(slash_command)]
+pub async fn slice_sheet(
+    ctx: Context<'_>,
+    #[description = "Sprite sheet or tileset to cut up"] file: Attachment,
+    #[description = "Tile width in pixels"]
+    #[min = 1]
+    #[max = 4096]
+    cell_width: u32,
+    #[description = "Tile height in pixels (defaults to the width)"]
+    #[min = 1]
+    #[max = 4096]
+    cell_height: Option<u32>,
+    #[description = "Border around the sheet in pixels (defaults to 0)"]
+    #[min = 0]
+    #[max = 1024]
+    margin: Option<u32>,
+    #[description = "Gap between tiles in pixels (defaults to 0)"]
+    #[min = 0]
+    #[max = 1024]
+    spacing: Option<u32>,
+    #[description = "Keep fully transparent tiles (skipped by default)"] keep_empty: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let cell = (cell_width, cell_height.unwrap_or(cell_width));
+    let margin = margin.unwrap_or(0);
+    let spacing = spacing.unwrap_or(0);
+    let keep_empty = keep_empty.unwrap_or(false);
+
+    let img = match download_image(&file, None).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ Slicing Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let stem = sanitize_filename(&file_stem(&file.filename));
+    let entry_stem = stem.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let sheet = img.to_rgba8();
+        let (columns, rows) = tile_grid(sheet.dimensions(), cell, margin, spacing);
+        let tile_count = columns as usize * rows as usize;
+        if tile_count == 0 {
+            return Err(Error::from(
+                "The tile size is larger than the sheet. Check the cell size and margin.",
+            ));
+        }
+        if tile_count > MAX_TILES {
+            return Err(Error::from(format!(
+                "This would produce {} tiles; at most {} are supported. Use a larger cell size.",
+                tile_count, MAX_TILES
+            )));
+        }
+        let origins = tile_origins(sheet.dimensions(), cell, margin, spacing);
+
+        let width = origins.len().to_string().len();
+        let mut entries = Vec::new();
+        let mut skipped = 0;
+        for (index, &(x, y)) in origins.iter().enumerate() {
+            let tile = imageops::crop_imm(&sheet, x, y, cell.0, cell.1).to_image();
+            if !keep_empty && tile.pixels().all(|pixel| pixel[3] == 0) {
+                skipped += 1;
+                continue;
+            }
+            let png = encode_image(DynamicImage::ImageRgba8(tile), OutputFormat::Png)?;
+            entries.push((
+                format!("{}_{:0width$}.png", entry_stem, index + 1, width = width),
+                png,
+            ));
+        }
+        if entries.is_empty() {
+            return Err(Error::from("Every tile in the sheet is empty."));
+        }
+
+        let archive = zip_entries(&entries)?;
+        Ok::<_, Error>((origins.len(), entries.len(), skipped, archive))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Slicing task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (total, written, skipped, archive) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let embed = error_embed("❌ Slicing Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(archive.len(), "Tile archive").map_err(Error::from)?;
+
+    let mut embed = CreateEmbed::new()
+        .title("✅ Sheet Sliced")
+        .description(format!(
+            "**File:** `{}`\n**Tile size:** {}×{}\n**Tiles:** {} of {}\n**Archive size:** {}",
+            file.filename,
+            cell.0,
+            cell.1,
+            written,
+            total,
+            format_file_size(archive.len() as u64)
+        ))
+        .color(0x27ae60);
+    if skipped > 0 {
+        embed = embed.footer(CreateEmbedFooter::new(format!(
+            "{} fully transparent tiles were skipped.",
+            skipped
+        )));
+    }
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                archive,
+                format!("{}_tiles.zip", stem),
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_collage, default_columns, pack_frames, tile_grid, tile_origins};
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    #[test]
+    fn collage_letterboxes_images_into_uniform_cells() {
+        let wide = DynamicImage::ImageRgba8(RgbaImage::from_pixel(40, 20, Rgba([255, 0, 0, 255])));
+        let tall = DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 40, Rgba([0, 0, 255, 255])));
+        let canvas = build_collage(&[wide, tall], 2, 20, 2, [0, 0, 0, 0], false).unwrap();
+
+        assert_eq!(canvas.dimensions(), (46, 24));
+        // The wide image is scaled to 20×10 and centred vertically.
+        assert_eq!(canvas.get_pixel(12, 12).0, [255, 0, 0, 255]);
+        assert_eq!(canvas.get_pixel(12, 4)[3], 0);
+    }
+
+    #[test]
+    fn frames_are_shelf_packed() {
+        let packing = pack_frames(&[(10, 10), (20, 5), (8, 12)], 2, 1);
+        assert_eq!(packing.positions, vec![(1, 1), (12, 1), (1, 12)]);
+        assert_eq!((packing.width, packing.height), (33, 25));
+        assert_eq!(default_columns(5), 3);
+    }
+
+    #[test]
+    fn tiles_respect_margin_and_spacing() {
+        let origins = tile_origins((37, 20), (16, 16), 2, 1);
+        assert_eq!(origins, vec![(2, 2), (19, 2)]);
+        let origins = tile_origins((35, 16), (16, 8), 1, 1);
+        assert_eq!(origins, vec![(1, 1), (18, 1)]);
+        assert_eq!(tile_grid((16_384, 16_384), (1, 1), 0, 0), (16_384, 16_384));
+        assert_eq!(tile_grid((10, 10), (16, 16), 0, 0), (0, 0));
+    }
+}
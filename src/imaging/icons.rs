@@ -0,0 +1,265 @@
+use crate::attachments::{sanitize_filename, validate_output_size};
+use crate::commands::resize::resize_rgba;
+use crate::compression::compress::zip_entries;
+use crate::conversion::image::{OutputFormat, download_image, encode_image};
+use crate::embeds::error_embed;
+use crate::utils::{file_stem, format_file_size, parse_hex_color};
+use crate::{Context, Error};
+use image::codecs::ico::{IcoEncoder, IcoFrame};
+use image::{ColorType, DynamicImage, RgbaImage, imageops};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+/// Sizes bundled into the multi-resolution `favicon.ico`.
+const ICO_SIZES: [u32; 3] = [16, 32, 48];
+/// Standalone PNG icons for browsers, iOS and PWAs.
+const PNG_ICONS: [(&str, u32); 5] = [
+    ("favicon-16x16.png", 16),
+    ("favicon-32x32.png", 32),
+    ("apple-touch-icon.png", 180),
+    ("android-chrome-192x192.png", 192),
+    ("android-chrome-512x512.png", 512),
+];
+/// Android launcher icons by density bucket.
+const ANDROID_LAUNCHER_ICONS: [(&str, u32); 6] = [
+    ("ldpi", 36),
+    ("mdpi", 48),
+    ("hdpi", 72),
+    ("xhdpi", 96),
+    ("xxhdpi", 144),
+    ("xxxhdpi", 192),
+];
+const LARGEST_ICON: u32 = 512;
+const DEFAULT_THEME_COLOR: &str = "#ffffff";
+
+/// Centre an image on a transparent square canvas.
+fn pad_to_square(img: &DynamicImage) -> RgbaImage {
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    if width == height {
+        return rgba;
+    }
+
+    let side = width.max(height);
+    let mut canvas = RgbaImage::new(side, side);
+    imageops::overlay(
+        &mut canvas,
+        &rgba,
+        i64::from((side - width) / 2),
+        i64::from((side - height) / 2),
+    );
+    canvas
+}
+
+fn icon_png(source: &RgbaImage, size: u32) -> Result<Vec<u8>, Error> {
+    let resized = resize_rgba(source, size, size)?;
+    encode_image(DynamicImage::ImageRgba8(resized), OutputFormat::Png)
+}
+
+/// Build a single ICO file holding every size in [`ICO_SIZES`].
+fn favicon_ico(source: &RgbaImage) -> Result<Vec<u8>, Error> {
+    let encoded = ICO_SIZES
+        .iter()
+        .map(|&size| icon_png(source, size).map(|png| (size, png)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let frames = encoded
+        .iter()
+        .map(|(size, png)| IcoFrame::with_encoded(png.as_slice(), *size, *size, ColorType::Rgba8))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::from(format!("Failed to build favicon: {}", e)))?;
+
+    let mut output = Vec::new();
+    IcoEncoder::new(&mut output)
+        .encode_images(&frames)
+        .map_err(|e| Error::from(format!("Failed to encode favicon: {}", e)))?;
+    Ok(output)
+}
+
+/// Web app manifest referencing the Android Chrome icons.
+fn web_manifest(name: &str, theme_color: &str) -> String {
+    let manifest = serde_json::json!({
+        "name": name,
+        "short_name": name,
+        "icons": [
+            { "src": "/android-chrome-192x192.png", "sizes": "192x192", "type": "image/png" },
+            { "src": "/android-chrome-512x512.png", "sizes": "512x512", "type": "image/png" }
+        ],
+        "theme_color": theme_color,
+        "background_color": theme_color,
+        "display": "standalone"
+    });
+    serde_json::to_string_pretty(&manifest).unwrap_or_default()
+}
+
+/// `<head>` tags wiring up the generated files.
+fn head_snippet(theme_color: &str) -> String {
+    format!(
+        concat!(
+            "<link rel=\"icon\" href=\"/favicon.ico\" sizes=\"any\">\n",
+            "<link rel=\"icon\" type=\"image/png\" sizes=\"32x32\" href=\"/favicon-32x32.png\">\n",
+            "<link rel=\"icon\" type=\"image/png\" sizes=\"16x16\" href=\"/favicon-16x16.png\">\n",
+            "<link rel=\"apple-touch-icon\" sizes=\"180x180\" href=\"/apple-touch-icon.png\">\n",
+            "<link rel=\"manifest\" href=\"/site.webmanifest\">\n",
+            "<meta name=\"theme-color\" content=\"{}\">\n"
+        ),
+        theme_color
+    )
+}
+
+/// Render every icon in the set and return the archive entries.
+fn build_icon_set(
+    img: &DynamicImage,
+    name: &str,
+    theme_color: &str,
+) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    let square = pad_to_square(img);
+
+    let mut entries = vec![("favicon.ico".to_string(), favicon_ico(&square)?)];
+    for (filename, size) in PNG_ICONS {
+        entries.push((filename.to_string(), icon_png(&square, size)?));
+    }
+    for (density, size) in ANDROID_LAUNCHER_ICONS {
+        entries.push((
+            format!("android/mipmap-{}/ic_launcher.png", density),
+            icon_png(&square, size)?,
+        ));
+    }
+    entries.push((
+        "site.webmanifest".to_string(),
+        web_manifest(name, theme_color).into_bytes(),
+    ));
+    entries.push((
+        "head.html".to_string(),
+        head_snippet(theme_color).into_bytes(),
+    ));
+
+    Ok(entries)
+}
+
+/// Generate a favicon and app icon set from one image
+#[poise::command(slash_command)]
+pub async fn make_icons(
+    ctx: Context<'_>,
+    #[description = "Square image to build icons from (other shapes are padded)"] file: Attachment,
+    #[description = "App name used in site.webmanifest"] name: Option<String>,
+    #[description = "Theme colour as hex, e.g. #1e90ff"] theme_color: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let theme_color = theme_color.unwrap_or_else(|| DEFAULT_THEME_COLOR.to_string());
+    let Some([r, g, b, _]) = parse_hex_color(&theme_color) else {
+        let embed = error_embed(
+            "❌ Invalid Colour",
+            "Theme colour must be a hex value such as `#1e90ff`.",
+        );
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+    let theme_color = format!("#{:02x}{:02x}{:02x}", r, g, b);
+    let stem = sanitize_filename(&file_stem(&file.filename));
+    let name = name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| stem.clone());
+
+    let img = match download_image(&file, Some(LARGEST_ICON)).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ Icon Generation Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let (width, height) = (img.width(), img.height());
+
+    let result = tokio::task::spawn_blocking(move || {
+        let entries = build_icon_set(&img, &name, &theme_color)?;
+        let archive = zip_entries(&entries)?;
+        Ok::<_, Error>((entries.len(), archive))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Icon generation task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (file_count, archive) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let embed = error_embed("❌ Icon Generation Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(archive.len(), "Icon archive").map_err(Error::from)?;
+
+    let mut embed = CreateEmbed::new()
+        .title("✅ Icons Generated")
+        .description(format!(
+            "**Source:** `{}` ({}×{})\n**Files:** {}\n**Archive size:** {}",
+            file.filename,
+            width,
+            height,
+            file_count,
+            format_file_size(archive.len() as u64)
+        ))
+        .field(
+            "Contents",
+            "• `favicon.ico` (16, 32, 48)\n• Favicon, Apple touch and Android Chrome PNGs\n• Android launcher icons (ldpi–xxxhdpi)\n• `site.webmanifest` and `head.html` snippet",
+            false,
+        )
+        .color(0x27ae60);
+    if width != height {
+        embed = embed.footer(CreateEmbedFooter::new(
+            "The image was not square, so it was padded with transparency.",
+        ));
+    } else if width < LARGEST_ICON {
+        embed = embed.footer(CreateEmbedFooter::new(format!(
+            "The source is smaller than {}px, so larger icons were upscaled.",
+            LARGEST_ICON
+        )));
+    }
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(
+                archive,
+                format!("{}_icons.zip", stem),
+            )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_icon_set, pad_to_square};
+    use image::{DynamicImage, RgbaImage};
+
+    #[test]
+    fn pad_to_square_centres_the_image() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 2, [255, 0, 0, 255].into()));
+        let square = pad_to_square(&img);
+        assert_eq!(square.dimensions(), (4, 4));
+        assert_eq!(square.get_pixel(0, 0)[3], 0);
+        assert_eq!(square.get_pixel(0, 1)[3], 255);
+    }
+
+    #[test]
+    fn icon_set_contains_multi_size_favicon_and_manifest() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::new(64, 64));
+        let entries = build_icon_set(&img, "Demo", "#ffffff").unwrap();
+
+        let (_, favicon) = entries
+            .iter()
+            .find(|(name, _)| name == "favicon.ico")
+            .unwrap();
+        // The ICONDIR header stores the image count in bytes 4..6.
+        assert_eq!(u16::from_le_bytes([favicon[4], favicon[5]]), 3);
+        assert!(entries.iter().any(|(name, _)| name == "site.webmanifest"));
+        assert!(
+            entries
+                .iter()
+                .any(|(name, _)| name == "android/mipmap-xxxhdpi/ic_launcher.png")
+        );
+    }
+}
@@ -0,0 +1,1084 @@
+use crate::attachments::{sanitize_filename, validate_attachment_size, validate_output_size};
+use crate::conversion::image::decode_image;
+use crate::embeds::{EMBED_SUCCESS_COLOR, EMBED_WARNING_COLOR, error_embed};
+use crate::utils::{file_stem, format_file_size};
+use crate::{Context, Error};
+use exif::{Exif, In, Tag};
+use image::{DynamicImage, ImageFormat, ImageOutputFormat};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+use std::fmt::Write as _;
+use std::io::Cursor;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const JPEG_XMP_EXTENSION_HEADER: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+const JPEG_EXIF_HEADER: &[u8] = b"Exif\0\0";
+const JPEG_ICC_HEADER: &[u8] = b"ICC_PROFILE\0";
+const PHOTOSHOP_HEADER: &[u8] = b"Photoshop 3.0\0";
+const PNG_XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+const IPTC_RESOURCE_ID: u16 = 0x0404;
+
+/// PNG ancillary chunks that carry descriptive metadata rather than pixels.
+const PNG_METADATA_CHUNKS: [&[u8; 4]; 5] = [b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// Everything found in an image's metadata blocks.
+#[derive(Default)]
+struct ImageMetadata {
+    exif: Option<Exif>,
+    xmp: Option<String>,
+    iptc: Vec<(String, String)>,
+    text: Vec<(String, String)>,
+    icc_size: Option<usize>,
+    frames: Option<usize>,
+}
+
+impl ImageMetadata {
+    fn has_descriptive_metadata(&self) -> bool {
+        self.exif.is_some() || self.xmp.is_some() || !self.iptc.is_empty() || !self.text.is_empty()
+    }
+}
+
+fn read_u16_be(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// A JPEG marker segment: the marker byte and the raw bytes including the
+/// `FF xx` prefix and length field.
+struct JpegSegment<'a> {
+    marker: u8,
+    raw: &'a [u8],
+}
+
+impl JpegSegment<'_> {
+    fn payload(&self) -> &[u8] {
+        self.raw.get(4..).unwrap_or_default()
+    }
+}
+
+/// Split a JPEG into its header segments and the entropy-coded remainder,
+/// which starts at the SOS marker.
+fn jpeg_segments(data: &[u8]) -> Result<(Vec<JpegSegment<'_>>, &[u8]), Error> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err(Error::from("The file is not a valid JPEG image."));
+    }
+
+    let mut segments = Vec::new();
+    let mut offset = 2;
+    loop {
+        if data.get(offset) != Some(&0xFF) {
+            return Err(Error::from("The JPEG structure is corrupted."));
+        }
+        // Skip optional fill bytes before the marker.
+        while data.get(offset + 1) == Some(&0xFF) {
+            offset += 1;
+        }
+        let marker = *data
+            .get(offset + 1)
+            .ok_or_else(|| Error::from("The JPEG ended unexpectedly."))?;
+
+        if marker == 0xDA || marker == 0xD9 {
+            return Ok((segments, &data[offset..]));
+        }
+        if (0xD0..=0xD7).contains(&marker) || marker == 0x01 {
+            segments.push(JpegSegment {
+                marker,
+                raw: &data[offset..offset + 2],
+            });
+            offset += 2;
+            continue;
+        }
+
+        let length = usize::from(
+            read_u16_be(data, offset + 2)
+                .ok_or_else(|| Error::from("The JPEG ended unexpectedly."))?,
+        );
+        let end = offset + 2 + length;
+        if length < 2 || end > data.len() {
+            return Err(Error::from("The JPEG structure is corrupted."));
+        }
+        segments.push(JpegSegment {
+            marker,
+            raw: &data[offset..end],
+        });
+        offset = end;
+    }
+}
+
+/// Number of colour components declared by a JPEG's start-of-frame header.
+pub fn jpeg_component_count(data: &[u8]) -> Option<u8> {
+    let (segments, _) = jpeg_segments(data).ok()?;
+    segments
+        .iter()
+        .find(|segment| {
+            (0xC0..=0xCF).contains(&segment.marker) && ![0xC4, 0xC8, 0xCC].contains(&segment.marker)
+        })
+        .and_then(|segment| segment.payload().get(5).copied())
+}
+
+/// A PNG or RIFF chunk: its type, its data and the raw bytes including the
+/// header and trailer.
+struct Chunk<'a> {
+    kind: [u8; 4],
+    data: &'a [u8],
+    raw: &'a [u8],
+}
+
+/// Split a PNG into its chunks.
+fn png_chunks(data: &[u8]) -> Result<Vec<Chunk<'_>>, Error> {
+    if !data.starts_with(PNG_SIGNATURE) {
+        return Err(Error::from("The file is not a valid PNG image."));
+    }
+
+    let mut chunks = Vec::new();
+    let mut offset = PNG_SIGNATURE.len();
+    while offset < data.len() {
+        let length = read_u32_be(data, offset)
+            .ok_or_else(|| Error::from("The PNG structure is corrupted."))?
+            as usize;
+        let end = offset
+            .checked_add(12)
+            .and_then(|value| value.checked_add(length))
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| Error::from("The PNG structure is corrupted."))?;
+        let mut kind = [0u8; 4];
+        kind.copy_from_slice(&data[offset + 4..offset + 8]);
+        chunks.push(Chunk {
+            kind,
+            data: &data[offset + 8..end - 4],
+            raw: &data[offset..end],
+        });
+        offset = end;
+        if &kind == b"IEND" {
+            break;
+        }
+    }
+
+    Ok(chunks)
+}
+
+/// Split a WebP into its RIFF chunks.
+fn webp_chunks(data: &[u8]) -> Result<Vec<Chunk<'_>>, Error> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return Err(Error::from("The file is not a valid WebP image."));
+    }
+
+    let mut chunks = Vec::new();
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let length = read_u32_le(data, offset + 4)
+            .ok_or_else(|| Error::from("The WebP structure is corrupted."))?
+            as usize;
+        let data_end = offset + 8 + length;
+        if data_end > data.len() {
+            return Err(Error::from("The WebP structure is corrupted."));
+        }
+        // Chunks are padded to an even length.
+        let end = (data_end + (length & 1)).min(data.len());
+        let mut kind = [0u8; 4];
+        kind.copy_from_slice(&data[offset..offset + 4]);
+        chunks.push(Chunk {
+            kind,
+            data: &data[offset + 8..data_end],
+            raw: &data[offset..end],
+        });
+        offset = end;
+    }
+
+    Ok(chunks)
+}
+
+/// Human-readable names for common IPTC IIM application records.
+fn iptc_dataset_name(record: u8, dataset: u8) -> String {
+    let name = match (record, dataset) {
+        (2, 5) => "Object Name",
+        (2, 15) => "Category",
+        (2, 25) => "Keywords",
+        (2, 40) => "Special Instructions",
+        (2, 55) => "Date Created",
+        (2, 60) => "Time Created",
+        (2, 80) => "By-line",
+        (2, 85) => "By-line Title",
+        (2, 90) => "City",
+        (2, 92) => "Sub-location",
+        (2, 95) => "Province/State",
+        (2, 100) => "Country Code",
+        (2, 101) => "Country",
+        (2, 103) => "Original Transmission Reference",
+        (2, 105) => "Headline",
+        (2, 110) => "Credit",
+        (2, 115) => "Source",
+        (2, 116) => "Copyright Notice",
+        (2, 120) => "Caption/Abstract",
+        (2, 122) => "Writer/Editor",
+        _ => return format!("{}:{:03}", record, dataset),
+    };
+    name.to_string()
+}
+
+/// Parse IPTC IIM records, skipping the envelope and binary records.
+fn parse_iptc(data: &[u8]) -> Vec<(String, String)> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset + 5 <= data.len() && data[offset] == 0x1C {
+        let record = data[offset + 1];
+        let dataset = data[offset + 2];
+        let length = usize::from(u16::from_be_bytes([data[offset + 3], data[offset + 4]]));
+        // Extended-length datasets are only used for binary previews.
+        if length & 0x8000 != 0 {
+            break;
+        }
+        let start = offset + 5;
+        let Some(value) = data.get(start..start + length) else {
+            break;
+        };
+        if record == 2 && dataset != 0 {
+            records.push((
+                iptc_dataset_name(record, dataset),
+                String::from_utf8_lossy(value).trim().to_string(),
+            ));
+        }
+        offset = start + length;
+    }
+    records
+}
+
+/// Find the IPTC block inside Photoshop image resource blocks.
+fn photoshop_iptc(data: &[u8]) -> Option<&[u8]> {
+    let mut offset = 0;
+    while offset + 12 <= data.len() && &data[offset..offset + 4] == b"8BIM" {
+        let id = read_u16_be(data, offset + 4)?;
+        // The resource name is a Pascal string padded to an even length.
+        let name_len = usize::from(*data.get(offset + 6)?);
+        let name_total = (name_len + 1 + 1) & !1;
+        let size_offset = offset + 6 + name_total;
+        let size = read_u32_be(data, size_offset)? as usize;
+        let start = size_offset + 4;
+        let block = data.get(start..start + size)?;
+        if id == IPTC_RESOURCE_ID {
+            return Some(block);
+        }
+        offset = start + size + (size & 1);
+    }
+    None
+}
+
+/// Parse a PNG `tEXt` or uncompressed `iTXt` chunk into a keyword and value.
+fn png_text_entry(kind: &[u8; 4], data: &[u8]) -> Option<(String, String)> {
+    let separator = data.iter().position(|&byte| byte == 0)?;
+    let keyword = String::from_utf8_lossy(&data[..separator]).to_string();
+    let rest = &data[separator + 1..];
+
+    let text = match kind {
+        b"tEXt" => rest.iter().map(|&byte| char::from(byte)).collect(),
+        b"zTXt" => "(compressed text)".to_string(),
+        b"iTXt" => {
+            let compressed = *rest.first()? == 1;
+            let after_flags = rest.get(2..)?;
+            let language_end = after_flags.iter().position(|&byte| byte == 0)?;
+            let after_language = &after_flags[language_end + 1..];
+            let translated_end = after_language.iter().position(|&byte| byte == 0)?;
+            if compressed {
+                "(compressed text)".to_string()
+            } else {
+                String::from_utf8_lossy(&after_language[translated_end + 1..]).to_string()
+            }
+        }
+        _ => return None,
+    };
+
+    Some((keyword, text))
+}
+
+/// A GIF block: its introducer (`0x21` extension or `0x2C` image), its
+/// extension label and the raw bytes including every data sub-block.
+struct GifBlock<'a> {
+    introducer: u8,
+    label: u8,
+    raw: &'a [u8],
+}
+
+impl GifBlock<'_> {
+    /// Identifier of an application extension, e.g. `NETSCAPE2.0`.
+    fn application_id(&self) -> Option<&[u8]> {
+        (self.introducer == 0x21 && self.label == 0xFF)
+            .then(|| self.raw.get(3..14))
+            .flatten()
+    }
+
+    /// The concatenated data sub-blocks of an extension.
+    fn sub_block_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut offset = 2;
+        while let Some(&size) = self.raw.get(offset) {
+            let Some(bytes) = self.raw.get(offset + 1..offset + 1 + usize::from(size)) else {
+                break;
+            };
+            data.extend_from_slice(bytes);
+            offset += 1 + usize::from(size);
+        }
+        data
+    }
+}
+
+/// Split a GIF into its header (including the global colour table) and the
+/// blocks up to the trailer.
+fn gif_blocks(data: &[u8]) -> Result<(&[u8], Vec<GifBlock<'_>>), Error> {
+    let corrupted = || Error::from("The GIF structure is corrupted.");
+    if !data.starts_with(b"GIF8") || data.len() < 13 {
+        return Err(Error::from("The file is not a valid GIF image."));
+    }
+
+    let flags = data[10];
+    let mut offset = 13;
+    if flags & 0x80 != 0 {
+        offset += 3 * (1 << ((flags & 0x07) + 1));
+    }
+    let header = data.get(..offset).ok_or_else(corrupted)?;
+
+    let skip_sub_blocks = |mut offset: usize| -> Result<usize, Error> {
+        loop {
+            let size = usize::from(*data.get(offset).ok_or_else(corrupted)?);
+            offset += 1;
+            if size == 0 {
+                return Ok(offset);
+            }
+            offset += size;
+        }
+    };
+
+    let mut blocks = Vec::new();
+    loop {
+        let start = offset;
+        let (introducer, label) = match data.get(offset) {
+            Some(0x21) => {
+                let label = *data.get(offset + 1).ok_or_else(corrupted)?;
+                offset = skip_sub_blocks(offset + 2)?;
+                (0x21, label)
+            }
+            Some(0x2C) => {
+                let local_flags = *data.get(offset + 9).ok_or_else(corrupted)?;
+                offset += 10;
+                if local_flags & 0x80 != 0 {
+                    offset += 3 * (1 << ((local_flags & 0x07) + 1));
+                }
+                // Skip the LZW minimum code size before the data sub-blocks.
+                offset = skip_sub_blocks(offset + 1)?;
+                (0x2C, 0)
+            }
+            // The trailer, or trailing garbage that decoders ignore.
+            _ => return Ok((header, blocks)),
+        };
+        blocks.push(GifBlock {
+            introducer,
+            label,
+            raw: data.get(start..offset).ok_or_else(corrupted)?,
+        });
+    }
+}
+
+/// Count GIF image descriptors without decoding any frames.
+pub fn gif_frame_count(data: &[u8]) -> Option<usize> {
+    let (_, blocks) = gif_blocks(data).ok()?;
+    Some(
+        blocks
+            .iter()
+            .filter(|block| block.introducer == 0x2C)
+            .count(),
+    )
+}
+
+/// Collect XMP, IPTC, ICC and animation details from the container.
+fn read_container_metadata(data: &[u8], format: ImageFormat, metadata: &mut ImageMetadata) {
+    match format {
+        ImageFormat::Jpeg => {
+            let Ok((segments, _)) = jpeg_segments(data) else {
+                return;
+            };
+            let mut icc_size = 0;
+            for segment in &segments {
+                let payload = segment.payload();
+                match segment.marker {
+                    0xE1 if payload.starts_with(JPEG_XMP_HEADER) => {
+                        metadata.xmp = Some(
+                            String::from_utf8_lossy(&payload[JPEG_XMP_HEADER.len()..]).to_string(),
+                        );
+                    }
+                    // Skip the sequence number and chunk count after the header.
+                    0xE2 if payload.starts_with(JPEG_ICC_HEADER) => {
+                        icc_size += payload.len().saturating_sub(JPEG_ICC_HEADER.len() + 2);
+                    }
+                    0xED if payload.starts_with(PHOTOSHOP_HEADER) => {
+                        if let Some(iptc) = photoshop_iptc(&payload[PHOTOSHOP_HEADER.len()..]) {
+                            metadata.iptc = parse_iptc(iptc);
+                        }
+                    }
+                    0xFE => metadata.text.push((
+                        "Comment".to_string(),
+                        String::from_utf8_lossy(payload).to_string(),
+                    )),
+                    _ => {}
+                }
+            }
+            metadata.icc_size = (icc_size > 0).then_some(icc_size);
+        }
+        ImageFormat::Png => {
+            let Ok(chunks) = png_chunks(data) else {
+                return;
+            };
+            for chunk in chunks {
+                match &chunk.kind {
+                    b"iCCP" => metadata.icc_size = Some(chunk.data.len()),
+                    b"acTL" => metadata.frames = read_u32_be(chunk.data, 0).map(|n| n as usize),
+                    b"tEXt" | b"zTXt" | b"iTXt" => {
+                        let Some((keyword, text)) = png_text_entry(&chunk.kind, chunk.data) else {
+                            continue;
+                        };
+                        if keyword.as_bytes() == PNG_XMP_KEYWORD {
+                            metadata.xmp = Some(text);
+                        } else {
+                            metadata.text.push((keyword, text));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        ImageFormat::WebP => {
+            let Ok(chunks) = webp_chunks(data) else {
+                return;
+            };
+            let mut frames = 0;
+            for chunk in chunks {
+                match &chunk.kind {
+                    b"ICCP" => metadata.icc_size = Some(chunk.data.len()),
+                    b"XMP " => {
+                        metadata.xmp = Some(String::from_utf8_lossy(chunk.data).to_string());
+                    }
+                    b"ANMF" => frames += 1,
+                    _ => {}
+                }
+            }
+            metadata.frames = (frames > 0).then_some(frames);
+        }
+        ImageFormat::Gif => {
+            let Ok((_, blocks)) = gif_blocks(data) else {
+                return;
+            };
+            for block in &blocks {
+                match (block.label, block.application_id()) {
+                    (0xFE, _) => metadata.text.push((
+                        "Comment".to_string(),
+                        String::from_utf8_lossy(&block.sub_block_data()).to_string(),
+                    )),
+                    // XMP is stored raw, so the sub-block sizes are part of the packet.
+                    (0xFF, Some(b"XMP DataXMP")) => {
+                        let packet = String::from_utf8_lossy(&block.raw[14..]);
+                        let end = packet.rfind('>').map_or(0, |index| index + 1);
+                        metadata.xmp = Some(packet[..end].to_string());
+                    }
+                    _ => {}
+                }
+            }
+            metadata.frames = gif_frame_count(data);
+        }
+        _ => {}
+    }
+}
+
+fn read_metadata(data: &[u8], format: ImageFormat) -> ImageMetadata {
+    let mut metadata = ImageMetadata {
+        exif: exif::Reader::new()
+            .read_from_container(&mut Cursor::new(data))
+            .ok(),
+        ..Default::default()
+    };
+    read_container_metadata(data, format, &mut metadata);
+    metadata
+}
+
+/// Convert an EXIF degrees/minutes/seconds triple to signed decimal degrees.
+fn gps_coordinate(exif: &Exif, value_tag: Tag, ref_tag: Tag) -> Option<f64> {
+    let exif::Value::Rational(parts) = &exif.get_field(value_tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    let [degrees, minutes, seconds] = parts.get(0..3)? else {
+        return None;
+    };
+    let decimal = degrees.to_f64() + minutes.to_f64() / 60.0 + seconds.to_f64() / 3600.0;
+
+    let reference = exif
+        .get_field(ref_tag, In::PRIMARY)
+        .map(|field| field.display_value().to_string())
+        .unwrap_or_default();
+    if reference.contains('S') || reference.contains('W') {
+        Some(-decimal)
+    } else {
+        Some(decimal)
+    }
+}
+
+/// Format GPS data for a privacy warning, if the EXIF block contains any.
+fn gps_summary(exif: &Exif) -> Option<String> {
+    let has_gps = exif
+        .fields()
+        .any(|field| field.tag.context() == exif::Context::Gps);
+    if !has_gps {
+        return None;
+    }
+
+    match (
+        gps_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef),
+        gps_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef),
+    ) {
+        (Some(latitude), Some(longitude)) => Some(format!("{:.6}, {:.6}", latitude, longitude)),
+        _ => Some("GPS tags present".to_string()),
+    }
+}
+
+fn exif_value(exif: &Exif, tag: Tag) -> Option<String> {
+    exif.get_field(tag, In::PRIMARY).map(|field| {
+        field
+            .display_value()
+            .with_unit(exif)
+            .to_string()
+            .trim_matches('"')
+            .to_string()
+    })
+}
+
+/// Build the full plain-text metadata report attached to `/image_info`.
+fn metadata_report(filename: &str, metadata: &ImageMetadata) -> String {
+    let mut report = format!("Metadata report for {}\n", filename);
+
+    if let Some(exif) = &metadata.exif {
+        let _ = writeln!(report, "\n== EXIF ({} fields) ==", exif.fields().len());
+        for field in exif.fields() {
+            let _ = writeln!(
+                report,
+                "[{}] {}: {}",
+                field.ifd_num,
+                field.tag,
+                field.display_value().with_unit(exif)
+            );
+        }
+    }
+
+    if !metadata.iptc.is_empty() {
+        let _ = writeln!(report, "\n== IPTC ({} records) ==", metadata.iptc.len());
+        for (name, value) in &metadata.iptc {
+            let _ = writeln!(report, "{}: {}", name, value);
+        }
+    }
+
+    if !metadata.text.is_empty() {
+        let _ = writeln!(report, "\n== Text ({} entries) ==", metadata.text.len());
+        for (keyword, text) in &metadata.text {
+            let _ = writeln!(report, "{}: {}", keyword, text);
+        }
+    }
+
+    if let Some(xmp) = &metadata.xmp {
+        let _ = writeln!(report, "\n== XMP ==\n{}", xmp.trim_end_matches('\0').trim());
+    }
+
+    report
+}
+
+fn format_name(format: ImageFormat) -> String {
+    format
+        .extensions_str()
+        .first()
+        .map_or_else(|| format!("{:?}", format), |ext| ext.to_ascii_uppercase())
+}
+
+/// Download an attachment after validating its size.
+async fn download_file(file: &Attachment) -> Result<Vec<u8>, Error> {
+    validate_attachment_size(file).map_err(Error::from)?;
+    file.download()
+        .await
+        .map_err(|e| Error::from(format!("Failed to download image: {}", e)))
+}
+
+/// Remove metadata from a JPEG without re-encoding the image data.
+fn strip_jpeg(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let (segments, remainder) = jpeg_segments(data)?;
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&[0xFF, 0xD8]);
+
+    for segment in segments {
+        let payload = segment.payload();
+        let is_metadata = match segment.marker {
+            0xE1 => [JPEG_EXIF_HEADER, JPEG_XMP_HEADER, JPEG_XMP_EXTENSION_HEADER]
+                .iter()
+                .any(|header| payload.starts_with(header)),
+            0xED | 0xFE => true,
+            _ => false,
+        };
+        if !is_metadata {
+            output.extend_from_slice(segment.raw);
+        }
+    }
+
+    output.extend_from_slice(remainder);
+    Ok(output)
+}
+
+/// Remove metadata chunks from a PNG without touching image data.
+fn strip_png(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(PNG_SIGNATURE);
+
+    for chunk in png_chunks(data)? {
+        if !PNG_METADATA_CHUNKS.contains(&&chunk.kind) {
+            output.extend_from_slice(chunk.raw);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Remove EXIF and XMP chunks from a WebP and clear their VP8X flags.
+fn strip_webp(data: &[u8]) -> Result<Vec<u8>, Error> {
+    const VP8X_XMP_FLAG: u8 = 0x04;
+    const VP8X_EXIF_FLAG: u8 = 0x08;
+
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(b"RIFF\0\0\0\0WEBP");
+
+    for chunk in webp_chunks(data)? {
+        match &chunk.kind {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" => {
+                let start = output.len();
+                output.extend_from_slice(chunk.raw);
+                if let Some(flags) = output.get_mut(start + 8) {
+                    *flags &= !(VP8X_XMP_FLAG | VP8X_EXIF_FLAG);
+                }
+            }
+            _ => output.extend_from_slice(chunk.raw),
+        }
+    }
+
+    let riff_size =
+        u32::try_from(output.len() - 8).map_err(|_| Error::from("The WebP image is too large."))?;
+    output[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(output)
+}
+
+/// Remove comment and application extensions from a GIF, keeping the
+/// looping extension so animations still repeat.
+fn strip_gif(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let (header, blocks) = gif_blocks(data)?;
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(header);
+
+    for block in blocks {
+        let is_metadata = match block.label {
+            0xFE => true,
+            0xFF => !matches!(
+                block.application_id(),
+                Some(b"NETSCAPE2.0") | Some(b"ANIMEXTS1.0")
+            ),
+            _ => false,
+        };
+        if block.introducer == 0x2C || !is_metadata {
+            output.extend_from_slice(block.raw);
+        }
+    }
+
+    output.push(0x3B);
+    Ok(output)
+}
+
+/// Re-encode an image, which drops all metadata the encoder does not write.
+fn reencode(img: DynamicImage, format: ImageFormat) -> Result<Vec<u8>, Error> {
+    let output_format = match format {
+        ImageFormat::Jpeg => ImageOutputFormat::Jpeg(95),
+        other => ImageOutputFormat::from(other),
+    };
+    if let ImageOutputFormat::Unsupported(_) = output_format {
+        return Err(Error::from(
+            "Metadata cannot be removed from this image format.",
+        ));
+    }
+
+    let img = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8()),
+        _ => img,
+    };
+    let mut buffer = Cursor::new(Vec::new());
+    img.write_to(&mut buffer, output_format)
+        .map_err(|e| Error::from(format!("Failed to re-encode image: {}", e)))?;
+    Ok(buffer.into_inner())
+}
+
+/// Strip metadata, preferring lossless segment removal where supported.
+///
+/// Returns the cleaned bytes and whether the image had to be re-encoded.
+fn strip_metadata_bytes(data: &[u8], format: ImageFormat) -> Result<(Vec<u8>, bool), Error> {
+    let lossless = match format {
+        ImageFormat::Jpeg => Some(strip_jpeg(data)),
+        ImageFormat::Png => Some(strip_png(data)),
+        ImageFormat::WebP => Some(strip_webp(data)),
+        ImageFormat::Gif => Some(strip_gif(data)),
+        _ => None,
+    };
+
+    match lossless {
+        Some(Ok(stripped)) => Ok((stripped, false)),
+        // Re-encoding would flatten an animated GIF to its first frame.
+        Some(Err(e)) if format == ImageFormat::Gif => Err(e),
+        _ => Ok((reencode(decode_image(data)?, format)?, true)),
+    }
+}
+
+/// Show format details and metadata for an image
+#[poise::command(slash_command)]
+pub async fn image_info(
+    ctx: Context<'_>,
+    #[description = "Image to inspect"] file: Attachment,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_file(&file).await {
+        Ok(data) => data,
+        Err(e) => {
+            let embed = error_embed("❌ Download Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let result = tokio::task::spawn_blocking(move || -> Result<_, Error> {
+        let format =
+            image::guess_format(&data).map_err(|_| Error::from("Unrecognized image format."))?;
+        let img = decode_image(&data)?;
+        let metadata = read_metadata(&data, format);
+        Ok((format, img.width(), img.height(), img.color(), metadata))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Metadata reading task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (format, width, height, color, metadata) = match result {
+        Ok(info) => info,
+        Err(e) => {
+            let embed = error_embed("❌ Image Info Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let bit_depth = color.bits_per_pixel() / u16::from(color.channel_count());
+    let mut present = Vec::new();
+    if let Some(exif) = &metadata.exif {
+        present.push(format!("EXIF ({} fields)", exif.fields().len()));
+    }
+    if metadata.xmp.is_some() {
+        present.push("XMP".to_string());
+    }
+    if !metadata.iptc.is_empty() {
+        present.push(format!("IPTC ({} records)", metadata.iptc.len()));
+    }
+    if !metadata.text.is_empty() {
+        present.push(format!("Text ({} entries)", metadata.text.len()));
+    }
+
+    let mut embed = CreateEmbed::new()
+        .title("🖼️ Image Info")
+        .description(format!(
+            "**File:** `{}`\n**Size:** {}",
+            file.filename,
+            format_file_size(u64::from(file.size))
+        ))
+        .field("Format", format_name(format), true)
+        .field("Dimensions", format!("{}×{}", width, height), true)
+        .field(
+            "Colour",
+            format!(
+                "{:?} ({} channels, {}-bit)",
+                color,
+                color.channel_count(),
+                bit_depth
+            ),
+            true,
+        )
+        .field(
+            "ICC Profile",
+            metadata
+                .icc_size
+                .map_or_else(|| "None".to_string(), |size| format_file_size(size as u64)),
+            true,
+        )
+        .field(
+            "Frames",
+            metadata
+                .frames
+                .map_or_else(|| "1".to_string(), |frames| frames.to_string()),
+            true,
+        )
+        .field(
+            "Metadata",
+            if present.is_empty() {
+                "None".to_string()
+            } else {
+                present.join(", ")
+            },
+            true,
+        )
+        .color(EMBED_SUCCESS_COLOR);
+
+    if let Some(exif) = &metadata.exif {
+        let camera = [Tag::Make, Tag::Model, Tag::DateTimeOriginal, Tag::Software]
+            .into_iter()
+            .filter_map(|tag| exif_value(exif, tag).map(|value| format!("**{}:** {}", tag, value)))
+            .collect::<Vec<_>>();
+        if !camera.is_empty() {
+            embed = embed.field("Camera", camera.join("\n"), false);
+        }
+
+        if let Some(location) = gps_summary(exif) {
+            embed = embed
+                .field(
+                    "⚠️ GPS Location",
+                    format!(
+                        "`{}`\nThis image reveals where it was taken. Use `/strip_metadata` before sharing it.",
+                        location
+                    ),
+                    false,
+                )
+                .color(EMBED_WARNING_COLOR);
+        }
+    }
+
+    let mut reply = poise::CreateReply::default();
+    if metadata.has_descriptive_metadata() {
+        let report = metadata_report(&file.filename, &metadata);
+        validate_output_size(report.len(), "Metadata report").map_err(Error::from)?;
+        let filename = format!(
+            "{}_metadata.txt",
+            sanitize_filename(&file_stem(&file.filename))
+        );
+        embed = embed.footer(CreateEmbedFooter::new(
+            "The full metadata dump is attached as a file.",
+        ));
+        reply = reply.attachment(CreateAttachment::bytes(report.into_bytes(), filename));
+    }
+
+    ctx.send(reply.embed(embed)).await?;
+    Ok(())
+}
+
+/// Remove EXIF, GPS, XMP and IPTC metadata from an image
+#[poise::command(slash_command)]
+pub async fn strip_metadata(
+    ctx: Context<'_>,
+    #[description = "Image to clean"] file: Attachment,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let data = match download_file(&file).await {
+        Ok(data) => data,
+        Err(e) => {
+            let embed = error_embed("❌ Download Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    let original_len = data.len();
+
+    let result = tokio::task::spawn_blocking(move || -> Result<_, Error> {
+        let format =
+            image::guess_format(&data).map_err(|_| Error::from("Unrecognized image format."))?;
+        let metadata = read_metadata(&data, format);
+        let gps = metadata.exif.as_ref().and_then(gps_summary);
+        let (stripped, reencoded) = strip_metadata_bytes(&data, format)?;
+        Ok((format, metadata, gps, stripped, reencoded))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Metadata removal task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (format, metadata, gps, stripped, reencoded) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let embed = error_embed("❌ Metadata Removal Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(stripped.len(), "Cleaned image").map_err(Error::from)?;
+
+    let mut removed = Vec::new();
+    if metadata.exif.is_some() {
+        removed.push("EXIF");
+    }
+    if metadata.xmp.is_some() {
+        removed.push("XMP");
+    }
+    if !metadata.iptc.is_empty() {
+        removed.push("IPTC");
+    }
+    if !metadata.text.is_empty() {
+        removed.push("text comments");
+    }
+
+    let mut embed = CreateEmbed::new()
+        .title("✅ Metadata Removed")
+        .description(format!(
+            "**File:** `{}`\n**Removed:** {}\n**Size:** {} → {}",
+            file.filename,
+            if removed.is_empty() {
+                "nothing found".to_string()
+            } else {
+                removed.join(", ")
+            },
+            format_file_size(original_len as u64),
+            format_file_size(stripped.len() as u64)
+        ))
+        .color(EMBED_SUCCESS_COLOR)
+        .footer(CreateEmbedFooter::new(if reencoded {
+            "Method: re-encoded (this format has no lossless metadata removal)"
+        } else {
+            "Method: lossless segment removal"
+        }));
+
+    if let Some(location) = gps {
+        embed = embed.field(
+            "⚠️ GPS Location Removed",
+            format!("The original image contained coordinates `{}`.", location),
+            false,
+        );
+    }
+
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+    let filename = format!(
+        "{}_clean.{}",
+        sanitize_filename(&file_stem(&file.filename)),
+        extension
+    );
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(stripped, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        JPEG_EXIF_HEADER, JPEG_XMP_EXTENSION_HEADER, gif_frame_count, jpeg_segments, parse_iptc,
+        strip_gif, strip_jpeg, strip_webp,
+    };
+    use image::{DynamicImage, ImageOutputFormat, RgbImage};
+    use std::io::Cursor;
+
+    fn jpeg_with_exif_segment() -> Vec<u8> {
+        let mut encoded = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(RgbImage::new(4, 4))
+            .write_to(&mut encoded, ImageOutputFormat::Jpeg(90))
+            .unwrap();
+        let encoded = encoded.into_inner();
+
+        let mut payload = JPEG_EXIF_HEADER.to_vec();
+        payload.extend_from_slice(b"MM\0*\0\0\0\x08\0\0");
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        data.extend_from_slice(&payload);
+        data.extend_from_slice(&encoded[2..]);
+        data
+    }
+
+    #[test]
+    fn strip_jpeg_removes_exif_and_keeps_image_decodable() {
+        let data = jpeg_with_exif_segment();
+        let stripped = strip_jpeg(&data).unwrap();
+
+        let (segments, _) = jpeg_segments(&stripped).unwrap();
+        assert!(segments.iter().all(|segment| segment.marker != 0xE1));
+        assert!(image::load_from_memory(&stripped).is_ok());
+    }
+
+    #[test]
+    fn strip_jpeg_removes_extended_xmp() {
+        let mut payload = JPEG_XMP_EXTENSION_HEADER.to_vec();
+        payload.extend_from_slice(b"0123456789ABCDEF0123456789ABCDEF\0\0\0\x05\0\0\0\0<x/>");
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        data.extend_from_slice(&payload);
+        data.extend_from_slice(&jpeg_with_exif_segment()[2..]);
+
+        let stripped = strip_jpeg(&data).unwrap();
+        let (segments, _) = jpeg_segments(&stripped).unwrap();
+        assert!(segments.iter().all(|segment| segment.marker != 0xE1));
+    }
+
+    #[test]
+    fn strip_gif_keeps_frames_and_looping_but_drops_comments() {
+        let mut data = b"GIF89a\x01\0\x01\0\0\0\0".to_vec();
+        data.extend_from_slice(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\0\0\0");
+        data.extend_from_slice(b"\x21\xFE\x05hello\0");
+        data.extend_from_slice(b"\x21\xFF\x0BXMP DataXMP\x01<\0");
+        for _ in 0..2 {
+            data.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x4C, 0x01, 0]);
+        }
+        data.push(0x3B);
+
+        let stripped = strip_gif(&data).unwrap();
+        assert_eq!(gif_frame_count(&stripped), Some(2));
+        assert!(stripped.windows(11).any(|window| window == b"NETSCAPE2.0"));
+        assert!(!stripped.windows(5).any(|window| window == b"hello"));
+        assert!(!stripped.windows(3).any(|window| window == b"XMP"));
+        assert_eq!(stripped.last(), Some(&0x3B));
+    }
+
+    #[test]
+    fn strip_webp_drops_metadata_chunks_and_fixes_riff_size() {
+        let mut data = b"RIFF\0\0\0\0WEBP".to_vec();
+        data.extend_from_slice(b"VP8X\x0a\0\0\0\x0c\0\0\0\0\0\0\0\0\0");
+        data.extend_from_slice(b"EXIF\x03\0\0\0abc\0");
+        let size = (data.len() - 8) as u32;
+        data[4..8].copy_from_slice(&size.to_le_bytes());
+
+        let stripped = strip_webp(&data).unwrap();
+        assert_eq!(stripped.len(), 12 + 18);
+        assert_eq!(stripped[20], 0);
+        assert_eq!(&stripped[4..8], &22u32.to_le_bytes());
+    }
+
+    #[test]
+    fn parse_iptc_names_common_datasets() {
+        let mut data = vec![0x1C, 2, 0, 0, 2, 0, 4];
+        data.extend_from_slice(&[0x1C, 2, 80, 0, 3]);
+        data.extend_from_slice(b"Ada");
+        assert_eq!(
+            parse_iptc(&data),
+            vec![("By-line".to_string(), "Ada".to_string())]
+        );
+    }
+
+    #[test]
+    fn gif_frame_count_walks_blocks() {
+        let mut data = b"GIF89a\x01\0\x01\0\0\0\0".to_vec();
+        for _ in 0..3 {
+            data.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x4C, 0x01, 0]);
+        }
+        data.push(0x3B);
+        assert_eq!(gif_frame_count(&data), Some(3));
+    }
+}
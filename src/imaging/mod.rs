@@ -0,0 +1,10 @@
+pub mod background;
+pub mod compress;
+pub mod discord;
+pub mod filter;
+pub mod grid;
+pub mod icons;
+pub mod metadata;
+pub mod palette;
+pub mod similarity;
+pub mod watermark;
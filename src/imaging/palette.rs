@@ -0,0 +1,463 @@
+use crate::attachments::{sanitize_filename, validate_output_size};
+use crate::conversion::image::{OutputFormat, download_image, encode_image};
+use crate::embeds::error_embed;
+use crate::utils::file_stem;
+use crate::{Context, Error};
+use image::{DynamicImage, Rgba, RgbaImage};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Dither {
+    #[name = "None (flat colour areas)"]
+    None,
+    #[name = "Floyd–Steinberg (error diffusion)"]
+    FloydSteinberg,
+    #[name = "Ordered (8×8 Bayer pattern)"]
+    Ordered,
+}
+
+/// A palette colour and the number of sampled pixels it represents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub color: [u8; 3],
+    pub count: usize,
+}
+
+/// Colours reported by `/palette` when the caller does not pick a count
+const DEFAULT_PALETTE_COLORS: u32 = 6;
+/// Pixels considered when building a palette; larger images are sampled
+const MAX_SAMPLES: usize = 262_144;
+/// Lloyd iterations used to refine the median cut palette for `/palette`
+const KMEANS_ITERATIONS: usize = 8;
+/// Width and height of each colour block in the swatch image
+const SWATCH_SIZE: u32 = 64;
+/// Pixels below this alpha are treated as transparent
+const ALPHA_CUTOFF: u8 = 128;
+/// Largest palette a GIF can store, including the transparent entry
+const MAX_INDEXED_COLORS: usize = 256;
+/// Bits kept per channel when caching nearest-colour lookups
+const LOOKUP_BITS: u32 = 6;
+
+/// 8×8 Bayer threshold matrix for ordered dithering
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Collect the opaque pixels of an image, striding over large images.
+fn sample_pixels(img: &RgbaImage) -> Vec<[u8; 3]> {
+    let total = img.width() as usize * img.height() as usize;
+    let step = total.div_ceil(MAX_SAMPLES).max(1);
+    img.pixels()
+        .step_by(step)
+        .filter(|pixel| pixel[3] >= ALPHA_CUTOFF)
+        .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+        .collect()
+}
+
+/// The channel with the widest spread of values and the size of that spread.
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let (min, max) = pixels.iter().fold((u8::MAX, u8::MIN), |(min, max), pixel| {
+                (min.min(pixel[channel]), max.max(pixel[channel]))
+            });
+            (channel, max.saturating_sub(min))
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+fn average_color(pixels: &[[u8; 3]]) -> [u8; 3] {
+    let mut sums = [0u64; 3];
+    for pixel in pixels {
+        for (sum, &value) in sums.iter_mut().zip(pixel) {
+            *sum += u64::from(value);
+        }
+    }
+    let len = pixels.len().max(1) as u64;
+    sums.map(|sum| ((sum + len / 2) / len) as u8)
+}
+
+/// Build a palette of at most `count` colours with median cut.
+///
+/// The box whose widest channel spread, weighted by population, is largest is
+/// split at its median until there are `count` boxes or nothing left to split.
+/// Entries are sorted from most to least common.
+pub fn median_cut(pixels: &[[u8; 3]], count: usize) -> Vec<PaletteEntry> {
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![pixels.to_vec()];
+    while boxes.len() < count {
+        let candidate = boxes
+            .iter()
+            .enumerate()
+            .map(|(index, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (index, channel, u64::from(range) * pixels.len() as u64)
+            })
+            .filter(|&(_, _, score)| score > 0)
+            .max_by_key(|&(_, _, score)| score);
+        let Some((index, channel, _)) = candidate else {
+            break;
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|pixel| pixel[channel]);
+        // Split on a change of value so both halves cover distinct colours
+        let median = pixels[pixels.len() / 2][channel];
+        let mut split = pixels.partition_point(|pixel| pixel[channel] < median);
+        if split == 0 {
+            split = pixels.partition_point(|pixel| pixel[channel] <= median);
+        }
+        let upper = pixels.split_off(split);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    let mut entries: Vec<PaletteEntry> = boxes
+        .iter()
+        .map(|pixels| PaletteEntry {
+            color: average_color(pixels),
+            count: pixels.len(),
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+    entries
+}
+
+fn distance(a: [f32; 3], b: [u8; 3]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| (a - f32::from(b)).powi(2))
+        .sum()
+}
+
+fn nearest(palette: &[[u8; 3]], color: [f32; 3]) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(color, **a).total_cmp(&distance(color, **b)))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Refine a palette with a few k-means iterations, dropping unused colours.
+fn refine(pixels: &[[u8; 3]], entries: Vec<PaletteEntry>) -> Vec<PaletteEntry> {
+    let mut palette: Vec<[u8; 3]> = entries.iter().map(|entry| entry.color).collect();
+    let mut counts = vec![0usize; palette.len()];
+
+    for iteration in 0..=KMEANS_ITERATIONS {
+        let mut sums = vec![[0u64; 3]; palette.len()];
+        counts.iter_mut().for_each(|count| *count = 0);
+        for pixel in pixels {
+            let index = nearest(&palette, pixel.map(f32::from));
+            counts[index] += 1;
+            for (sum, &value) in sums[index].iter_mut().zip(pixel) {
+                *sum += u64::from(value);
+            }
+        }
+        // The last pass only counts the final assignment
+        if iteration == KMEANS_ITERATIONS {
+            break;
+        }
+        for ((color, sum), &count) in palette.iter_mut().zip(&sums).zip(&counts) {
+            if count > 0 {
+                let count = count as u64;
+                *color = sum.map(|sum| ((sum + count / 2) / count) as u8);
+            }
+        }
+    }
+
+    let mut refined: Vec<PaletteEntry> = palette
+        .into_iter()
+        .zip(counts)
+        .filter(|&(_, count)| count > 0)
+        .map(|(color, count)| PaletteEntry { color, count })
+        .collect();
+    refined.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+    refined
+}
+
+/// The `count` most representative colours of an image, most common first.
+pub fn dominant_colors(img: &RgbaImage, count: usize) -> Vec<PaletteEntry> {
+    let pixels = sample_pixels(img);
+    refine(&pixels, median_cut(&pixels, count))
+}
+
+/// Nearest palette colour lookups, cached on the top bits of each channel.
+struct NearestColor<'a> {
+    palette: &'a [[u8; 3]],
+    cache: Vec<u16>,
+}
+
+impl<'a> NearestColor<'a> {
+    fn new(palette: &'a [[u8; 3]]) -> Self {
+        Self {
+            palette,
+            cache: vec![u16::MAX; 1 << (LOOKUP_BITS * 3)],
+        }
+    }
+
+    fn get(&mut self, color: [u8; 3]) -> [u8; 3] {
+        let shift = 8 - LOOKUP_BITS;
+        let [r, g, b] = color.map(|value| usize::from(value >> shift));
+        let key = (r << (LOOKUP_BITS * 2)) | (g << LOOKUP_BITS) | b;
+        if self.cache[key] == u16::MAX {
+            // Match from the centre of the bucket so results do not depend on visit order
+            let centre =
+                color.map(|value| f32::from(((value >> shift) << shift) | (1 << (shift - 1))));
+            self.cache[key] = nearest(self.palette, centre) as u16;
+        }
+        self.palette[usize::from(self.cache[key])]
+    }
+}
+
+/// Reduce an image to at most `colors` colours with optional dithering.
+///
+/// Alpha is made binary so GIF output keeps the palette exactly. When the image
+/// has transparent pixels one palette entry is reserved for them.
+pub fn quantize(img: &RgbaImage, colors: usize, dither: Dither) -> RgbaImage {
+    let has_transparency = img.pixels().any(|pixel| pixel[3] < ALPHA_CUTOFF);
+    let limit = if has_transparency {
+        colors.min(MAX_INDEXED_COLORS - 1)
+    } else {
+        colors.min(MAX_INDEXED_COLORS)
+    };
+    let palette: Vec<[u8; 3]> = median_cut(&sample_pixels(img), limit)
+        .into_iter()
+        .map(|entry| entry.color)
+        .collect();
+    let mut output = RgbaImage::new(img.width(), img.height());
+    if palette.is_empty() {
+        return output;
+    }
+
+    let mut lookup = NearestColor::new(&palette);
+    let width = img.width() as usize;
+    // Floyd–Steinberg error for the current and next row, padded by one pixel on each side
+    let mut current = vec![[0f32; 3]; width + 2];
+    let mut next = vec![[0f32; 3]; width + 2];
+    let spread = 255.0 / (palette.len() as f32).cbrt();
+
+    for y in 0..img.height() {
+        for x in 0..img.width() {
+            let pixel = img.get_pixel(x, y);
+            if pixel[3] < ALPHA_CUTOFF {
+                continue;
+            }
+            let source = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+            let column = x as usize + 1;
+            let adjusted = match dither {
+                Dither::None => source,
+                Dither::FloydSteinberg => {
+                    let error = current[column];
+                    [0, 1, 2].map(|channel| source[channel] + error[channel])
+                }
+                Dither::Ordered => {
+                    let threshold =
+                        (f32::from(BAYER_8X8[y as usize % 8][x as usize % 8]) + 0.5) / 64.0 - 0.5;
+                    source.map(|value| value + threshold * spread)
+                }
+            };
+            let color = lookup.get(adjusted.map(|value| value.clamp(0.0, 255.0).round() as u8));
+
+            if dither == Dither::FloydSteinberg {
+                for channel in 0..3 {
+                    let error = adjusted[channel] - f32::from(color[channel]);
+                    current[column + 1][channel] += error * 7.0 / 16.0;
+                    next[column - 1][channel] += error * 3.0 / 16.0;
+                    next[column][channel] += error * 5.0 / 16.0;
+                    next[column + 1][channel] += error / 16.0;
+                }
+            }
+            output.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.iter_mut().for_each(|error| *error = [0.0; 3]);
+    }
+
+    output
+}
+
+/// Draw one square block per palette colour, left to right.
+fn render_swatch(entries: &[PaletteEntry]) -> RgbaImage {
+    let columns = entries.len().max(1) as u32;
+    RgbaImage::from_fn(columns * SWATCH_SIZE, SWATCH_SIZE, |x, _| {
+        let [r, g, b] = entries
+            .get((x / SWATCH_SIZE) as usize)
+            .map_or([0, 0, 0], |entry| entry.color);
+        Rgba([r, g, b, 255])
+    })
+}
+
+fn hex_code(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Show the dominant colours of an image as hex codes and a swatch
+#[poise::command(slash_command)]
+pub async fn palette(
+    ctx: Context<'_>,
+    #[description = "Image to sample"] file: Attachment,
+    #[description = "Number of colours to extract (default 6)"]
+    #[min = 2]
+    #[max = 16]
+    count: Option<u32>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let count = count.unwrap_or(DEFAULT_PALETTE_COLORS) as usize;
+    let img = match download_image(&file, None).await {
+        Ok(img) => img,
+        Err(e) => {
+            let embed = error_embed("❌ Palette Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let result = tokio::task::spawn_blocking(move || {
+        let entries = dominant_colors(&img.to_rgba8(), count);
+        let swatch = encode_image(
+            DynamicImage::ImageRgba8(render_swatch(&entries)),
+            OutputFormat::Png,
+        )?;
+        Ok::<_, Error>((entries, swatch))
+    })
+    .await
+    .map_err(|e| Error::from(format!("Palette task failed: {}", e)))
+    .and_then(|result| result);
+
+    let (entries, swatch) = match result {
+        Ok(output) => output,
+        Err(e) => {
+            let embed = error_embed("❌ Palette Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    if entries.is_empty() {
+        let embed = error_embed("❌ Palette Failed", "The image has no opaque pixels.");
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+    validate_output_size(swatch.len(), "Palette swatch").map_err(Error::from)?;
+
+    let total: usize = entries.iter().map(|entry| entry.count).sum();
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "`{}` — {:.1}%",
+                hex_code(entry.color),
+                entry.count as f64 * 100.0 / total as f64
+            )
+        })
+        .collect();
+    let [r, g, b] = entries[0].color;
+
+    let filename = format!(
+        "{}_palette.png",
+        sanitize_filename(&file_stem(&file.filename))
+    );
+    let embed = CreateEmbed::new()
+        .title("🎨 Colour Palette")
+        .description(format!(
+            "**File:** `{}`\n\n{}",
+            file.filename,
+            lines.join("\n")
+        ))
+        .color((u32::from(r) << 16) | (u32::from(g) << 8) | u32::from(b))
+        .attachment(&filename)
+        .footer(CreateEmbedFooter::new(
+            "Use /convert_image with colors to reduce an image to a palette",
+        ));
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(swatch, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dither, dominant_colors, median_cut, quantize};
+    use crate::conversion::image::{OutputFormat, encode_image};
+    use image::{DynamicImage, Rgba, RgbaImage};
+    use std::collections::HashSet;
+
+    fn gradient(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            Rgba([(x * 255 / width) as u8, (y * 255 / height) as u8, 128, 255])
+        })
+    }
+
+    fn distinct_colors(img: &RgbaImage) -> usize {
+        img.pixels()
+            .map(|pixel| pixel.0)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    #[test]
+    fn median_cut_separates_two_colours_by_population() {
+        let mut pixels = vec![[255, 0, 0]; 30];
+        pixels.extend(vec![[0, 0, 255]; 10]);
+        let entries = median_cut(&pixels, 4);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].color, entries[0].count), ([255, 0, 0], 30));
+        assert_eq!((entries[1].color, entries[1].count), ([0, 0, 255], 10));
+    }
+
+    #[test]
+    fn dominant_colours_ignore_transparent_pixels() {
+        let img = RgbaImage::from_fn(8, 8, |x, _| {
+            if x < 4 {
+                Rgba([10, 200, 30, 255])
+            } else {
+                Rgba([255, 255, 255, 0])
+            }
+        });
+        let entries = dominant_colors(&img, 6);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].color, [10, 200, 30]);
+    }
+
+    #[test]
+    fn quantize_limits_colour_count_for_every_dither_mode() {
+        let img = gradient(64, 64);
+        for dither in [Dither::None, Dither::FloydSteinberg, Dither::Ordered] {
+            let quantized = quantize(&img, 8, dither);
+            assert!(distinct_colors(&quantized) <= 8, "{:?}", dither);
+        }
+    }
+
+    #[test]
+    fn quantized_gif_keeps_the_exact_palette() {
+        let mut img = gradient(32, 32);
+        img.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+        let quantized = quantize(&img, 256, Dither::FloydSteinberg);
+        assert!(distinct_colors(&quantized) <= 256);
+
+        let gif = encode_image(
+            DynamicImage::ImageRgba8(quantized.clone()),
+            OutputFormat::Gif,
+        )
+        .unwrap();
+        let decoded = image::load_from_memory(&gif).unwrap().to_rgba8();
+        assert_eq!(decoded.get_pixel(0, 0)[3], 0);
+        assert_eq!(decoded.get_pixel(5, 7), quantized.get_pixel(5, 7));
+    }
+}
@@ -0,0 +1,452 @@
+use crate::attachments::{
+    sanitize_filename, validate_combined_attachment_size, validate_output_size,
+};
+use crate::commands::resize::resize_rgba;
+use crate::conversion::image::{OutputFormat, download_image, encode_image};
+use crate::conversion::pdf::flatten_onto_white;
+use crate::embeds::error_embed;
+use crate::utils::file_stem;
+use crate::{Context, Error};
+use image::{DynamicImage, GrayImage, Rgb, RgbImage, imageops};
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerceptualHash {
+    /// Each bit records whether an 8×8 thumbnail pixel is brighter than the mean
+    Average,
+    /// Each bit records whether a pixel is brighter than its right neighbour
+    Difference,
+    /// Each bit records whether a low-frequency DCT coefficient is above the median
+    Perceptual,
+}
+
+impl PerceptualHash {
+    pub const ALL: [Self; 3] = [Self::Average, Self::Difference, Self::Perceptual];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Average => "aHash",
+            Self::Difference => "dHash",
+            Self::Perceptual => "pHash",
+        }
+    }
+}
+
+/// Hash distance (out of 64 bits) at or below which two images count as near-duplicates
+pub const DUPLICATE_DISTANCE: u32 = 5;
+/// Hash distance at or below which two images count as visually similar
+pub const SIMILAR_DISTANCE: u32 = 10;
+/// Per-channel difference ignored by the diff image when the caller does not pick one
+const DEFAULT_TOLERANCE: u8 = 16;
+/// Side of the square SSIM window and the step between windows
+const SSIM_WINDOW: u32 = 8;
+const SSIM_STEP: u32 = 4;
+/// Thumbnail side used before the pHash DCT
+const DCT_SIZE: usize = 32;
+
+/// Shrink an image to a grayscale thumbnail, compositing transparency over white.
+fn thumbnail(img: &DynamicImage, width: u32, height: u32) -> GrayImage {
+    let gray = DynamicImage::ImageRgb8(flatten_onto_white(img)).to_luma8();
+    imageops::resize(&gray, width, height, imageops::FilterType::Triangle)
+}
+
+/// Pack 64 booleans into a hash, first bit most significant.
+fn pack_bits(bits: impl Iterator<Item = bool>) -> u64 {
+    bits.take(64)
+        .fold(0, |hash, bit| (hash << 1) | u64::from(bit))
+}
+
+/// The 8×8 lowest-frequency DCT-II coefficients of a square thumbnail.
+fn low_frequencies(thumb: &GrayImage) -> [f32; 64] {
+    let cosines: Vec<[f32; 8]> = (0..DCT_SIZE)
+        .map(|x| {
+            std::array::from_fn(|u| {
+                ((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / (2 * DCT_SIZE) as f32).cos()
+            })
+        })
+        .collect();
+
+    // Transform rows first, keeping only the first eight frequencies
+    let rows: Vec<[f32; 8]> = (0..DCT_SIZE)
+        .map(|y| {
+            std::array::from_fn(|u| {
+                (0..DCT_SIZE)
+                    .map(|x| f32::from(thumb.get_pixel(x as u32, y as u32)[0]) * cosines[x][u])
+                    .sum()
+            })
+        })
+        .collect();
+
+    std::array::from_fn(|index| {
+        let (v, u) = (index / 8, index % 8);
+        (0..DCT_SIZE).map(|y| rows[y][u] * cosines[y][v]).sum()
+    })
+}
+
+/// Compute a 64-bit perceptual hash that survives re-encoding and resizing.
+pub fn perceptual_hash(img: &DynamicImage, kind: PerceptualHash) -> u64 {
+    match kind {
+        PerceptualHash::Average => {
+            let thumb = thumbnail(img, 8, 8);
+            let mean = thumb.pixels().map(|pixel| u32::from(pixel[0])).sum::<u32>() / 64;
+            pack_bits(thumb.pixels().map(|pixel| u32::from(pixel[0]) > mean))
+        }
+        PerceptualHash::Difference => {
+            let thumb = thumbnail(img, 9, 8);
+            pack_bits((0..8).flat_map(|y| {
+                let thumb = &thumb;
+                (0..8).map(move |x| thumb.get_pixel(x, y)[0] > thumb.get_pixel(x + 1, y)[0])
+            }))
+        }
+        PerceptualHash::Perceptual => {
+            let thumb = thumbnail(img, DCT_SIZE as u32, DCT_SIZE as u32);
+            let coefficients = low_frequencies(&thumb);
+            // The DC term only reflects overall brightness, so it is left out of the median
+            let mut ac = coefficients[1..].to_vec();
+            ac.sort_by(f32::total_cmp);
+            let median = ac[ac.len() / 2];
+            pack_bits(coefficients.iter().map(|&value| value > median))
+        }
+    }
+}
+
+/// Number of differing bits between two hashes.
+pub const fn hash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+fn luma(pixel: &Rgb<u8>) -> f64 {
+    0.299 * f64::from(pixel[0]) + 0.587 * f64::from(pixel[1]) + 0.114 * f64::from(pixel[2])
+}
+
+/// Mean structural similarity of two equally sized images, from 0 to 1.
+///
+/// Computed on luma over overlapping square windows.
+pub fn ssim(a: &RgbImage, b: &RgbImage) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let window = SSIM_WINDOW.min(a.width()).min(a.height()).max(1);
+    let starts = |size: u32| {
+        let mut starts: Vec<u32> = (0..=size - window).step_by(SSIM_STEP as usize).collect();
+        if starts.last() != Some(&(size - window)) {
+            starts.push(size - window);
+        }
+        starts
+    };
+
+    let plane = |img: &RgbImage| img.pixels().map(luma).collect::<Vec<f64>>();
+    let (plane_a, plane_b) = (plane(a), plane(b));
+    let width = a.width() as usize;
+    let n = f64::from(window * window);
+
+    let mut total = 0.0;
+    let mut windows = 0u32;
+    for &top in &starts(a.height()) {
+        for &left in &starts(a.width()) {
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) =
+                (0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in top..top + window {
+                let row = y as usize * width;
+                for x in left..left + window {
+                    let (sa, sb) = (plane_a[row + x as usize], plane_b[row + x as usize]);
+                    sum_a += sa;
+                    sum_b += sb;
+                    sum_aa += sa * sa;
+                    sum_bb += sb * sb;
+                    sum_ab += sa * sb;
+                }
+            }
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let covariance = sum_ab / n - mean_a * mean_b;
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a.powi(2) + mean_b.powi(2) + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    total / f64::from(windows)
+}
+
+/// Peak signal-to-noise ratio in decibels, or `None` when the images are identical.
+pub fn psnr(a: &RgbImage, b: &RgbImage) -> Option<f64> {
+    let squared: f64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&a, &b)| (f64::from(a) - f64::from(b)).powi(2))
+        .sum();
+    let mse = squared / a.as_raw().len() as f64;
+    (mse > 0.0).then(|| 10.0 * (255.0 * 255.0 / mse).log10())
+}
+
+/// Highlight pixels that differ by more than `tolerance` in red over a faded copy of `a`.
+///
+/// Returns the diff image and the number of changed pixels.
+pub fn diff_image(a: &RgbImage, b: &RgbImage, tolerance: u8) -> (RgbImage, u64) {
+    let mut changed = 0;
+    let diff = RgbImage::from_fn(a.width(), a.height(), |x, y| {
+        let (pa, pb) = (a.get_pixel(x, y), b.get_pixel(x, y));
+        let delta = (0..3).map(|c| pa[c].abs_diff(pb[c])).max().unwrap_or(0);
+        if delta > tolerance {
+            changed += 1;
+            // Stronger differences are drawn in a more saturated red
+            let fade = 160 - (u32::from(delta) * 160 / 255) as u8;
+            Rgb([255, fade, fade])
+        } else {
+            let faded = 255 - ((255.0 - luma(pa)) / 4.0).round() as u8;
+            Rgb([faded, faded, faded])
+        }
+    });
+    (diff, changed)
+}
+
+/// Everything `/compare_images` reports about a pair of images.
+struct Comparison {
+    hashes: Vec<(PerceptualHash, u64, u64)>,
+    ssim: f64,
+    psnr: Option<f64>,
+    changed: u64,
+    pixels: u64,
+    resized_from: Option<(u32, u32)>,
+    diff: Vec<u8>,
+}
+
+fn compare(first: DynamicImage, second: DynamicImage, tolerance: u8) -> Result<Comparison, Error> {
+    let hashes = PerceptualHash::ALL
+        .iter()
+        .map(|&kind| {
+            (
+                kind,
+                perceptual_hash(&first, kind),
+                perceptual_hash(&second, kind),
+            )
+        })
+        .collect();
+
+    // Pixel metrics need matching sizes, so the second image is scaled to the first
+    let resized_from = (first.width(), first.height()) != (second.width(), second.height());
+    let original = (second.width(), second.height());
+    let second = if resized_from {
+        DynamicImage::ImageRgba8(resize_rgba(
+            &second.to_rgba8(),
+            first.width(),
+            first.height(),
+        )?)
+    } else {
+        second
+    };
+
+    let (a, b) = (flatten_onto_white(&first), flatten_onto_white(&second));
+    let (diff, changed) = diff_image(&a, &b, tolerance);
+
+    Ok(Comparison {
+        hashes,
+        ssim: ssim(&a, &b),
+        psnr: psnr(&a, &b),
+        changed,
+        pixels: u64::from(a.width()) * u64::from(a.height()),
+        resized_from: resized_from.then_some(original),
+        diff: encode_image(DynamicImage::ImageRgb8(diff), OutputFormat::Png)?,
+    })
+}
+
+/// Compare two images with perceptual hashes, SSIM and PSNR
+#[poise::command(slash_command)]
+pub async fn compare_images(
+    ctx: Context<'_>,
+    #[description = "Reference image"] image1: Attachment,
+    #[description = "Image to compare against the reference"] image2: Attachment,
+    #[description = "Per-channel difference to ignore in the diff image (default 16)"]
+    #[min = 0]
+    #[max = 255]
+    tolerance: Option<u8>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let files = [image1, image2];
+    if let Err(message) = validate_combined_attachment_size(&files) {
+        let embed = error_embed("❌ Files Too Large", message);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let mut images = Vec::with_capacity(files.len());
+    for file in &files {
+        match download_image(file, None).await {
+            Ok(img) => images.push(img),
+            Err(e) => {
+                let embed = error_embed(
+                    "❌ Comparison Failed",
+                    format!("`{}`: {}", file.filename, e),
+                );
+                ctx.send(poise::CreateReply::default().embed(embed)).await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let tolerance = tolerance.unwrap_or(DEFAULT_TOLERANCE);
+    let second = images.pop().expect("two images were downloaded");
+    let first = images.pop().expect("two images were downloaded");
+    let result = tokio::task::spawn_blocking(move || compare(first, second, tolerance))
+        .await
+        .map_err(|e| Error::from(format!("Comparison task failed: {}", e)))
+        .and_then(|result| result);
+
+    let comparison = match result {
+        Ok(comparison) => comparison,
+        Err(e) => {
+            let embed = error_embed("❌ Comparison Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(comparison.diff.len(), "Diff image").map_err(Error::from)?;
+
+    let phash_distance = comparison
+        .hashes
+        .iter()
+        .find(|(kind, _, _)| *kind == PerceptualHash::Perceptual)
+        .map_or(64, |&(_, a, b)| hash_distance(a, b));
+    let (verdict, color) = if phash_distance <= DUPLICATE_DISTANCE {
+        ("Near-duplicates", 0x27ae60)
+    } else if phash_distance <= SIMILAR_DISTANCE {
+        ("Visually similar", 0xf1c40f)
+    } else {
+        ("Different images", 0xe67e22)
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title("🔍 Image Comparison")
+        .description(format!(
+            "**Reference:** `{}`\n**Compared:** `{}`\n**Verdict:** {}",
+            files[0].filename, files[1].filename, verdict
+        ))
+        .color(color);
+    for &(kind, a, b) in &comparison.hashes {
+        embed = embed.field(
+            kind.label(),
+            format!(
+                "`{:016x}`\n`{:016x}`\nDistance: {}/64",
+                a,
+                b,
+                hash_distance(a, b)
+            ),
+            true,
+        );
+    }
+    embed = embed
+        .field("SSIM", format!("{:.4}", comparison.ssim), true)
+        .field(
+            "PSNR",
+            comparison
+                .psnr
+                .map_or_else(|| "∞ (identical)".to_string(), |db| format!("{:.2} dB", db)),
+            true,
+        )
+        .field(
+            "Changed pixels",
+            format!(
+                "{:.2}%",
+                comparison.changed as f64 * 100.0 / comparison.pixels.max(1) as f64
+            ),
+            true,
+        );
+
+    let mut footer = format!("Diff tolerance: {}", tolerance);
+    if let Some((width, height)) = comparison.resized_from {
+        footer.push_str(&format!(
+            " • Compared image scaled from {}×{} to match the reference",
+            width, height
+        ));
+    }
+    embed = embed.footer(CreateEmbedFooter::new(footer));
+
+    let filename = format!(
+        "{}_diff.png",
+        sanitize_filename(&file_stem(&files[0].filename))
+    );
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed.attachment(&filename))
+            .attachment(CreateAttachment::bytes(comparison.diff, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PerceptualHash, diff_image, hash_distance, perceptual_hash, psnr, ssim};
+    use image::{DynamicImage, Rgb, RgbImage, imageops};
+
+    /// A smooth scene with a bright disc off-centre, like a simple photo.
+    fn pattern(size: u32) -> RgbImage {
+        RgbImage::from_fn(size, size, |x, y| {
+            let (fx, fy) = (x as f32 / size as f32, y as f32 / size as f32);
+            let disc = if (fx - 0.3).powi(2) + (fy - 0.35).powi(2) < 0.04 {
+                120.0
+            } else {
+                0.0
+            };
+            let value = (fx * 100.0 + fy * 30.0 + disc).min(255.0) as u8;
+            Rgb([value, value / 2, 255 - value])
+        })
+    }
+
+    #[test]
+    fn hashes_survive_resizing() {
+        let original = pattern(256);
+        let smaller = imageops::resize(&original, 100, 100, imageops::FilterType::Triangle);
+        for kind in PerceptualHash::ALL {
+            let a = perceptual_hash(&DynamicImage::ImageRgb8(original.clone()), kind);
+            let b = perceptual_hash(&DynamicImage::ImageRgb8(smaller.clone()), kind);
+            assert!(hash_distance(a, b) <= 4, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn hashes_tell_different_images_apart() {
+        let a = DynamicImage::ImageRgb8(pattern(64));
+        let b = DynamicImage::ImageRgb8(imageops::rotate90(&pattern(64)));
+        let distance = hash_distance(
+            perceptual_hash(&a, PerceptualHash::Perceptual),
+            perceptual_hash(&b, PerceptualHash::Perceptual),
+        );
+        assert!(distance > 10, "{}", distance);
+    }
+
+    #[test]
+    fn identical_images_score_perfectly() {
+        let img = pattern(32);
+        assert!((ssim(&img, &img) - 1.0).abs() < 1e-9);
+        assert_eq!(psnr(&img, &img), None);
+    }
+
+    #[test]
+    fn diff_marks_only_changed_pixels() {
+        let a = pattern(16);
+        let mut b = a.clone();
+        b.put_pixel(3, 4, Rgb([255, 0, 0]));
+        b.put_pixel(
+            5,
+            5,
+            Rgb([
+                a.get_pixel(5, 5)[0] + 4,
+                a.get_pixel(5, 5)[1],
+                a.get_pixel(5, 5)[2],
+            ]),
+        );
+
+        let (diff, changed) = diff_image(&a, &b, 16);
+        assert_eq!(changed, 1);
+        assert_eq!(diff.get_pixel(3, 4)[0], 255);
+        assert!(diff.get_pixel(3, 4)[1] < 160);
+        assert!(psnr(&a, &b).unwrap() > 20.0);
+        assert!(ssim(&a, &b) < 1.0);
+    }
+}
@@ -0,0 +1,426 @@
+use crate::attachments::{sanitize_filename, validate_image_dimensions, validate_output_size};
+use crate::commands::resize::{output_format_for_filename, resize_rgba};
+use crate::conversion::image::{download_image, encode_image};
+use crate::embeds::error_embed;
+use crate::utils::{file_stem, parse_hex_color};
+use crate::{Context, Error};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use poise::ChoiceParameter;
+use poise::serenity_prelude::{Attachment, CreateAttachment, CreateEmbed, CreateEmbedFooter};
+
+/// DejaVu Sans, bundled so text renders the same on every host.
+const FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const MAX_TEXT_CHARS: usize = 200;
+const DEFAULT_OPACITY: u8 = 50;
+const DEFAULT_SCALE: u8 = 25;
+const DEFAULT_COLOR: &str = "#ffffff";
+/// Default font size as a fraction of the base image width
+const DEFAULT_FONT_DIVISOR: u32 = 20;
+const MIN_DEFAULT_FONT_SIZE: u32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Position {
+    #[name = "Top left"]
+    TopLeft,
+    #[name = "Top"]
+    Top,
+    #[name = "Top right"]
+    TopRight,
+    #[name = "Left"]
+    Left,
+    #[name = "Center"]
+    Center,
+    #[name = "Right"]
+    Right,
+    #[name = "Bottom left"]
+    BottomLeft,
+    #[name = "Bottom"]
+    Bottom,
+    #[name = "Bottom right"]
+    BottomRight,
+}
+
+impl Position {
+    /// Horizontal and vertical alignment, each 0 (start), 1 (middle) or 2 (end).
+    const fn alignment(self) -> (u32, u32) {
+        match self {
+            Self::TopLeft => (0, 0),
+            Self::Top => (1, 0),
+            Self::TopRight => (2, 0),
+            Self::Left => (0, 1),
+            Self::Center => (1, 1),
+            Self::Right => (2, 1),
+            Self::BottomLeft => (0, 2),
+            Self::Bottom => (1, 2),
+            Self::BottomRight => (2, 2),
+        }
+    }
+
+    /// Top-left corner at which a `stamp`-sized overlay sits inside `base`.
+    pub fn offset(self, base: (u32, u32), stamp: (u32, u32), margin: u32) -> (i64, i64) {
+        let place = |align: u32, base: u32, stamp: u32| -> i64 {
+            let (base, stamp, margin) = (i64::from(base), i64::from(stamp), i64::from(margin));
+            match align {
+                0 => margin,
+                1 => (base - stamp) / 2,
+                _ => base - stamp - margin,
+            }
+        };
+        let (horizontal, vertical) = self.alignment();
+        (
+            place(horizontal, base.0, stamp.0),
+            place(vertical, base.1, stamp.1),
+        )
+    }
+}
+
+/// Render a single line of text onto a transparent, tightly cropped canvas.
+fn render_text(text: &str, size: f32, color: [u8; 4]) -> Result<RgbaImage, Error> {
+    let font = FontRef::try_from_slice(FONT_DATA)
+        .map_err(|e| Error::from(format!("Failed to load the bundled font: {}", e)))?;
+    let scaled = font.as_scaled(PxScale::from(size));
+
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let mut glyph = scaled.scaled_glyph(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, glyph.id);
+        }
+        glyph.position = point(caret, scaled.ascent());
+        caret += scaled.h_advance(glyph.id);
+        previous = Some(glyph.id);
+        glyphs.push(glyph);
+    }
+
+    let width = caret.ceil().max(1.0) as u32;
+    let height = (scaled.ascent() - scaled.descent()).ceil().max(1.0) as u32;
+    validate_image_dimensions(width, height).map_err(Error::from)?;
+
+    let [r, g, b, a] = color;
+    let mut canvas = RgbaImage::new(width, height);
+    for glyph in glyphs {
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|x, y, coverage| {
+            let x = bounds.min.x as i64 + i64::from(x);
+            let y = bounds.min.y as i64 + i64::from(y);
+            if x < 0 || y < 0 || x >= i64::from(width) || y >= i64::from(height) {
+                return;
+            }
+            let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+            let alpha = (coverage.clamp(0.0, 1.0) * f32::from(a)).round() as u8;
+            *pixel = Rgba([r, g, b, pixel[3].max(alpha)]);
+        });
+    }
+
+    Ok(canvas)
+}
+
+/// Multiply every pixel's alpha by `opacity` percent.
+fn apply_opacity(img: &mut RgbaImage, opacity: u8) {
+    for pixel in img.pixels_mut() {
+        pixel[3] = (u32::from(pixel[3]) * u32::from(opacity) / 100) as u8;
+    }
+}
+
+/// Rotate an image counter-clockwise by `degrees`, growing the canvas to fit.
+///
+/// Samples bilinearly with premultiplied alpha so transparent edges do not
+/// pick up dark fringes.
+fn rotate(img: &RgbaImage, degrees: f32) -> Result<RgbaImage, Error> {
+    if degrees.rem_euclid(360.0) == 0.0 {
+        return Ok(img.clone());
+    }
+
+    let (sin, cos) = (-degrees.to_radians()).sin_cos();
+    let (width, height) = (img.width() as f32, img.height() as f32);
+    // Trim float noise so right angles do not gain an extra row or column.
+    let out_width = (width * cos.abs() + height * sin.abs() - 1e-3)
+        .ceil()
+        .max(1.0) as u32;
+    let out_height = (width * sin.abs() + height * cos.abs() - 1e-3)
+        .ceil()
+        .max(1.0) as u32;
+    validate_image_dimensions(out_width, out_height).map_err(Error::from)?;
+
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let (ocx, ocy) = (out_width as f32 / 2.0, out_height as f32 / 2.0);
+    let texel = |x: i64, y: i64| -> [f32; 4] {
+        if x < 0 || y < 0 || x >= i64::from(img.width()) || y >= i64::from(img.height()) {
+            return [0.0; 4];
+        }
+        let [r, g, b, a] = img.get_pixel(x as u32, y as u32).0;
+        let alpha = f32::from(a) / 255.0;
+        [
+            f32::from(r) * alpha,
+            f32::from(g) * alpha,
+            f32::from(b) * alpha,
+            f32::from(a),
+        ]
+    };
+
+    Ok(RgbaImage::from_fn(out_width, out_height, |x, y| {
+        let dx = x as f32 + 0.5 - ocx;
+        let dy = y as f32 + 0.5 - ocy;
+        // Map the destination pixel back into source space.
+        let sx = dx * cos + dy * sin + cx - 0.5;
+        let sy = -dx * sin + dy * cos + cy - 0.5;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut sample = [0.0f32; 4];
+        for (px, py, weight) in [
+            (x0, y0, (1.0 - fx) * (1.0 - fy)),
+            (x0 + 1, y0, fx * (1.0 - fy)),
+            (x0, y0 + 1, (1.0 - fx) * fy),
+            (x0 + 1, y0 + 1, fx * fy),
+        ] {
+            let texel = texel(px, py);
+            for (channel, value) in sample.iter_mut().zip(texel) {
+                *channel += value * weight;
+            }
+        }
+
+        let alpha = sample[3];
+        if alpha <= 0.0 {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let unpremultiply = |value: f32| (value * 255.0 / alpha).round().clamp(0.0, 255.0) as u8;
+        Rgba([
+            unpremultiply(sample[0]),
+            unpremultiply(sample[1]),
+            unpremultiply(sample[2]),
+            alpha.round().clamp(0.0, 255.0) as u8,
+        ])
+    }))
+}
+
+/// Overlay `stamp` once at `position`, or repeatedly across the whole image.
+fn apply_stamp(base: &mut RgbaImage, stamp: &RgbaImage, position: Position, tile: bool) {
+    let margin = base.width().min(base.height()) / 50;
+    if !tile {
+        let (x, y) = position.offset(base.dimensions(), stamp.dimensions(), margin);
+        imageops::overlay(base, stamp, x, y);
+        return;
+    }
+
+    // Leave half a stamp of space between tiles and stagger alternate rows.
+    let step_x = i64::from(stamp.width() + (stamp.width() / 2).max(margin)).max(1);
+    let step_y = i64::from(stamp.height() + (stamp.height() / 2).max(margin)).max(1);
+    let (width, height) = (i64::from(base.width()), i64::from(base.height()));
+    let mut y = 0;
+    let mut row = 0;
+    while y < height {
+        let mut x = if row % 2 == 0 { 0 } else { -step_x / 2 };
+        while x < width {
+            imageops::overlay(base, stamp, x, y);
+            x += step_x;
+        }
+        y += step_y;
+        row += 1;
+    }
+}
+
+/// Overlay a watermark on an image
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+pub async fn watermark(
+    ctx: Context<'_>,
+    #[description = "Image to watermark"] file: Attachment,
+    #[description = "Watermark text"] text: Option<String>,
+    #[description = "Watermark image, such as a logo (instead of text)"] image: Option<Attachment>,
+    #[description = "Where to place the watermark (defaults to bottom right)"] position: Option<
+        Position,
+    >,
+    #[description = "Watermark opacity in percent (defaults to 50)"]
+    #[min = 1]
+    #[max = 100]
+    opacity: Option<u8>,
+    #[description = "Watermark image width as a percent of the base image (defaults to 25)"]
+    #[min = 1]
+    #[max = 100]
+    scale: Option<u8>,
+    #[description = "Text size in pixels (defaults to 1/20 of the image width)"]
+    #[min = 6]
+    #[max = 512]
+    font_size: Option<u32>,
+    #[description = "Text colour as hex, e.g. #ffffff"] color: Option<String>,
+    #[description = "Rotation in degrees, counter-clockwise"]
+    #[min = -180]
+    #[max = 180]
+    angle: Option<f64>,
+    #[description = "Repeat the watermark across the whole image"] tile: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let text = text
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    let validation = match (&text, &image) {
+        (Some(_), Some(_)) => Err("Provide either `text` or `image`, not both.".to_string()),
+        (None, None) => Err("Provide watermark `text` or an `image` to overlay.".to_string()),
+        (Some(text), None) if text.chars().count() > MAX_TEXT_CHARS => Err(format!(
+            "Watermark text is limited to {} characters.",
+            MAX_TEXT_CHARS
+        )),
+        _ => Ok(()),
+    };
+    let color = color.unwrap_or_else(|| DEFAULT_COLOR.to_string());
+    let parsed_color = parse_hex_color(&color);
+    let validation = validation.and_then(|()| {
+        parsed_color.ok_or_else(|| "Colour must be a hex value such as `#ffffff`.".to_string())
+    });
+    let color = match validation {
+        Ok(color) => color,
+        Err(message) => {
+            let embed = error_embed("❌ Invalid Watermark", message);
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let position = position.unwrap_or(Position::BottomRight);
+    let opacity = opacity.unwrap_or(DEFAULT_OPACITY);
+    let scale = scale.unwrap_or(DEFAULT_SCALE);
+    let angle = angle.unwrap_or(0.0) as f32;
+    let tile = tile.unwrap_or(false);
+
+    let downloads = async {
+        let base = download_image(&file, None).await?;
+        let overlay = match &image {
+            Some(image) => Some(download_image(image, None).await?),
+            None => None,
+        };
+        Ok::<_, Error>((base, overlay))
+    };
+    let (base, overlay) = match downloads.await {
+        Ok(images) => images,
+        Err(e) => {
+            let embed = error_embed("❌ Watermark Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let output_format = output_format_for_filename(&file.filename);
+    let result = tokio::task::spawn_blocking(move || {
+        let mut canvas = base.to_rgba8();
+        let mut stamp = match (overlay, text) {
+            (Some(overlay), _) => {
+                let width = (canvas.width() * u32::from(scale) / 100).max(1);
+                let height = ((u64::from(overlay.height()) * u64::from(width))
+                    / u64::from(overlay.width()))
+                .max(1) as u32;
+                validate_image_dimensions(width, height).map_err(Error::from)?;
+                resize_rgba(&overlay.to_rgba8(), width, height)?
+            }
+            (None, Some(text)) => {
+                let size = font_size.unwrap_or_else(|| {
+                    (canvas.width() / DEFAULT_FONT_DIVISOR).max(MIN_DEFAULT_FONT_SIZE)
+                });
+                render_text(&text, size as f32, color)?
+            }
+            (None, None) => return Err(Error::from("No watermark was provided.")),
+        };
+        apply_opacity(&mut stamp, opacity);
+        let stamp = rotate(&stamp, angle)?;
+        apply_stamp(&mut canvas, &stamp, position, tile);
+        encode_image(DynamicImage::ImageRgba8(canvas), output_format)
+    })
+    .await
+    .map_err(|e| Error::from(format!("Watermark task failed: {}", e)))
+    .and_then(|result| result);
+
+    let output_bytes = match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let embed = error_embed("❌ Watermark Failed", e.to_string());
+            ctx.send(poise::CreateReply::default().embed(embed)).await?;
+            return Ok(());
+        }
+    };
+    validate_output_size(output_bytes.len(), "Watermarked image").map_err(Error::from)?;
+
+    let placement = if tile {
+        "Tiled".to_string()
+    } else {
+        position.name().to_string()
+    };
+    let embed = CreateEmbed::new()
+        .title("✅ Watermark Applied")
+        .description(format!(
+            "**File:** `{}`\n**Watermark:** {}\n**Placement:** {}\n**Opacity:** {}%",
+            file.filename,
+            image.as_ref().map_or_else(
+                || "Text".to_string(),
+                |image| format!("`{}`", image.filename)
+            ),
+            placement,
+            opacity
+        ))
+        .color(0x27ae60)
+        .footer(CreateEmbedFooter::new(format!(
+            "Format: {}",
+            output_format.extension()
+        )));
+
+    let filename = format!(
+        "{}_watermarked.{}",
+        sanitize_filename(&file_stem(&file.filename)),
+        output_format.extension()
+    );
+
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(CreateAttachment::bytes(output_bytes, filename)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Position, apply_stamp, render_text, rotate};
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn positions_respect_margin_and_alignment() {
+        assert_eq!(Position::TopLeft.offset((100, 50), (10, 10), 2), (2, 2));
+        assert_eq!(Position::Center.offset((100, 50), (10, 10), 2), (45, 20));
+        assert_eq!(
+            Position::BottomRight.offset((100, 50), (10, 10), 2),
+            (88, 38)
+        );
+    }
+
+    #[test]
+    fn text_renders_coloured_glyphs() {
+        let text = render_text("Hi", 32.0, [255, 0, 0, 255]).unwrap();
+        assert!(text.width() > 10 && text.height() >= 32);
+        assert!(text.pixels().any(|pixel| pixel.0 == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn quarter_turn_swaps_dimensions() {
+        let img = RgbaImage::from_pixel(20, 10, Rgba([0, 0, 255, 255]));
+        assert_eq!(rotate(&img, 90.0).unwrap().dimensions(), (10, 20));
+        assert_eq!(rotate(&img, 0.0).unwrap().dimensions(), (20, 10));
+    }
+
+    #[test]
+    fn tiling_covers_opposite_corners() {
+        let mut base = RgbaImage::new(100, 100);
+        let stamp = RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 255]));
+        apply_stamp(&mut base, &stamp, Position::Center, true);
+        assert_eq!(base.get_pixel(0, 0)[3], 255);
+        assert!(base.pixels().filter(|pixel| pixel[3] == 255).count() > 1000);
+    }
+}
@@ -9,8 +9,10 @@ type Context<'a> = poise::Context<'a, Data, Error>;
 mod commands;
 mod compression;
 mod conversion;
+mod embeds;
 mod encoding;
 mod encryption;
+mod imaging;
 mod attachments;
 mod utils;
 
@@ -36,8 +38,32 @@ async fn main() {
                 // Image tools
                 commands::resize::resize_image(),
                 conversion::image::convert_image(),
+                conversion::pdf::images_to_pdf(),
+                conversion::pdf::pdf_to_images(),
+                conversion::pdf_tools::pdf(),
+                imaging::compress::compress_image(),
+                imaging::filter::image_filter(),
+                imaging::metadata::image_info(),
+                imaging::metadata::strip_metadata(),
+                imaging::icons::make_icons(),
+                imaging::discord::make_emoji(),
+                imaging::discord::make_sticker(),
+                imaging::discord::make_banner(),
+                imaging::palette::palette(),
+                imaging::similarity::compare_images(),
+                imaging::watermark::watermark(),
+                imaging::background::remove_background(),
+                imaging::background::pad_image(),
+                imaging::grid::collage(),
+                imaging::grid::sprite_sheet(),
+                imaging::grid::slice_sheet(),
                 // Document conversion
                 conversion::document::convert_document(),
+                conversion::render::render(),
+                conversion::extract::extract_text(),
+                conversion::data::convert_data(),
+                conversion::json::json(),
+                conversion::sheet::convert_sheet(),
                 // Compression
                 compression::compress::zip(),
                 compression::decompress::unzip(),
@@ -49,6 +75,9 @@ async fn main() {
                 encoding::base64::base64_decode(),
                 encoding::hex::hex_encode(),
                 encoding::hex::hex_decode(),
+                encoding::qr::qr_encode(),
+                encoding::qr::qr_decode(),
+                encoding::charset::text_encoding(),
                 // Audio
                 commands::metadata::audio_meta(),
             ],
@@ -1,5 +1,10 @@
+use chardetng::EncodingDetector;
+use encoding_rs::Encoding;
 use std::path::Path;
 
+/// Bytes inspected when looking for UTF-16 without a byte order mark
+const UTF16_SNIFF_LEN: usize = 1024;
+
 /// Format file size in bytes to the most readable format
 pub fn format_file_size(bytes: u64) -> String {
     const UNITS: &[&str] = &["bytes", "KB", "MB", "GB", "TB"];
@@ -26,6 +31,58 @@ pub fn format_file_size(bytes: u64) -> String {
     }
 }
 
+/// Parse a human-readable file size such as `200KB`, `1.5 MB` or `4096`.
+///
+/// Units are binary to match [`format_file_size`]; a bare number is bytes.
+pub fn parse_file_size(input: &str) -> Option<u64> {
+    let trimmed = input.trim();
+    let split_at = trimmed
+        .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split_at);
+    let value: f64 = number.parse().ok()?;
+
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" | "byte" | "bytes" => 1.0,
+        "k" | "kb" | "kib" => 1024.0,
+        "m" | "mb" | "mib" => 1024.0 * 1024.0,
+        "g" | "gb" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    let bytes = value * multiplier;
+    if !bytes.is_finite() || bytes < 1.0 || bytes > u64::MAX as f64 {
+        return None;
+    }
+
+    Some(bytes.round() as u64)
+}
+
+/// Parse a `#RGB`, `#RRGGBB` or `#RRGGBBAA` colour into RGBA components.
+///
+/// The leading `#` is optional and missing alpha defaults to fully opaque.
+pub fn parse_hex_color(input: &str) -> Option<[u8; 4]> {
+    let hex = input.trim().trim_start_matches('#');
+    if !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    match hex.len() {
+        3 => {
+            let mut rgba = [255; 4];
+            for (slot, ch) in rgba.iter_mut().zip(hex.chars()) {
+                let value = ch.to_digit(16)? as u8;
+                *slot = value * 17;
+            }
+            Some(rgba)
+        }
+        6 => Some([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Some([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => None,
+    }
+}
+
 /// Extract a display-safe file stem while preserving multi-dot names.
 pub fn file_stem(filename: &str) -> String {
     Path::new(filename)
@@ -36,16 +93,94 @@ pub fn file_stem(filename: &str) -> String {
         .to_string()
 }
 
-/// Determine whether bytes are safe to preview as text in Discord.
-pub fn is_previewable_text(data: &[u8]) -> bool {
-    let Ok(text) = std::str::from_utf8(data) else {
-        return false;
+/// The encoding a file appears to use.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectedEncoding {
+    pub encoding: &'static Encoding,
+    /// Length of the byte order mark at the start, 0 when there is none
+    pub bom_len: usize,
+    /// False when the guess is based on statistics over little text
+    pub confident: bool,
+}
+
+/// UTF-16 without a BOM shows up as a zero byte in every other position.
+fn sniff_utf16(data: &[u8]) -> Option<&'static Encoding> {
+    let sample = &data[..data.len().min(UTF16_SNIFF_LEN) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let zeros_at = |offset: usize| {
+        sample
+            .iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
     };
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    if odd * 2 > pairs && even == 0 {
+        Some(encoding_rs::UTF_16LE)
+    } else if even * 2 > pairs && odd == 0 {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Work out the encoding of text from its BOM, UTF-8 validity, UTF-16 byte
+/// patterns or, failing those, the statistics of legacy encodings.
+pub fn detect_encoding(data: &[u8]) -> DetectedEncoding {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(data) {
+        return DetectedEncoding {
+            encoding,
+            bom_len,
+            confident: true,
+        };
+    }
+    if std::str::from_utf8(data).is_ok() {
+        return DetectedEncoding {
+            encoding: encoding_rs::UTF_8,
+            bom_len: 0,
+            confident: true,
+        };
+    }
+    if let Some(encoding) = sniff_utf16(data) {
+        return DetectedEncoding {
+            encoding,
+            bom_len: 0,
+            confident: true,
+        };
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(data, true);
+    let (encoding, confident) = detector.guess_assess(None, false);
+    DetectedEncoding {
+        encoding,
+        bom_len: 0,
+        confident,
+    }
+}
+
+/// Decode text in its detected encoding, or `None` when it is not valid there.
+pub fn decode_text(data: &[u8]) -> Option<(String, &'static Encoding)> {
+    let detected = detect_encoding(data);
+    let (text, had_errors) = detected
+        .encoding
+        .decode_without_bom_handling(&data[detected.bom_len..]);
+    (!had_errors).then(|| (text.into_owned(), detected.encoding))
+}
 
-    !text.is_empty()
+/// Decode bytes that are safe to preview as text in Discord, in whichever
+/// encoding they appear to use.
+pub fn preview_text(data: &[u8]) -> Option<(String, &'static Encoding)> {
+    let (text, encoding) = decode_text(data)?;
+
+    (!text.is_empty()
         && text
             .chars()
-            .all(|ch| ch == '\n' || ch == '\r' || ch == '\t' || !ch.is_control())
+            .all(|ch| ch == '\n' || ch == '\r' || ch == '\t' || !ch.is_control()))
+    .then_some((text, encoding))
 }
 
 fn detect_file_type_name(data: &[u8]) -> &'static str {
@@ -86,7 +221,10 @@ pub fn detect_file_type(data: &[u8]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{detect_file_type, file_stem, format_file_size, is_previewable_text};
+    use super::{
+        decode_text, detect_encoding, detect_file_type, file_stem, format_file_size,
+        parse_file_size, parse_hex_color, preview_text,
+    };
 
     #[test]
     fn file_stem_preserves_multi_dot_names() {
@@ -95,10 +233,38 @@ mod tests {
     }
 
     #[test]
-    fn previewable_text_rejects_binary() {
-        assert!(is_previewable_text(b"hello\nworld"));
-        assert!(!is_previewable_text(&[0, 159, 146, 150]));
-        assert!(!is_previewable_text(&[0, 1, 2, 3]));
+    fn preview_text_rejects_binary() {
+        assert_eq!(
+            preview_text(b"hello\nworld").map(|(text, _)| text),
+            Some("hello\nworld".to_string())
+        );
+        assert!(preview_text(&[0, 159, 146, 150]).is_none());
+        assert!(preview_text(&[0, 1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn preview_text_decodes_legacy_and_utf16_text() {
+        let (text, encoding) = preview_text(b"\xFF\xFEh\0i\0").unwrap();
+        assert_eq!((text.as_str(), encoding.name()), ("hi", "UTF-16LE"));
+        let (text, _) = preview_text(b"Gr\xfc\xdfe aus K\xf6ln und M\xfcnchen").unwrap();
+        assert_eq!(text, "Grüße aus Köln und München");
+    }
+
+    #[test]
+    fn detects_boms_utf16_and_legacy_encodings() {
+        let utf16: Vec<u8> = "hello wörld"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(detect_encoding(&utf16).encoding, encoding_rs::UTF_16LE);
+        let with_bom = [b"\xFF\xFE".as_slice(), &utf16].concat();
+        assert_eq!(detect_encoding(&with_bom).bom_len, 2);
+        assert_eq!(decode_text(&with_bom).unwrap().0, "hello wörld");
+
+        let latin = b"Caf\xe9 cr\xe8me br\xfbl\xe9e et na\xefvet\xe9, d\xe9j\xe0 vu";
+        let (text, encoding) = decode_text(latin).unwrap();
+        assert_eq!(encoding, encoding_rs::WINDOWS_1252);
+        assert_eq!(text, "Café crème brûlée et naïveté, déjà vu");
     }
 
     #[test]
@@ -108,6 +274,24 @@ mod tests {
         assert_eq!(format_file_size(1536), "1.50 KB");
     }
 
+    #[test]
+    fn parse_file_size_accepts_units_and_bare_bytes() {
+        assert_eq!(parse_file_size("200KB"), Some(200 * 1024));
+        assert_eq!(parse_file_size("1.5 mb"), Some(1536 * 1024));
+        assert_eq!(parse_file_size("4096"), Some(4096));
+        assert_eq!(parse_file_size("12 parsecs"), None);
+        assert_eq!(parse_file_size("0KB"), None);
+    }
+
+    #[test]
+    fn parse_hex_color_accepts_short_long_and_alpha_forms() {
+        assert_eq!(parse_hex_color("#fff"), Some([255, 255, 255, 255]));
+        assert_eq!(parse_hex_color("1e90ff"), Some([30, 144, 255, 255]));
+        assert_eq!(parse_hex_color("#00000080"), Some([0, 0, 0, 128]));
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("red"), None);
+    }
+
     #[test]
     fn detect_file_type_handles_common_cases() {
         assert_eq!(detect_file_type(b"%PDF-sample"), "decoded_document.pdf");